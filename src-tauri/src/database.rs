@@ -3,8 +3,14 @@
 pub mod sqlx_db;
 pub mod redis_manager;
 pub mod connection_manager;
+pub mod value;
+pub mod schema;
+pub mod ddl;
 
 // 重新导出主要类型
 pub use sqlx_db::{DatabaseConfig, DatabaseType, SqlxDatabaseConnection, DatabaseManager};
-pub use redis_manager::{RedisConfig, RedisConnectionManager, RedisManager};
-pub use connection_manager::{UnifiedConnection, UnifiedConnectionManager};
\ No newline at end of file
+pub use redis_manager::{RedisConfig, RedisConnectionManager, RedisExpiry, RedisManager, RedisWrite, RedisWriteValue};
+pub use connection_manager::{UnifiedConnection, UnifiedConnectionManager};
+pub use value::{SqlRowSet, SqlValue};
+pub use schema::{ColumnKind, ColumnSchema, DatabaseSchema, ForeignKeySchema, IndexSchema, TableSchema};
+pub use ddl::{DdlGenerator, DdlScript, DdlWarning, TableDdl};
\ No newline at end of file
@@ -22,11 +22,7 @@ impl UnifiedConnection {
 
     pub fn get_type(&self) -> DatabaseType {
         match self {
-            UnifiedConnection::Sqlx(_) => {
-                // 由于SqlxDatabaseConnection现在是枚举，我们需要其他方式来确定类型
-                // 暂时返回一个默认值，或者我们可以从配置中获取
-                DatabaseType::Sqlite // 临时默认值
-            },
+            UnifiedConnection::Sqlx(conn) => conn.db_type(),
             UnifiedConnection::Redis(_) => DatabaseType::Redis,
         }
     }
@@ -0,0 +1,738 @@
+// 跨方言DDL生成模块 - 根据中立表结构生成目标库的建表、索引和约束语句
+
+use crate::database::schema::quote_literal;
+use crate::database::{ColumnKind, ColumnSchema, DatabaseSchema, DatabaseType, ForeignKeySchema, SqlValue, TableSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ColumnKind as K;
+use DatabaseType::{MySql as MY, PostgreSql as PG, Sqlite as SL};
+
+// 类型映射规则
+struct TypeRule {
+    target: DatabaseType,
+    kind: ColumnKind,
+    // 仅对指定的源方言生效，None 表示所有方言
+    source: Option<DatabaseType>,
+    // 匹配的源基础类型（小写、不含参数），为空表示匹配该分类下所有类型
+    source_types: &'static [&'static str],
+    // 目标类型模板，支持 ({n}) 长度、({p},{s}) 精度/小数位、({fsp}) 秒小数位
+    template: &'static str,
+    // 有损或近似映射时的提示
+    warning: Option<&'static str>,
+}
+
+const fn rule(
+    target: DatabaseType,
+    kind: ColumnKind,
+    source: Option<DatabaseType>,
+    source_types: &'static [&'static str],
+    template: &'static str,
+    warning: Option<&'static str>,
+) -> TypeRule {
+    TypeRule {
+        target,
+        kind,
+        source,
+        source_types,
+        template,
+        warning,
+    }
+}
+
+// 类型映射表，按顺序匹配，先写具体规则再写通用规则
+const TYPE_RULES: &[TypeRule] = &[
+    // -> PostgreSQL
+    rule(PG, K::Bool, Some(MY), &[], "BOOLEAN", Some("TINYINT(1) mapped to BOOLEAN; values other than 0/1 become TRUE")),
+    rule(PG, K::Bool, None, &[], "BOOLEAN", None),
+    rule(PG, K::Integer, Some(SL), &[], "BIGINT", None),
+    rule(PG, K::Integer, None, &["year"], "SMALLINT", Some("YEAR mapped to SMALLINT")),
+    rule(PG, K::Integer, None, &["tinyint", "smallint", "int2", "smallserial"], "SMALLINT", None),
+    rule(PG, K::Integer, None, &["mediumint", "int", "integer", "int4", "serial"], "INTEGER", None),
+    rule(PG, K::Integer, None, &[], "BIGINT", None),
+    rule(PG, K::Float, Some(SL), &[], "DOUBLE PRECISION", None),
+    rule(PG, K::Float, None, &["float", "real", "float4"], "REAL", None),
+    rule(PG, K::Float, None, &[], "DOUBLE PRECISION", None),
+    rule(PG, K::Decimal, None, &[], "NUMERIC({p},{s})", None),
+    rule(PG, K::Text, None, &["char", "character", "bpchar", "nchar"], "CHAR({n})", None),
+    rule(PG, K::Text, None, &["varchar", "character varying", "nvarchar"], "VARCHAR({n})", None),
+    rule(PG, K::Text, Some(MY), &["enum", "set"], "TEXT", Some("ENUM/SET mapped to TEXT; allowed values are not enforced")),
+    rule(PG, K::Text, None, &[], "TEXT", None),
+    rule(PG, K::Bytes, None, &["binary"], "BYTEA", Some("BINARY mapped to BYTEA; fixed-length padding is not preserved")),
+    rule(PG, K::Bytes, None, &[], "BYTEA", None),
+    rule(PG, K::Date, None, &[], "DATE", None),
+    rule(PG, K::Time, None, &["timetz", "time with time zone"], "TIME({fsp}) WITH TIME ZONE", None),
+    rule(PG, K::Time, None, &[], "TIME({fsp})", None),
+    rule(PG, K::DateTime, Some(MY), &["timestamp"], "TIMESTAMP({fsp}) WITH TIME ZONE", None),
+    rule(PG, K::DateTime, None, &["timestamptz", "timestamp with time zone"], "TIMESTAMP({fsp}) WITH TIME ZONE", None),
+    rule(PG, K::DateTime, None, &[], "TIMESTAMP({fsp})", None),
+    rule(PG, K::Json, None, &["jsonb"], "JSONB", None),
+    rule(PG, K::Json, None, &[], "JSONB", Some("JSON mapped to JSONB; key order and duplicate keys are not preserved")),
+    // -> MySQL
+    rule(MY, K::Bool, None, &[], "TINYINT(1)", None),
+    rule(MY, K::Integer, None, &["tinyint"], "TINYINT", None),
+    rule(MY, K::Integer, None, &["smallint", "int2", "smallserial"], "SMALLINT", None),
+    rule(MY, K::Integer, None, &["mediumint"], "MEDIUMINT", None),
+    rule(MY, K::Integer, None, &["year"], "YEAR", None),
+    rule(MY, K::Integer, Some(PG), &["integer", "int", "int4", "serial"], "INT", None),
+    rule(MY, K::Integer, Some(MY), &["int", "integer"], "INT", None),
+    rule(MY, K::Integer, None, &[], "BIGINT", None),
+    rule(MY, K::Float, Some(SL), &[], "DOUBLE", None),
+    rule(MY, K::Float, None, &["float", "real", "float4"], "FLOAT", None),
+    rule(MY, K::Float, None, &[], "DOUBLE", None),
+    rule(MY, K::Decimal, None, &[], "DECIMAL({p},{s})", None),
+    rule(MY, K::Text, None, &["char", "character", "bpchar", "nchar"], "CHAR({n})", None),
+    rule(MY, K::Text, None, &["varchar", "character varying", "nvarchar"], "VARCHAR({n})", None),
+    rule(MY, K::Text, Some(PG), &["uuid"], "CHAR(36)", Some("UUID mapped to CHAR(36)")),
+    rule(MY, K::Text, Some(PG), &["inet", "cidr", "macaddr"], "VARCHAR(64)", Some("network address type mapped to VARCHAR(64)")),
+    rule(MY, K::Text, None, &["tinytext"], "TINYTEXT", None),
+    rule(MY, K::Text, None, &["text"], "TEXT", None),
+    rule(MY, K::Text, None, &["mediumtext"], "MEDIUMTEXT", None),
+    rule(MY, K::Text, None, &[], "LONGTEXT", None),
+    rule(MY, K::Bytes, None, &["binary"], "BINARY({n})", None),
+    rule(MY, K::Bytes, None, &["varbinary"], "VARBINARY({n})", None),
+    rule(MY, K::Bytes, None, &["blob"], "BLOB", None),
+    rule(MY, K::Bytes, None, &[], "LONGBLOB", None),
+    rule(MY, K::Date, None, &[], "DATE", None),
+    rule(MY, K::Time, None, &["timetz", "time with time zone"], "TIME({fsp})", Some("TIME WITH TIME ZONE mapped to TIME; time zone is dropped")),
+    rule(MY, K::Time, None, &[], "TIME({fsp})", None),
+    rule(MY, K::DateTime, Some(MY), &["timestamp"], "TIMESTAMP({fsp})", None),
+    rule(MY, K::DateTime, None, &["timestamptz", "timestamp with time zone"], "DATETIME({fsp})", Some("TIMESTAMP WITH TIME ZONE mapped to DATETIME; time zone offset is dropped")),
+    rule(MY, K::DateTime, None, &[], "DATETIME({fsp})", None),
+    rule(MY, K::Json, Some(PG), &["jsonb"], "JSON", None),
+    rule(MY, K::Json, None, &[], "JSON", None),
+    // -> SQLite
+    rule(SL, K::Bool, None, &[], "BOOLEAN", None),
+    rule(SL, K::Integer, None, &[], "INTEGER", None),
+    rule(SL, K::Float, None, &[], "REAL", None),
+    rule(SL, K::Decimal, None, &[], "NUMERIC", Some("DECIMAL mapped to NUMERIC; precision is not enforced and fractional values are stored as floating point")),
+    rule(SL, K::Text, None, &[], "TEXT", None),
+    rule(SL, K::Bytes, None, &[], "BLOB", None),
+    rule(SL, K::Date, None, &[], "DATE", None),
+    rule(SL, K::Time, None, &[], "TIME", None),
+    rule(SL, K::DateTime, None, &[], "DATETIME", None),
+    rule(SL, K::Json, None, &[], "JSON", None),
+];
+
+// DDL生成过程中的提示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DdlWarning {
+    pub table: String,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+// 单张表的DDL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDdl {
+    pub table: String,
+    pub create_table: String,
+    pub indexes: Vec<String>,
+    pub foreign_keys: Vec<String>,
+    // 数据导入后需要执行的语句（如重置自增序列）
+    pub post_load: Vec<String>,
+    pub warnings: Vec<DdlWarning>,
+}
+
+// 整个库的DDL脚本，表按外键依赖顺序排列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DdlScript {
+    pub tables: Vec<TableDdl>,
+    pub warnings: Vec<DdlWarning>,
+}
+
+impl DdlScript {
+    // 按执行顺序展开所有语句：建表、数据导入后的语句、索引、外键
+    pub fn statements(&self) -> Vec<String> {
+        let mut statements: Vec<String> = self.tables.iter().map(|t| t.create_table.clone()).collect();
+        statements.extend(self.tables.iter().flat_map(|t| t.post_load.clone()));
+        statements.extend(self.tables.iter().flat_map(|t| t.indexes.clone()));
+        statements.extend(self.tables.iter().flat_map(|t| t.foreign_keys.clone()));
+        statements
+    }
+}
+
+// 中立的默认值
+#[derive(Debug, Clone, PartialEq)]
+enum DefaultValue {
+    Number(String),
+    Literal(String),
+    Bool(bool),
+    CurrentTimestamp,
+    CurrentDate,
+    Expression(String),
+}
+
+// DDL生成器
+#[derive(Debug, Clone)]
+pub struct DdlGenerator {
+    source: DatabaseType,
+    target: DatabaseType,
+}
+
+impl DdlGenerator {
+    // 创建从源方言到目标方言的生成器
+    pub fn new(source: DatabaseType, target: DatabaseType) -> Self {
+        Self { source, target }
+    }
+
+    // 生成整个库的DDL
+    pub fn generate(&self, schema: &DatabaseSchema) -> DdlScript {
+        let tables: Vec<TableDdl> = schema
+            .tables_in_dependency_order()
+            .into_iter()
+            .map(|table| self.generate_table(table, Some(schema)))
+            .collect();
+        let warnings = tables.iter().flat_map(|t| t.warnings.clone()).collect();
+        DdlScript { tables, warnings }
+    }
+
+    // 生成单张表的DDL，schema 用于解析外键引用的主键
+    pub fn generate_table(&self, table: &TableSchema, schema: Option<&DatabaseSchema>) -> TableDdl {
+        let mut warnings = Vec::new();
+        let mut warn = |column: Option<&str>, message: String| {
+            warnings.push(DdlWarning {
+                table: table.name.clone(),
+                column: column.map(|c| c.to_string()),
+                message,
+            })
+        };
+
+        let q = |name: &str| self.target.quote_identifier(name);
+
+        let mut inline_primary_key = false;
+        let mut definitions = Vec::new();
+        // 列名（小写）到目标类型的映射，用于生成索引
+        let mut mapped_types = HashMap::new();
+        for column in &table.columns {
+            let (mut data_type, warning) = self.map_type(column, table.is_primary_key(&column.name));
+            mapped_types.insert(column.name.to_lowercase(), data_type.clone());
+            if let Some(message) = warning {
+                warn(Some(&column.name), message);
+            }
+
+            let mut definition = format!("{} ", q(&column.name));
+            if column.auto_increment {
+                match self.auto_increment_clause(table, column) {
+                    Ok(AutoIncrement::Suffix(suffix)) => data_type.push_str(&suffix),
+                    Ok(AutoIncrement::InlinePrimaryKey) => {
+                        data_type = "INTEGER PRIMARY KEY AUTOINCREMENT".to_string();
+                        inline_primary_key = true;
+                    }
+                    Err(message) => warn(Some(&column.name), message),
+                }
+            }
+            definition.push_str(&data_type);
+
+            if !column.nullable && !inline_primary_key_column(inline_primary_key, table, column) {
+                definition.push_str(" NOT NULL");
+            }
+            if let Some(raw) = column.default_value.as_deref() {
+                if !column.auto_increment {
+                    match self.render_default(column, &data_type, raw) {
+                        Ok(Some(default)) => definition.push_str(&format!(" DEFAULT {}", default)),
+                        Ok(None) => {}
+                        Err(message) => warn(Some(&column.name), message),
+                    }
+                }
+            }
+            definitions.push(definition);
+        }
+
+        if !table.primary_key.is_empty() && !inline_primary_key {
+            let columns: Vec<String> = table.primary_key.iter().map(|c| q(c)).collect();
+            definitions.push(format!("PRIMARY KEY ({})", columns.join(", ")));
+        }
+
+        // SQLite 不支持 ALTER TABLE ADD CONSTRAINT，外键写在建表语句中
+        let mut foreign_keys = Vec::new();
+        for fk in &table.foreign_keys {
+            let constraint = self.foreign_key_clause(fk, schema);
+            if self.target == DatabaseType::Sqlite {
+                definitions.push(constraint);
+            } else {
+                foreign_keys.push(format!("ALTER TABLE {} ADD {}", q(&table.name), constraint));
+            }
+        }
+
+        let create_table = format!("CREATE TABLE {} ({})", q(&table.name), definitions.join(", "));
+
+        let mut indexes = Vec::new();
+        for index in &table.indexes {
+            let name = self.index_name(&table.name, &index.name, &index.columns);
+            let columns: Vec<String> = index
+                .columns
+                .iter()
+                .map(|c| {
+                    let column = q(c);
+                    // MySQL 在 TEXT/BLOB 列上建索引必须指定前缀长度
+                    match mapped_types.get(&c.to_lowercase()) {
+                        Some(data_type) if self.target == DatabaseType::MySql && self.needs_prefix_type(data_type) => {
+                            warn(Some(c), format!("index {} uses a 191-character prefix on {}", name, c));
+                            format!("{}(191)", column)
+                        }
+                        _ => column,
+                    }
+                })
+                .collect();
+            indexes.push(format!(
+                "CREATE {}INDEX {} ON {} ({})",
+                if index.unique { "UNIQUE " } else { "" },
+                q(&name),
+                q(&table.name),
+                columns.join(", ")
+            ));
+        }
+
+        let post_load = self.post_load_statements(table);
+
+        TableDdl {
+            table: table.name.clone(),
+            create_table,
+            indexes,
+            foreign_keys,
+            post_load,
+            warnings,
+        }
+    }
+
+    // 映射列类型，返回目标类型和可能的提示
+    pub fn map_type(&self, column: &ColumnSchema, is_key: bool) -> (String, Option<String>) {
+        // 同方言直接沿用原始类型
+        if self.source == self.target {
+            return (column.data_type.clone(), None);
+        }
+
+        let base = base_type(&column.data_type);
+        let matched = TYPE_RULES.iter().find(|r| {
+            r.target == self.target
+                && r.kind == column.kind
+                && r.source.as_ref().map(|s| *s == self.source).unwrap_or(true)
+                && (r.source_types.is_empty() || r.source_types.contains(&base.as_str()))
+        });
+
+        let (template, mut warning) = match matched {
+            Some(r) => (r.template, r.warning.map(|w| w.to_string())),
+            None => ("TEXT", Some(format!("no mapping for {}; stored as TEXT", column.data_type))),
+        };
+        let mut data_type = self.render_template(template, column);
+
+        if column.data_type.ends_with("[]") {
+            warning = Some(format!("array type {} stored as {}", column.data_type, data_type));
+        }
+
+        // 无符号整数提升一级，避免溢出
+        if column.unsigned && column.kind == ColumnKind::Integer && self.target != DatabaseType::MySql {
+            data_type = match data_type.as_str() {
+                "SMALLINT" => "INTEGER".to_string(),
+                "INTEGER" => "BIGINT".to_string(),
+                other => other.to_string(),
+            };
+        }
+
+        if self.target == DatabaseType::MySql {
+            if column.kind == ColumnKind::Decimal {
+                match column.precision {
+                    None => {
+                        data_type = "DECIMAL(65,30)".to_string();
+                        warning = Some("unbounded NUMERIC mapped to DECIMAL(65,30)".to_string());
+                    }
+                    Some(p) if p > 65 => {
+                        data_type = format!("DECIMAL(65,{})", column.scale.unwrap_or(0).min(30));
+                        warning = Some(format!("NUMERIC precision {} exceeds the MySQL limit of 65", p));
+                    }
+                    _ => {}
+                }
+            }
+            // 无长度的 VARCHAR/VARBINARY 在 MySQL 中不合法
+            if data_type == "VARCHAR" || data_type == "CHAR" {
+                data_type = "LONGTEXT".to_string();
+            } else if data_type == "VARBINARY" || data_type == "BINARY" {
+                data_type = "LONGBLOB".to_string();
+            }
+            if let Some(length) = column.length {
+                if column.kind == ColumnKind::Text && length > 16383 && data_type.starts_with("VARCHAR") {
+                    data_type = "LONGTEXT".to_string();
+                    warning = Some(format!("VARCHAR({}) exceeds the MySQL row limit; mapped to LONGTEXT", length));
+                }
+            }
+            // 主键列不能是 TEXT/BLOB
+            if is_key && self.needs_prefix_type(&data_type) {
+                let replacement = if column.kind == ColumnKind::Bytes { "VARBINARY(255)" } else { "VARCHAR(255)" };
+                warning = Some(format!("key column {} mapped to {}; longer values will be rejected", column.data_type, replacement));
+                data_type = replacement.to_string();
+            }
+        }
+
+        (data_type, warning)
+    }
+
+    // 渲染类型模板
+    fn render_template(&self, template: &str, column: &ColumnSchema) -> String {
+        let mut fsp = column.precision;
+        if self.target == DatabaseType::MySql && matches!(column.kind, ColumnKind::Time | ColumnKind::DateTime) {
+            // MySQL 默认秒精度为0，其他库默认是微秒
+            if fsp.is_none() && self.source != DatabaseType::MySql {
+                fsp = Some(6);
+            }
+            fsp = fsp.map(|p| p.min(6));
+        }
+
+        let mut rendered = template.to_string();
+        rendered = match column.length {
+            Some(n) => rendered.replace("({n})", &format!("({})", n)),
+            None => rendered.replace("({n})", ""),
+        };
+        rendered = match column.precision {
+            Some(p) => rendered.replace("({p},{s})", &format!("({},{})", p, column.scale.unwrap_or(0))),
+            None => rendered.replace("({p},{s})", ""),
+        };
+        rendered = match fsp {
+            Some(p) if p > 0 => rendered.replace("({fsp})", &format!("({})", p)),
+            _ => rendered.replace("({fsp})", ""),
+        };
+        rendered
+    }
+
+    // 生成自增列的写法
+    fn auto_increment_clause(
+        &self,
+        table: &TableSchema,
+        column: &ColumnSchema,
+    ) -> Result<AutoIncrement, String> {
+        if column.kind != ColumnKind::Integer {
+            return Err(format!("auto-increment on non-integer type {} dropped", column.data_type));
+        }
+        match self.target {
+            DatabaseType::PostgreSql => {
+                Ok(AutoIncrement::Suffix(" GENERATED BY DEFAULT AS IDENTITY".to_string()))
+            }
+            DatabaseType::MySql => {
+                // MySQL 要求自增列是某个键的第一列
+                let is_first_key = table.primary_key.first().map(|c| c.eq_ignore_ascii_case(&column.name)).unwrap_or(false)
+                    || table.indexes.iter().any(|i| i.columns.first().map(|c| c.eq_ignore_ascii_case(&column.name)).unwrap_or(false));
+                if self.source == DatabaseType::MySql || is_first_key {
+                    Ok(AutoIncrement::Suffix(" AUTO_INCREMENT".to_string()))
+                } else {
+                    Err("AUTO_INCREMENT requires the column to lead a key; dropped".to_string())
+                }
+            }
+            DatabaseType::Sqlite => {
+                if table.primary_key.len() == 1 && table.is_primary_key(&column.name) {
+                    Ok(AutoIncrement::InlinePrimaryKey)
+                } else {
+                    Err("SQLite only supports AUTOINCREMENT on a single-column INTEGER PRIMARY KEY; dropped".to_string())
+                }
+            }
+            DatabaseType::Redis => Err("Redis has no auto-increment columns".to_string()),
+        }
+    }
+
+    // 渲染默认值，Ok(None) 表示没有默认值
+    fn render_default(&self, column: &ColumnSchema, data_type: &str, raw: &str) -> Result<Option<String>, String> {
+        let Some(default) = parse_default(&self.source, column, raw) else {
+            return Ok(None);
+        };
+
+        if self.target == DatabaseType::MySql
+            && self.needs_prefix_type(data_type)
+            && !matches!(default, DefaultValue::Expression(_))
+        {
+            return Err(format!("default {} dropped; MySQL TEXT/BLOB/JSON columns cannot have literal defaults", raw));
+        }
+
+        let rendered = match default {
+            DefaultValue::Number(n) if column.kind == ColumnKind::Bool => self.render_bool(n.trim() != "0"),
+            DefaultValue::Number(n) => n,
+            DefaultValue::Bool(b) => self.render_bool(b),
+            DefaultValue::Literal(s) if column.kind == ColumnKind::Bool => {
+                match SqlValue::Text(s.clone()).as_bool() {
+                    Some(b) => self.render_bool(b),
+                    None => quote_literal(&s),
+                }
+            }
+            DefaultValue::Literal(s) => quote_literal(&s),
+            DefaultValue::CurrentTimestamp => match self.target {
+                // MySQL 要求默认值的秒精度与列一致
+                DatabaseType::MySql if data_type.contains('(') => {
+                    let fsp = data_type.split('(').nth(1).unwrap_or("").trim_end_matches(')');
+                    format!("CURRENT_TIMESTAMP({})", fsp)
+                }
+                _ => "CURRENT_TIMESTAMP".to_string(),
+            },
+            DefaultValue::CurrentDate => match self.target {
+                DatabaseType::MySql => "(CURRENT_DATE)".to_string(),
+                _ => "CURRENT_DATE".to_string(),
+            },
+            DefaultValue::Expression(expr) => {
+                if self.source == self.target {
+                    expr
+                } else {
+                    return Err(format!("default expression {} cannot be translated; dropped", expr));
+                }
+            }
+        };
+        Ok(Some(rendered))
+    }
+
+    fn render_bool(&self, value: bool) -> String {
+        match (&self.target, value) {
+            (DatabaseType::PostgreSql, true) => "TRUE".to_string(),
+            (DatabaseType::PostgreSql, false) => "FALSE".to_string(),
+            (_, true) => "1".to_string(),
+            (_, false) => "0".to_string(),
+        }
+    }
+
+    // 外键约束子句
+    fn foreign_key_clause(&self, fk: &ForeignKeySchema, schema: Option<&DatabaseSchema>) -> String {
+        let q = |name: &str| self.target.quote_identifier(name);
+        let columns: Vec<String> = fk.columns.iter().map(|c| q(c)).collect();
+        let mut referenced = fk.referenced_columns.clone();
+        // SQLite 允许省略被引用列（即引用主键），其他库需要显式写出
+        if referenced.is_empty() {
+            if let Some(table) = schema.and_then(|s| s.table(&fk.referenced_table)) {
+                referenced = table.primary_key.clone();
+            }
+        }
+        let referenced_list = if referenced.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", referenced.iter().map(|c| q(c)).collect::<Vec<_>>().join(", "))
+        };
+
+        let mut clause = format!(
+            "CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}{}",
+            q(&fk.name),
+            columns.join(", "),
+            q(&fk.referenced_table),
+            referenced_list
+        );
+        for (event, action) in [("DELETE", &fk.on_delete), ("UPDATE", &fk.on_update)] {
+            let action = action.to_uppercase();
+            if !action.is_empty() && action != "NO ACTION" {
+                clause.push_str(&format!(" ON {} {}", event, action));
+            }
+        }
+        clause
+    }
+
+    // 生成目标库中的索引名
+    fn index_name(&self, table: &str, name: &str, columns: &[String]) -> String {
+        // SQLite 保留 sqlite_ 前缀，自动索引需要重新命名
+        if name.starts_with("sqlite_") {
+            return format!("{}_{}_key", table, columns.join("_"));
+        }
+        // MySQL 的索引名只在表内唯一，迁移到其他库时加上表名前缀
+        if self.source == DatabaseType::MySql && self.target != DatabaseType::MySql && !name.starts_with(table) {
+            return format!("{}_{}", table, name);
+        }
+        name.to_string()
+    }
+
+    // 判断MySQL类型是否为 TEXT/BLOB/JSON 这类需要索引前缀的类型
+    fn needs_prefix_type(&self, data_type: &str) -> bool {
+        let upper = data_type.to_uppercase();
+        upper.ends_with("TEXT") || upper.ends_with("BLOB") || upper == "JSON"
+    }
+
+    // 数据导入后需要执行的语句
+    fn post_load_statements(&self, table: &TableSchema) -> Vec<String> {
+        if self.target != DatabaseType::PostgreSql {
+            return Vec::new();
+        }
+        // 显式写入自增列后需要把序列推进到当前最大值
+        table
+            .columns
+            .iter()
+            .filter(|c| c.auto_increment && c.kind == ColumnKind::Integer)
+            .map(|c| {
+                let q_table = self.target.quote_identifier(&table.name);
+                let q_column = self.target.quote_identifier(&c.name);
+                format!(
+                    "SELECT setval(pg_get_serial_sequence({}, {}), COALESCE(MAX({}), 0) + 1, false) FROM {}",
+                    quote_literal(&q_table),
+                    quote_literal(&c.name),
+                    q_column,
+                    q_table
+                )
+            })
+            .collect()
+    }
+}
+
+// 自增列的渲染方式
+enum AutoIncrement {
+    Suffix(String),
+    InlinePrimaryKey,
+}
+
+// 内联主键的列不再单独写 NOT NULL
+fn inline_primary_key_column(inline: bool, table: &TableSchema, column: &ColumnSchema) -> bool {
+    inline && column.auto_increment && table.is_primary_key(&column.name)
+}
+
+// 提取小写的基础类型名
+fn base_type(data_type: &str) -> String {
+    let lower = data_type.to_lowercase();
+    let without_params = match lower.find('(') {
+        Some(start) => {
+            let end = lower[start..].find(')').map(|e| start + e + 1).unwrap_or(lower.len());
+            format!("{}{}", &lower[..start], &lower[end..])
+        }
+        None => lower,
+    };
+    without_params
+        .trim_end_matches("[]")
+        .replace(" unsigned", "")
+        .replace(" zerofill", "")
+        .trim()
+        .to_string()
+}
+
+// 把各方言的默认值表达式解析成中立形式
+fn parse_default(source: &DatabaseType, column: &ColumnSchema, raw: &str) -> Option<DefaultValue> {
+    let mut value = raw.trim().to_string();
+    // 去掉外层括号，如 SQLite 的 (0) 或 PostgreSQL 的 (0)::numeric
+    while value.starts_with('(') && value.ends_with(')') && value.len() >= 2 {
+        value = value[1..value.len() - 1].trim().to_string();
+    }
+    if value.is_empty() || value.eq_ignore_ascii_case("null") || value.to_lowercase().starts_with("null::") {
+        return None;
+    }
+
+    let upper = value.to_uppercase();
+    if upper.starts_with("CURRENT_TIMESTAMP")
+        || upper == "NOW()"
+        || upper.starts_with("LOCALTIMESTAMP")
+        || upper == "DATETIME('NOW')"
+    {
+        return Some(DefaultValue::CurrentTimestamp);
+    }
+    if upper == "CURRENT_DATE" || upper == "CURDATE()" || upper == "DATE('NOW')" {
+        return Some(DefaultValue::CurrentDate);
+    }
+    if upper == "TRUE" || upper == "FALSE" {
+        return Some(DefaultValue::Bool(upper == "TRUE"));
+    }
+
+    // 带引号的字面量，可能带有 PostgreSQL 的 ::type 后缀
+    if let Some(quoted) = value.strip_prefix('\'') {
+        let mut literal = String::new();
+        let mut chars = quoted.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    literal.push('\'');
+                    chars.next();
+                } else {
+                    break;
+                }
+            } else {
+                literal.push(c);
+            }
+        }
+        return Some(match column.kind {
+            ColumnKind::Integer | ColumnKind::Float | ColumnKind::Decimal if literal.parse::<f64>().is_ok() => {
+                DefaultValue::Number(literal)
+            }
+            _ => DefaultValue::Literal(literal),
+        });
+    }
+
+    // 带类型转换的数字可能还带括号，如 (0)::numeric
+    let numeric = value.split("::").next().unwrap_or("").trim().trim_start_matches('(').trim_end_matches(')');
+    if numeric.parse::<f64>().is_ok() {
+        return Some(DefaultValue::Number(numeric.to_string()));
+    }
+
+    // MySQL 8 的 COLUMN_DEFAULT 中字符串字面量不带引号
+    if *source == DatabaseType::MySql && !value.contains('(') {
+        return Some(DefaultValue::Literal(value));
+    }
+
+    Some(DefaultValue::Expression(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(source: DatabaseType, target: DatabaseType, data_type: &str, is_key: bool) -> (String, Option<String>) {
+        let column = ColumnSchema::new(&source, "c".to_string(), data_type.to_string(), true);
+        DdlGenerator::new(source, target).map_type(&column, is_key)
+    }
+
+    #[test]
+    fn same_dialect_keeps_declared_type() {
+        assert_eq!(map(MY, MY, "int(11) unsigned", false), ("int(11) unsigned".to_string(), None));
+    }
+
+    #[test]
+    fn maps_types_to_postgres() {
+        let (data_type, warning) = map(MY, PG, "tinyint(1)", false);
+        assert_eq!(data_type, "BOOLEAN");
+        assert!(warning.is_some());
+        assert_eq!(map(MY, PG, "int(11) unsigned", false).0, "BIGINT");
+        assert_eq!(map(MY, PG, "smallint unsigned", false).0, "INTEGER");
+        assert_eq!(map(MY, PG, "varchar(64)", false).0, "VARCHAR(64)");
+        assert_eq!(map(MY, PG, "decimal(10,2)", false).0, "NUMERIC(10,2)");
+        assert_eq!(map(MY, PG, "datetime(3)", false).0, "TIMESTAMP(3)");
+        assert_eq!(map(MY, PG, "timestamp", false).0, "TIMESTAMP WITH TIME ZONE");
+        assert_eq!(map(SL, PG, "INTEGER", false).0, "BIGINT");
+        assert!(map(MY, PG, "json", false).1.is_some());
+    }
+
+    #[test]
+    fn maps_types_to_mysql() {
+        assert_eq!(map(PG, MY, "integer", false).0, "INT");
+        assert_eq!(map(PG, MY, "character varying(20)", false).0, "VARCHAR(20)");
+        assert_eq!(map(PG, MY, "character varying", false).0, "LONGTEXT");
+        assert_eq!(map(PG, MY, "timestamp without time zone", false).0, "DATETIME(6)");
+        assert_eq!(map(PG, MY, "uuid", false), ("CHAR(36)".to_string(), Some("UUID mapped to CHAR(36)".to_string())));
+
+        let (data_type, warning) = map(PG, MY, "numeric", false);
+        assert_eq!(data_type, "DECIMAL(65,30)");
+        assert!(warning.is_some());
+        assert_eq!(map(PG, MY, "numeric(80,40)", false).0, "DECIMAL(65,30)");
+        assert_eq!(map(PG, MY, "varchar(20000)", false).0, "LONGTEXT");
+    }
+
+    #[test]
+    fn mysql_key_columns_avoid_text_and_blob() {
+        let (data_type, warning) = map(PG, MY, "text", true);
+        assert_eq!(data_type, "VARCHAR(255)");
+        assert!(warning.is_some());
+        assert_eq!(map(PG, MY, "bytea", true).0, "VARBINARY(255)");
+        assert_eq!(map(PG, MY, "text", false).0, "TEXT");
+    }
+
+    #[test]
+    fn maps_types_to_sqlite() {
+        assert_eq!(map(PG, SL, "bigint", false).0, "INTEGER");
+        assert_eq!(map(MY, SL, "longblob", false).0, "BLOB");
+        let (data_type, warning) = map(PG, SL, "numeric(10,2)", false);
+        assert_eq!(data_type, "NUMERIC");
+        assert!(warning.is_some());
+    }
+
+    #[test]
+    fn base_type_drops_parameters_and_modifiers() {
+        assert_eq!(base_type("INT(11) UNSIGNED ZEROFILL"), "int");
+        assert_eq!(base_type("character varying(10)[]"), "character varying");
+        assert_eq!(base_type("timestamp(3) with time zone"), "timestamp with time zone");
+    }
+
+    #[test]
+    fn parses_default_values() {
+        let text = ColumnSchema::new(&PG, "c".to_string(), "text".to_string(), true);
+        let int = ColumnSchema::new(&PG, "c".to_string(), "integer".to_string(), true);
+        assert_eq!(parse_default(&PG, &text, "'it''s'::text"), Some(DefaultValue::Literal("it's".to_string())));
+        assert_eq!(parse_default(&PG, &int, "(0)::numeric"), Some(DefaultValue::Number("0".to_string())));
+        assert_eq!(parse_default(&SL, &int, "'42'"), Some(DefaultValue::Number("42".to_string())));
+        assert_eq!(parse_default(&PG, &text, "now()"), Some(DefaultValue::CurrentTimestamp));
+        assert_eq!(parse_default(&SL, &text, "(date('now'))"), Some(DefaultValue::CurrentDate));
+        assert_eq!(parse_default(&PG, &text, "NULL::character varying"), None);
+        assert_eq!(parse_default(&MY, &text, "pending"), Some(DefaultValue::Literal("pending".to_string())));
+        assert_eq!(parse_default(&PG, &text, "gen_random_uuid()"), Some(DefaultValue::Expression("gen_random_uuid()".to_string())));
+    }
+}
@@ -1,6 +1,6 @@
 // Redis 连接管理模块
 
-use redis::{Client, AsyncCommands, aio::MultiplexedConnection};
+use redis::{Client, AsyncCommands, IntoConnectionInfo, aio::MultiplexedConnection};
 use serde::{Serialize, Deserialize};
 
 // Redis 配置
@@ -10,6 +10,118 @@ pub struct RedisConfig {
     pub db: Option<i64>,
 }
 
+// 键的过期方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedisExpiry {
+    // 相对过期时间（秒）
+    After(u64),
+    // 绝对过期时间（Unix秒）
+    At(i64),
+}
+
+// 一次写入的值
+#[derive(Debug, Clone)]
+pub enum RedisWriteValue {
+    // 整体替换哈希的全部字段
+    Hash(Vec<(String, Vec<u8>)>),
+    String(Vec<u8>),
+    // 向有序集合添加或更新一个成员
+    SortedSetMember { member: Vec<u8>, score: f64 },
+}
+
+// 管道中的一次写入
+#[derive(Debug, Clone)]
+pub struct RedisWrite {
+    pub key: String,
+    pub value: RedisWriteValue,
+    pub expiry: Option<RedisExpiry>,
+}
+
+impl RedisWrite {
+    // 写入的数据量（字节），包括键名和字段名
+    pub fn byte_size(&self) -> u64 {
+        let value = match &self.value {
+            RedisWriteValue::Hash(fields) => fields.iter().map(|(field, value)| field.len() + value.len()).sum(),
+            RedisWriteValue::String(value) => value.len(),
+            RedisWriteValue::SortedSetMember { member, .. } => member.len() + 8,
+        };
+        (self.key.len() + value) as u64
+    }
+
+    // 写入对应的命令，值只显示长度，用于演练计划展示
+    pub fn describe(&self) -> String {
+        let mut commands = match &self.value {
+            RedisWriteValue::Hash(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{} <{} bytes>", field, value.len()))
+                    .collect();
+                vec![format!("DEL {}", self.key), format!("HSET {} {}", self.key, fields.join(" "))]
+            }
+            RedisWriteValue::String(value) => match self.expiry {
+                Some(RedisExpiry::After(seconds)) => {
+                    vec![format!("SET {} <{} bytes> EX {}", self.key, value.len(), seconds)]
+                }
+                _ => vec![format!("SET {} <{} bytes>", self.key, value.len())],
+            },
+            RedisWriteValue::SortedSetMember { member, score } => {
+                vec![format!("ZADD {} {} {}", self.key, score, String::from_utf8_lossy(member))]
+            }
+        };
+        match (self.expiry, &self.value) {
+            (Some(RedisExpiry::After(_)), RedisWriteValue::String(_)) | (None, _) => {}
+            (Some(RedisExpiry::After(seconds)), _) => commands.push(format!("EXPIRE {} {}", self.key, seconds)),
+            (Some(RedisExpiry::At(timestamp)), _) => commands.push(format!("EXPIREAT {} {}", self.key, timestamp)),
+        }
+        commands.join("; ")
+    }
+}
+
+// 通过一次管道执行一批写入
+pub async fn write_pipeline(conn: &mut MultiplexedConnection, writes: &[RedisWrite]) -> Result<(), String> {
+    if writes.is_empty() {
+        return Ok(());
+    }
+    let mut pipe = redis::pipe();
+    for write in writes {
+        match &write.value {
+            RedisWriteValue::Hash(fields) => {
+                pipe.cmd("DEL").arg(&write.key).ignore();
+                if !fields.is_empty() {
+                    let cmd = pipe.cmd("HSET").arg(&write.key);
+                    for (field, value) in fields {
+                        cmd.arg(field).arg(value);
+                    }
+                    cmd.ignore();
+                }
+            }
+            RedisWriteValue::String(value) => {
+                let cmd = pipe.cmd("SET").arg(&write.key).arg(value);
+                if let Some(RedisExpiry::After(seconds)) = write.expiry {
+                    cmd.arg("EX").arg(seconds);
+                }
+                cmd.ignore();
+            }
+            RedisWriteValue::SortedSetMember { member, score } => {
+                pipe.cmd("ZADD").arg(&write.key).arg(*score).arg(member).ignore();
+            }
+        }
+        // SET已带上相对过期时间，其他情况单独设置
+        match (write.expiry, &write.value) {
+            (Some(RedisExpiry::After(_)), RedisWriteValue::String(_)) | (None, _) => {}
+            (Some(RedisExpiry::After(seconds)), _) => {
+                pipe.cmd("EXPIRE").arg(&write.key).arg(seconds).ignore();
+            }
+            (Some(RedisExpiry::At(timestamp)), _) => {
+                pipe.cmd("EXPIREAT").arg(&write.key).arg(timestamp).ignore();
+            }
+        }
+    }
+    pipe.query_async::<_, ()>(conn)
+        .await
+        .map_err(|e| format!("Failed to write Redis batch: {}", e))
+}
+
 // Redis 连接管理器
 #[derive(Clone, Debug)]
 pub struct RedisConnectionManager {
@@ -20,9 +132,15 @@ pub struct RedisConnectionManager {
 impl RedisConnectionManager {
     // 创建新的 Redis 连接管理器
     pub fn new(config: RedisConfig) -> Result<Self, String> {
-        println!("创建Redis连接，URL: {}", config.url.replace("redis://", "").replace(":", "****"));
+        log::info!("创建Redis连接，URL: {}", config.url.replace("redis://", "").replace(":", "****"));
         
-        let client = Client::open(&*config.url)
+        let mut connection_info = config.url.as_str().into_connection_info()
+            .map_err(|e| format!("Failed to create Redis client with URL '{}': {}", config.url.replace("redis://", "").replace(":", "****"), e))?;
+        // 配置中指定的数据库编号优先于URL中的编号
+        if let Some(db) = config.db {
+            connection_info.redis.db = db;
+        }
+        let client = Client::open(connection_info)
             .map_err(|e| format!("Failed to create Redis client with URL '{}': {}", config.url.replace("redis://", "").replace(":", "****"), e))?;
         
         Ok(Self {
@@ -90,6 +208,58 @@ impl RedisConnectionManager {
         Ok(())
     }
     
+    // 设置带过期时间的字符串值
+    pub async fn set_string_ex(&self, key: &str, value: &str, seconds: u64) -> Result<(), String> {
+        let mut conn = self.get_connection().await?;
+        
+        conn.set_ex::<_, _, ()>(key, value, seconds)
+            .await
+            .map_err(|e| format!("Failed to set string value: {}", e))?;
+        
+        Ok(())
+    }
+    
+    // 设置哈希的多个字段
+    pub async fn set_hash(&self, key: &str, fields: &[(String, String)]) -> Result<(), String> {
+        let mut conn = self.get_connection().await?;
+        
+        conn.hset_multiple::<_, _, _, ()>(key, fields)
+            .await
+            .map_err(|e| format!("Failed to set hash fields: {}", e))?;
+        
+        Ok(())
+    }
+    
+    // 添加或更新有序集合成员
+    pub async fn add_sorted_set_member(&self, key: &str, member: &str, score: f64) -> Result<(), String> {
+        let mut conn = self.get_connection().await?;
+        
+        conn.zadd::<_, _, _, ()>(key, member, score)
+            .await
+            .map_err(|e| format!("Failed to add sorted set member: {}", e))?;
+        
+        Ok(())
+    }
+    
+    // 设置键的过期时间，键不存在时返回 false
+    pub async fn expire(&self, key: &str, expiry: RedisExpiry) -> Result<bool, String> {
+        let mut conn = self.get_connection().await?;
+        
+        let result: bool = match expiry {
+            RedisExpiry::After(seconds) => conn.expire(key, seconds as i64).await,
+            RedisExpiry::At(timestamp) => conn.expire_at(key, timestamp).await,
+        }
+        .map_err(|e| format!("Failed to set key expiry: {}", e))?;
+        
+        Ok(result)
+    }
+    
+    // 用一个连接通过管道执行一批写入
+    pub async fn write_batch(&self, writes: &[RedisWrite]) -> Result<(), String> {
+        let mut conn = self.get_connection().await?;
+        write_pipeline(&mut conn, writes).await
+    }
+    
     // 删除键
     pub async fn delete_key(&self, key: &str) -> Result<bool, String> {
         let mut conn = self.get_connection().await?;
@@ -0,0 +1,669 @@
+// 数据库结构查询模块
+
+use crate::database::{DatabaseType, SqlRowSet, SqlValue, SqlxDatabaseConnection};
+use serde::{Deserialize, Serialize};
+
+// 列的中立类型分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnKind {
+    Bool,
+    Integer,
+    Float,
+    Decimal,
+    Text,
+    Bytes,
+    Date,
+    Time,
+    DateTime,
+    Json,
+}
+
+impl ColumnKind {
+    // 根据数据库声明的类型推断中立类型
+    pub fn from_declared_type(db_type: &DatabaseType, declared: &str) -> Self {
+        let declared = declared.trim().to_lowercase();
+        let base = declared
+            .split('(')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .trim_end_matches(" unsigned")
+            .to_string();
+
+        if declared == "tinyint(1)" || declared == "bit(1)" || base == "boolean" || base == "bool" {
+            return ColumnKind::Bool;
+        }
+        // 无符号BIGINT可能超出i64范围，按定点数处理
+        if base == "bigint" && declared.contains("unsigned") {
+            return ColumnKind::Decimal;
+        }
+
+        match base.as_str() {
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "int2" | "int4"
+            | "int8" | "serial" | "smallserial" | "bigserial" | "year" => ColumnKind::Integer,
+            "real" | "float" | "double" | "double precision" | "float4" | "float8" => {
+                ColumnKind::Float
+            }
+            "decimal" | "numeric" | "dec" | "fixed" => ColumnKind::Decimal,
+            "blob" | "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary" | "bytea" => {
+                ColumnKind::Bytes
+            }
+            "date" => ColumnKind::Date,
+            "time" | "time without time zone" | "time with time zone" | "timetz" => ColumnKind::Time,
+            "datetime" | "timestamp" | "timestamp without time zone"
+            | "timestamp with time zone" | "timestamptz" => ColumnKind::DateTime,
+            "json" | "jsonb" => ColumnKind::Json,
+            _ if *db_type == DatabaseType::Sqlite => Self::from_sqlite_affinity(&base),
+            _ => ColumnKind::Text,
+        }
+    }
+
+    // SQLite 类型亲和性规则
+    fn from_sqlite_affinity(base: &str) -> Self {
+        if base.contains("int") {
+            ColumnKind::Integer
+        } else if base.contains("char") || base.contains("clob") || base.contains("text") {
+            ColumnKind::Text
+        } else if base.contains("blob") {
+            ColumnKind::Bytes
+        } else if base.contains("real") || base.contains("floa") || base.contains("doub") {
+            ColumnKind::Float
+        } else if base.is_empty() {
+            ColumnKind::Text
+        } else {
+            ColumnKind::Decimal
+        }
+    }
+}
+
+
+// 列结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    // 数据库声明的原始类型
+    pub data_type: String,
+    pub kind: ColumnKind,
+    pub nullable: bool,
+    // 默认值表达式（原样保留源库写法）
+    pub default_value: Option<String>,
+    // 自增 / 标识列
+    pub auto_increment: bool,
+    // 从声明类型中解析出的长度、精度和小数位
+    pub length: Option<u32>,
+    pub precision: Option<u32>,
+    pub scale: Option<u32>,
+    pub unsigned: bool,
+}
+
+impl ColumnSchema {
+    // 根据声明类型创建列，并解析类型参数
+    pub fn new(db_type: &DatabaseType, name: String, data_type: String, nullable: bool) -> Self {
+        let params = parse_type_params(&data_type);
+        let kind = ColumnKind::from_declared_type(db_type, &data_type);
+        let (length, precision, scale) = match kind {
+            ColumnKind::Decimal => (None, params.first().copied(), params.get(1).copied()),
+            ColumnKind::Text | ColumnKind::Bytes => (params.first().copied(), None, None),
+            // 时间类型的参数是秒的小数位
+            ColumnKind::Time | ColumnKind::DateTime => (None, params.first().copied(), None),
+            _ => (None, None, None),
+        };
+        Self {
+            unsigned: data_type.to_lowercase().contains("unsigned"),
+            name,
+            kind,
+            data_type,
+            nullable,
+            default_value: None,
+            auto_increment: false,
+            length,
+            precision,
+            scale,
+        }
+    }
+}
+
+// 索引结构（不包含主键）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSchema {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+// 外键结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeySchema {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+    pub on_update: String,
+    pub on_delete: String,
+}
+
+// 表结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<ColumnSchema>,
+    // 按顺序排列的主键列名
+    pub primary_key: Vec<String>,
+    pub indexes: Vec<IndexSchema>,
+    pub foreign_keys: Vec<ForeignKeySchema>,
+}
+
+impl TableSchema {
+    // 按名称查找列（忽略大小写）
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    // 按主键顺序返回主键列
+    pub fn primary_key_columns(&self) -> Vec<&ColumnSchema> {
+        self.primary_key.iter().filter_map(|name| self.column(name)).collect()
+    }
+
+    // 判断列是否属于主键
+    pub fn is_primary_key(&self, column: &str) -> bool {
+        self.primary_key.iter().any(|c| c.eq_ignore_ascii_case(column))
+    }
+}
+
+// 整个数据库的结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSchema {
+    pub db_type: DatabaseType,
+    pub tables: Vec<TableSchema>,
+}
+
+impl DatabaseSchema {
+    // 按名称查找表（忽略大小写）
+    pub fn table(&self, name: &str) -> Option<&TableSchema> {
+        self.tables.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    // 按外键依赖排序：被引用的表排在前面，循环依赖的表保持原顺序
+    pub fn tables_in_dependency_order(&self) -> Vec<&TableSchema> {
+        let mut ordered: Vec<&TableSchema> = Vec::with_capacity(self.tables.len());
+        let mut remaining: Vec<&TableSchema> = self.tables.iter().collect();
+
+        while !remaining.is_empty() {
+            let (ready, blocked): (Vec<&TableSchema>, Vec<&TableSchema>) =
+                remaining.into_iter().partition(|table| {
+                    table.foreign_keys.iter().all(|fk| {
+                        fk.referenced_table.eq_ignore_ascii_case(&table.name)
+                            || self.table(&fk.referenced_table).is_none()
+                            || ordered.iter().any(|t| t.name.eq_ignore_ascii_case(&fk.referenced_table))
+                    })
+                });
+
+            if ready.is_empty() {
+                // 存在循环依赖，剩余的表按原顺序追加
+                ordered.extend(blocked);
+                break;
+            }
+            ordered.extend(ready);
+            remaining = blocked;
+        }
+
+        ordered
+    }
+}
+
+impl SqlxDatabaseConnection {
+    // 读取整个数据库的结构
+    pub async fn introspect(&self) -> Result<DatabaseSchema, String> {
+        let mut tables = Vec::new();
+        for name in self.list_tables().await? {
+            if let Some(table) = self.get_table_schema(&name).await? {
+                tables.push(table);
+            }
+        }
+        Ok(DatabaseSchema {
+            db_type: self.db_type(),
+            tables,
+        })
+    }
+
+    // 列出当前库中的所有用户表
+    pub async fn list_tables(&self) -> Result<Vec<String>, String> {
+        let sql = match self.db_type() {
+            DatabaseType::Sqlite => {
+                // _bodhi_changelog 是触发器变更捕获的日志表，不属于用户数据
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' \
+                 AND name <> '_bodhi_changelog' ORDER BY name"
+            }
+            DatabaseType::MySql => {
+                "SELECT CAST(TABLE_NAME AS CHAR(255)) AS name FROM information_schema.TABLES \
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE' ORDER BY TABLE_NAME"
+            }
+            DatabaseType::PostgreSql => {
+                "SELECT c.relname::text AS name FROM pg_catalog.pg_class c \
+                 JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE c.relkind IN ('r', 'p') AND n.nspname = current_schema() ORDER BY 1"
+            }
+            DatabaseType::Redis => return Err("Redis has no tables".to_string()),
+        };
+
+        let result = self.fetch_rows(sql, vec![]).await?;
+        Ok(result
+            .rows
+            .iter()
+            .filter_map(|row| row.first().and_then(|v| v.as_string()))
+            .collect())
+    }
+
+    // 获取表结构，表不存在时返回None
+    pub async fn get_table_schema(&self, table: &str) -> Result<Option<TableSchema>, String> {
+        match self.db_type() {
+            DatabaseType::Sqlite => self.sqlite_table_schema(table).await,
+            DatabaseType::MySql => self.mysql_table_schema(table).await,
+            DatabaseType::PostgreSql => self.postgres_table_schema(table).await,
+            DatabaseType::Redis => Err("Redis has no tables".to_string()),
+        }
+    }
+
+    // SQLite：通过 sqlite_master 和 pragma 读取表结构
+    async fn sqlite_table_schema(&self, table: &str) -> Result<Option<TableSchema>, String> {
+        let db_type = DatabaseType::Sqlite;
+        let literal = quote_literal(table);
+
+        let columns_sql = format!(
+            "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info({}) ORDER BY cid",
+            literal
+        );
+        let result = self.fetch_rows(&columns_sql, vec![]).await?;
+        if result.rows.is_empty() {
+            return Ok(None);
+        }
+
+        let mut columns = Vec::new();
+        let mut primary_key = Vec::new();
+        for row in &result.rows {
+            let name = text(&result, row, "name");
+            let mut column = ColumnSchema::new(
+                &db_type,
+                name.clone(),
+                text(&result, row, "type"),
+                !flag(&result, row, "notnull"),
+            );
+            column.default_value = opt_text(&result, row, "dflt_value");
+            let pk = int(&result, row, "pk");
+            if pk > 0 {
+                primary_key.push((pk, name));
+            }
+            columns.push(column);
+        }
+        primary_key.sort();
+        let primary_key: Vec<String> = primary_key.into_iter().map(|(_, name)| name).collect();
+
+        // INTEGER PRIMARY KEY 是 rowid 的别名，会自动分配值
+        if primary_key.len() == 1 {
+            if let Some(column) = columns.iter_mut().find(|c| c.name == primary_key[0]) {
+                column.auto_increment = column.data_type.eq_ignore_ascii_case("integer");
+            }
+        }
+
+        let index_list = self
+            .fetch_rows(
+                &format!("SELECT name, \"unique\", origin FROM pragma_index_list({})", literal),
+                vec![],
+            )
+            .await?;
+        let mut indexes = Vec::new();
+        for row in &index_list.rows {
+            if text(&index_list, row, "origin") == "pk" {
+                continue;
+            }
+            let name = text(&index_list, row, "name");
+            let info = self
+                .fetch_rows(
+                    &format!(
+                        "SELECT name FROM pragma_index_info({}) ORDER BY seqno",
+                        quote_literal(&name)
+                    ),
+                    vec![],
+                )
+                .await?;
+            indexes.push(IndexSchema {
+                columns: info.rows.iter().map(|r| text(&info, r, "name")).collect(),
+                unique: flag(&index_list, row, "unique"),
+                name,
+            });
+        }
+
+        let fk_list = self
+            .fetch_rows(
+                &format!(
+                    "SELECT id, \"table\" AS referenced_table, \"from\" AS column_name, \"to\" AS referenced_column, \
+                     on_update, on_delete FROM pragma_foreign_key_list({}) ORDER BY id, seq",
+                    literal
+                ),
+                vec![],
+            )
+            .await?;
+        let mut foreign_keys: Vec<ForeignKeySchema> = Vec::new();
+        let mut last_id = None;
+        for row in &fk_list.rows {
+            let id = int(&fk_list, row, "id");
+            let referenced_table = text(&fk_list, row, "referenced_table");
+            if last_id != Some(id) {
+                last_id = Some(id);
+                foreign_keys.push(ForeignKeySchema {
+                    name: format!("fk_{}_{}", table, id),
+                    columns: Vec::new(),
+                    referenced_table: referenced_table.clone(),
+                    referenced_columns: Vec::new(),
+                    on_update: text(&fk_list, row, "on_update"),
+                    on_delete: text(&fk_list, row, "on_delete"),
+                });
+            }
+            if let Some(fk) = foreign_keys.last_mut() {
+                fk.columns.push(text(&fk_list, row, "column_name"));
+                // 省略被引用列时表示引用对方主键，这里留空由调用方解析
+                if let Some(column) = opt_text(&fk_list, row, "referenced_column") {
+                    fk.referenced_columns.push(column);
+                }
+            }
+        }
+
+        Ok(Some(TableSchema {
+            name: table.to_string(),
+            columns,
+            primary_key,
+            indexes,
+            foreign_keys,
+        }))
+    }
+
+    // MySQL：通过 information_schema 读取表结构
+    async fn mysql_table_schema(&self, table: &str) -> Result<Option<TableSchema>, String> {
+        let db_type = DatabaseType::MySql;
+        let params = vec![SqlValue::Text(table.to_string())];
+
+        let result = self
+            .fetch_rows(
+                "SELECT CAST(COLUMN_NAME AS CHAR(255)) AS name, CAST(COLUMN_TYPE AS CHAR(255)) AS data_type, \
+                 CAST(IS_NULLABLE = 'YES' AS SIGNED) AS nullable, CAST(COLUMN_DEFAULT AS CHAR) AS default_value, \
+                 CAST(EXTRA AS CHAR(255)) AS extra \
+                 FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+                params.clone(),
+            )
+            .await?;
+        if result.rows.is_empty() {
+            return Ok(None);
+        }
+
+        let columns = result
+            .rows
+            .iter()
+            .map(|row| {
+                let mut column = ColumnSchema::new(
+                    &db_type,
+                    text(&result, row, "name"),
+                    text(&result, row, "data_type"),
+                    flag(&result, row, "nullable"),
+                );
+                column.default_value = opt_text(&result, row, "default_value");
+                column.auto_increment = text(&result, row, "extra")
+                    .to_lowercase()
+                    .contains("auto_increment");
+                column
+            })
+            .collect();
+
+        let stats = self
+            .fetch_rows(
+                "SELECT CAST(INDEX_NAME AS CHAR(255)) AS index_name, CAST(COLUMN_NAME AS CHAR(255)) AS column_name, \
+                 CAST(NON_UNIQUE AS SIGNED) AS non_unique \
+                 FROM information_schema.STATISTICS \
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? \
+                 ORDER BY INDEX_NAME, SEQ_IN_INDEX",
+                params.clone(),
+            )
+            .await?;
+        let mut primary_key = Vec::new();
+        let mut indexes: Vec<IndexSchema> = Vec::new();
+        for row in &stats.rows {
+            let index_name = text(&stats, row, "index_name");
+            // 表达式索引没有列名
+            let Some(column_name) = opt_text(&stats, row, "column_name") else {
+                continue;
+            };
+            if index_name == "PRIMARY" {
+                primary_key.push(column_name);
+                continue;
+            }
+            match indexes.iter_mut().find(|i| i.name == index_name) {
+                Some(index) => index.columns.push(column_name),
+                None => indexes.push(IndexSchema {
+                    unique: !flag(&stats, row, "non_unique"),
+                    name: index_name,
+                    columns: vec![column_name],
+                }),
+            }
+        }
+
+        let fks = self
+            .fetch_rows(
+                "SELECT CAST(k.CONSTRAINT_NAME AS CHAR(255)) AS name, CAST(k.COLUMN_NAME AS CHAR(255)) AS column_name, \
+                 CAST(k.REFERENCED_TABLE_NAME AS CHAR(255)) AS referenced_table, \
+                 CAST(k.REFERENCED_COLUMN_NAME AS CHAR(255)) AS referenced_column, \
+                 CAST(r.UPDATE_RULE AS CHAR(64)) AS on_update, CAST(r.DELETE_RULE AS CHAR(64)) AS on_delete \
+                 FROM information_schema.KEY_COLUMN_USAGE k \
+                 JOIN information_schema.REFERENTIAL_CONSTRAINTS r \
+                 ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+                 AND r.TABLE_NAME = k.TABLE_NAME \
+                 WHERE k.TABLE_SCHEMA = DATABASE() AND k.TABLE_NAME = ? AND k.REFERENCED_TABLE_NAME IS NOT NULL \
+                 ORDER BY k.CONSTRAINT_NAME, k.ORDINAL_POSITION",
+                params,
+            )
+            .await?;
+
+        Ok(Some(TableSchema {
+            name: table.to_string(),
+            columns,
+            primary_key,
+            indexes,
+            foreign_keys: group_foreign_keys(&fks),
+        }))
+    }
+
+    // PostgreSQL：通过 pg_catalog 读取表结构
+    async fn postgres_table_schema(&self, table: &str) -> Result<Option<TableSchema>, String> {
+        let db_type = DatabaseType::PostgreSql;
+        let params = vec![SqlValue::Text(table.to_string())];
+        // 当前schema下的目标表
+        let table_filter = "c.relname::text = $1 AND n.nspname = current_schema()";
+
+        let result = self
+            .fetch_rows(
+                &format!(
+                    "SELECT a.attname::text AS name, pg_catalog.format_type(a.atttypid, a.atttypmod) AS data_type, \
+                     (NOT a.attnotnull)::int AS nullable, \
+                     pg_catalog.pg_get_expr(d.adbin, d.adrelid) AS default_value, \
+                     a.attidentity::text AS identity \
+                     FROM pg_catalog.pg_attribute a \
+                     JOIN pg_catalog.pg_class c ON c.oid = a.attrelid \
+                     JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+                     LEFT JOIN pg_catalog.pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+                     WHERE {} AND a.attnum > 0 AND NOT a.attisdropped ORDER BY a.attnum",
+                    table_filter
+                ),
+                params.clone(),
+            )
+            .await?;
+        if result.rows.is_empty() {
+            return Ok(None);
+        }
+
+        let columns = result
+            .rows
+            .iter()
+            .map(|row| {
+                let mut column = ColumnSchema::new(
+                    &db_type,
+                    text(&result, row, "name"),
+                    text(&result, row, "data_type"),
+                    flag(&result, row, "nullable"),
+                );
+                let default_value = opt_text(&result, row, "default_value");
+                // serial 列的默认值是 nextval(...)，identity 列有 attidentity 标记
+                column.auto_increment = !text(&result, row, "identity").is_empty()
+                    || default_value
+                        .as_deref()
+                        .map(|d| d.starts_with("nextval("))
+                        .unwrap_or(false);
+                if !column.auto_increment {
+                    column.default_value = default_value;
+                }
+                column
+            })
+            .collect();
+
+        let index_rows = self
+            .fetch_rows(
+                &format!(
+                    "SELECT ic.relname::text AS index_name, a.attname::text AS column_name, \
+                     i.indisunique::int AS is_unique, i.indisprimary::int AS is_primary \
+                     FROM pg_catalog.pg_index i \
+                     JOIN pg_catalog.pg_class c ON c.oid = i.indrelid \
+                     JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+                     JOIN pg_catalog.pg_class ic ON ic.oid = i.indexrelid \
+                     CROSS JOIN LATERAL unnest(i.indkey::int2[]) WITH ORDINALITY AS k(attnum, position) \
+                     JOIN pg_catalog.pg_attribute a ON a.attrelid = c.oid AND a.attnum = k.attnum \
+                     WHERE {} ORDER BY ic.relname, k.position",
+                    table_filter
+                ),
+                params.clone(),
+            )
+            .await?;
+        let mut primary_key = Vec::new();
+        let mut indexes: Vec<IndexSchema> = Vec::new();
+        for row in &index_rows.rows {
+            let index_name = text(&index_rows, row, "index_name");
+            let column_name = text(&index_rows, row, "column_name");
+            if flag(&index_rows, row, "is_primary") {
+                primary_key.push(column_name);
+                continue;
+            }
+            match indexes.iter_mut().find(|i| i.name == index_name) {
+                Some(index) => index.columns.push(column_name),
+                None => indexes.push(IndexSchema {
+                    unique: flag(&index_rows, row, "is_unique"),
+                    name: index_name,
+                    columns: vec![column_name],
+                }),
+            }
+        }
+
+        let fks = self
+            .fetch_rows(
+                &format!(
+                    "SELECT con.conname::text AS name, a.attname::text AS column_name, \
+                     rc.relname::text AS referenced_table, ra.attname::text AS referenced_column, \
+                     con.confupdtype::text AS on_update, con.confdeltype::text AS on_delete \
+                     FROM pg_catalog.pg_constraint con \
+                     JOIN pg_catalog.pg_class c ON c.oid = con.conrelid \
+                     JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+                     JOIN pg_catalog.pg_class rc ON rc.oid = con.confrelid \
+                     CROSS JOIN LATERAL unnest(con.conkey, con.confkey) WITH ORDINALITY AS k(attnum, ref_attnum, position) \
+                     JOIN pg_catalog.pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum \
+                     JOIN pg_catalog.pg_attribute ra ON ra.attrelid = con.confrelid AND ra.attnum = k.ref_attnum \
+                     WHERE con.contype = 'f' AND {} ORDER BY con.conname, k.position",
+                    table_filter
+                ),
+                params,
+            )
+            .await?;
+        let mut foreign_keys = group_foreign_keys(&fks);
+        for fk in foreign_keys.iter_mut() {
+            fk.on_update = postgres_fk_action(&fk.on_update);
+            fk.on_delete = postgres_fk_action(&fk.on_delete);
+        }
+
+        Ok(Some(TableSchema {
+            name: table.to_string(),
+            columns,
+            primary_key,
+            indexes,
+            foreign_keys,
+        }))
+    }
+}
+
+// 把按列展开的外键行合并成外键结构
+fn group_foreign_keys(rows: &SqlRowSet) -> Vec<ForeignKeySchema> {
+    let mut foreign_keys: Vec<ForeignKeySchema> = Vec::new();
+    for row in &rows.rows {
+        let name = text(rows, row, "name");
+        if foreign_keys.last().map(|fk| fk.name != name).unwrap_or(true) {
+            foreign_keys.push(ForeignKeySchema {
+                name,
+                columns: Vec::new(),
+                referenced_table: text(rows, row, "referenced_table"),
+                referenced_columns: Vec::new(),
+                on_update: text(rows, row, "on_update"),
+                on_delete: text(rows, row, "on_delete"),
+            });
+        }
+        if let Some(fk) = foreign_keys.last_mut() {
+            fk.columns.push(text(rows, row, "column_name"));
+            fk.referenced_columns.push(text(rows, row, "referenced_column"));
+        }
+    }
+    foreign_keys
+}
+
+// PostgreSQL 外键动作代码转为标准写法
+fn postgres_fk_action(code: &str) -> String {
+    match code {
+        "r" => "RESTRICT",
+        "c" => "CASCADE",
+        "n" => "SET NULL",
+        "d" => "SET DEFAULT",
+        _ => "NO ACTION",
+    }
+    .to_string()
+}
+
+// 解析类型声明中的括号参数，如 decimal(10,2) -> [10, 2]
+fn parse_type_params(data_type: &str) -> Vec<u32> {
+    let Some(start) = data_type.find('(') else {
+        return Vec::new();
+    };
+    let Some(end) = data_type[start..].find(')') else {
+        return Vec::new();
+    };
+    data_type[start + 1..start + end]
+        .split(',')
+        .filter_map(|p| p.trim().parse().ok())
+        .collect()
+}
+
+// 读取文本列，NULL 返回空字符串
+fn text(rows: &SqlRowSet, row: &[SqlValue], column: &str) -> String {
+    opt_text(rows, row, column).unwrap_or_default()
+}
+
+// 读取可空文本列
+fn opt_text(rows: &SqlRowSet, row: &[SqlValue], column: &str) -> Option<String> {
+    rows.get(row, column).and_then(|v| v.as_string())
+}
+
+// 读取整数列
+fn int(rows: &SqlRowSet, row: &[SqlValue], column: &str) -> i64 {
+    rows.get(row, column).and_then(|v| v.as_i64()).unwrap_or(0)
+}
+
+// 读取布尔标记列
+fn flag(rows: &SqlRowSet, row: &[SqlValue], column: &str) -> bool {
+    rows.get(row, column).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+// 把字符串转为SQL字面量
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
@@ -1,8 +1,11 @@
 // 使用sqlx统一数据库操作模块
 
+use crate::database::value::{SqlRowSet, SqlValue};
+use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{any::AnyPoolOptions, any::AnyRow, Any, Pool, Sqlite};
+use sqlx::{any::AnyPoolOptions, any::AnyRow, Any, Column, Pool, Row, Sqlite};
 use std::str::FromStr;
+use tokio::sync::mpsc;
 
 // 数据库类型枚举
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -22,6 +25,31 @@ impl DatabaseType {
             DatabaseType::Redis => "redis",
         }
     }
+
+    // 按数据库方言引用标识符
+    pub fn quote_identifier(&self, name: &str) -> String {
+        match self {
+            DatabaseType::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    // 获取第 index 个参数占位符（从1开始）
+    pub fn placeholder(&self, index: usize) -> String {
+        match self {
+            DatabaseType::PostgreSql => format!("${}", index),
+            _ => "?".to_string(),
+        }
+    }
+
+    // 单条语句允许的最大绑定参数数量
+    pub fn max_bind_params(&self) -> usize {
+        match self {
+            // sqlx 内置的 SQLite 版本上限为 32766
+            DatabaseType::Sqlite => 32766,
+            _ => 65535,
+        }
+    }
 }
 
 impl FromStr for DatabaseType {
@@ -69,11 +97,11 @@ impl SqlxDatabaseConnection {
         let db_type = DatabaseType::from_str(&config.r#type)?;
         let connection_string = Self::build_connection_string(&config, &db_type)?;
 
-        println!(
+        log::info!(
             "创建数据库连接，类型: {:?}, 连接字符串: {}",
             db_type,
             if connection_string.contains("://") {
-                connection_string.replace("://", "://").replace(":", "****")
+                connection_string.replace(":", "****")
             } else {
                 connection_string.clone()
             }
@@ -91,7 +119,7 @@ impl SqlxDatabaseConnection {
                             "Failed to connect to SQLite database: {} (连接字符串: {})",
                             e,
                             if connection_string.contains("://") {
-                                connection_string.replace("://", "://").replace(":", "****")
+                                connection_string.replace(":", "****")
                             } else {
                                 connection_string.clone()
                             }
@@ -100,7 +128,8 @@ impl SqlxDatabaseConnection {
                 Ok(SqlxDatabaseConnection::Sqlite(pool))
             }
             _ => {
-                // 其他数据库使用Any连接池
+                // 其他数据库使用Any连接池，需要先注册驱动
+                sqlx::any::install_default_drivers();
                 let pool = AnyPoolOptions::new()
                     .max_connections(5)
                     .connect(&connection_string)
@@ -110,7 +139,7 @@ impl SqlxDatabaseConnection {
                             "Failed to connect to database: {} (连接字符串: {})",
                             e,
                             if connection_string.contains("://") {
-                                connection_string.replace("://", "://").replace(":", "****")
+                                connection_string.replace(":", "****")
                             } else {
                                 connection_string.clone()
                             }
@@ -145,9 +174,8 @@ impl SqlxDatabaseConnection {
                 // 使用反斜杠，不需要额外的斜杠前缀
                 if cfg!(windows) {
                     // 处理UNC路径格式 (\\?\C:\path\to\file.db)
-                    if db_path.starts_with("\\\\?\\") {
+                    if let Some(standard_path) = db_path.strip_prefix("\\\\?\\") {
                         // 移除UNC前缀，直接使用标准路径
-                        let standard_path = &db_path[4..];
                         Ok(format!("sqlite:{}", standard_path))
                     } else if db_path.starts_with("\\") {
                         // 网络路径
@@ -181,10 +209,10 @@ impl SqlxDatabaseConnection {
                     .as_ref()
                     .ok_or_else(|| "MySQL database is required".to_string())?;
 
-                let ssl_param = if config.ssl { "&ssl-mode=required" } else { "" };
+                let ssl_param = if config.ssl { "?ssl-mode=required" } else { "" };
 
                 Ok(format!(
-                    "mysql://{}{}:{}:{}/{}{}",
+                    "mysql://{}{}{}:{}/{}{}",
                     username, password, host, port, database, ssl_param
                 ))
             }
@@ -211,7 +239,7 @@ impl SqlxDatabaseConnection {
                 let ssl_param = if config.ssl { "?sslmode=require" } else { "" };
 
                 Ok(format!(
-                    "postgresql://{}{}:{}:{}/{}{}",
+                    "postgresql://{}{}{}:{}/{}{}",
                     username, password, host, port, database, ssl_param
                 ))
             }
@@ -222,6 +250,28 @@ impl SqlxDatabaseConnection {
         }
     }
 
+    // 获取连接对应的数据库类型
+    pub fn db_type(&self) -> DatabaseType {
+        match self {
+            SqlxDatabaseConnection::Sqlite(_) => DatabaseType::Sqlite,
+            SqlxDatabaseConnection::Any(pool) => {
+                match pool.connect_options().database_url.scheme() {
+                    "mysql" | "mariadb" => DatabaseType::MySql,
+                    "sqlite" => DatabaseType::Sqlite,
+                    _ => DatabaseType::PostgreSql,
+                }
+            }
+        }
+    }
+
+    // 获取MySQL/PostgreSQL连接的URL，供需要原生协议的功能（如复制流）使用
+    pub fn connection_url(&self) -> Option<String> {
+        match self {
+            SqlxDatabaseConnection::Sqlite(_) => None,
+            SqlxDatabaseConnection::Any(pool) => Some(pool.connect_options().database_url.to_string()),
+        }
+    }
+
     // 测试连接
     pub async fn test_connection(&self) -> Result<(), String> {
         match self {
@@ -346,6 +396,225 @@ impl SqlxDatabaseConnection {
             }
         }
     }
+
+    // 执行带中立值参数的查询并返回结果集
+    pub async fn fetch_rows(&self, sql: &str, params: Vec<SqlValue>) -> Result<SqlRowSet, String> {
+        let mut result = SqlRowSet::default();
+        match self {
+            SqlxDatabaseConnection::Sqlite(pool) => {
+                let mut query = sqlx::query(sql);
+                for param in params {
+                    query = bind_sqlite(query, param);
+                }
+                let rows = query
+                    .fetch_all(pool)
+                    .await
+                    .map_err(|e| format!("Failed to fetch rows: {}", e))?;
+                if let Some(first) = rows.first() {
+                    result.columns = first.columns().iter().map(|c| c.name().to_string()).collect();
+                }
+                for row in &rows {
+                    let values = (0..row.len())
+                        .map(|i| SqlValue::from_sqlite_row(row, i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    result.rows.push(values);
+                }
+            }
+            SqlxDatabaseConnection::Any(pool) => {
+                let mut query = sqlx::query(sql);
+                for param in params {
+                    query = bind_any(query, param);
+                }
+                let rows = query
+                    .fetch_all(pool)
+                    .await
+                    .map_err(|e| format!("Failed to fetch rows: {}", e))?;
+                if let Some(first) = rows.first() {
+                    result.columns = first.columns().iter().map(|c| c.name().to_string()).collect();
+                }
+                for row in &rows {
+                    let values = (0..row.len())
+                        .map(|i| SqlValue::from_any_row(row, i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    result.rows.push(values);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    // 执行带中立值参数的语句并返回影响行数
+    pub async fn execute_values(&self, sql: &str, params: Vec<SqlValue>) -> Result<u64, String> {
+        self.execute_batch(vec![(sql.to_string(), params)]).await
+    }
+
+    // 在同一个事务中执行多条语句，返回总影响行数
+    pub async fn execute_batch(&self, statements: Vec<(String, Vec<SqlValue>)>) -> Result<u64, String> {
+        let mut rows_affected = 0;
+        match self {
+            SqlxDatabaseConnection::Sqlite(pool) => {
+                let mut tx = pool
+                    .begin()
+                    .await
+                    .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+                for (sql, params) in &statements {
+                    let mut query = sqlx::query(sql);
+                    for param in params.iter().cloned() {
+                        query = bind_sqlite(query, param);
+                    }
+                    let result = query
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| format!("Failed to execute SQL: {}", e))?;
+                    rows_affected += result.rows_affected();
+                }
+                tx.commit()
+                    .await
+                    .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            }
+            SqlxDatabaseConnection::Any(pool) => {
+                let mut tx = pool
+                    .begin()
+                    .await
+                    .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+                for (sql, params) in &statements {
+                    let mut query = sqlx::query(sql);
+                    for param in params.iter().cloned() {
+                        query = bind_any(query, param);
+                    }
+                    let result = query
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| format!("Failed to execute SQL: {}", e))?;
+                    rows_affected += result.rows_affected();
+                }
+                tx.commit()
+                    .await
+                    .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            }
+        }
+        Ok(rows_affected)
+    }
+
+    // 以流的方式读取查询结果，按批次通过通道发送，接收端丢弃后停止读取
+    pub fn stream_rows(
+        &self,
+        sql: String,
+        params: Vec<SqlValue>,
+        batch_size: usize,
+    ) -> mpsc::Receiver<Result<SqlRowSet, String>> {
+        // 通道容量较小，消费端处理不过来时读取会暂停
+        let (tx, rx) = mpsc::channel(2);
+        let conn = self.clone();
+        let batch_size = batch_size.max(1);
+        tokio::spawn(async move {
+            if let Err(e) = conn.stream_rows_into(&sql, params, batch_size, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+        rx
+    }
+
+    async fn stream_rows_into(
+        &self,
+        sql: &str,
+        params: Vec<SqlValue>,
+        batch_size: usize,
+        tx: &mpsc::Sender<Result<SqlRowSet, String>>,
+    ) -> Result<(), String> {
+        let mut batch = SqlRowSet::default();
+        match self {
+            SqlxDatabaseConnection::Sqlite(pool) => {
+                let mut query = sqlx::query(sql);
+                for param in params {
+                    query = bind_sqlite(query, param);
+                }
+                let mut rows = query.fetch(pool);
+                while let Some(row) = rows
+                    .try_next()
+                    .await
+                    .map_err(|e| format!("Failed to fetch rows: {}", e))?
+                {
+                    if batch.columns.is_empty() {
+                        batch.columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                    }
+                    let values = (0..row.len())
+                        .map(|i| SqlValue::from_sqlite_row(&row, i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    batch.rows.push(values);
+                    if batch.rows.len() >= batch_size && !send_batch(tx, &mut batch).await {
+                        return Ok(());
+                    }
+                }
+            }
+            SqlxDatabaseConnection::Any(pool) => {
+                let mut query = sqlx::query(sql);
+                for param in params {
+                    query = bind_any(query, param);
+                }
+                let mut rows = query.fetch(pool);
+                while let Some(row) = rows
+                    .try_next()
+                    .await
+                    .map_err(|e| format!("Failed to fetch rows: {}", e))?
+                {
+                    if batch.columns.is_empty() {
+                        batch.columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                    }
+                    let values = (0..row.len())
+                        .map(|i| SqlValue::from_any_row(&row, i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    batch.rows.push(values);
+                    if batch.rows.len() >= batch_size && !send_batch(tx, &mut batch).await {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        if !batch.rows.is_empty() {
+            send_batch(tx, &mut batch).await;
+        }
+        Ok(())
+    }
+}
+
+// 发送当前批次并保留列名，返回接收端是否仍然存在
+async fn send_batch(tx: &mpsc::Sender<Result<SqlRowSet, String>>, batch: &mut SqlRowSet) -> bool {
+    let next = SqlRowSet {
+        columns: batch.columns.clone(),
+        rows: Vec::with_capacity(batch.rows.len()),
+    };
+    tx.send(Ok(std::mem::replace(batch, next))).await.is_ok()
+}
+
+// 为SQLite查询绑定中立值参数
+fn bind_sqlite<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    value: SqlValue,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    match value {
+        SqlValue::Null => query.bind(Option::<String>::None),
+        SqlValue::Bool(b) => query.bind(b),
+        SqlValue::Int(i) => query.bind(i),
+        SqlValue::Float(f) => query.bind(f),
+        SqlValue::Text(s) => query.bind(s),
+        SqlValue::Bytes(b) => query.bind(b),
+    }
+}
+
+// 为Any查询绑定中立值参数
+fn bind_any<'q>(
+    query: sqlx::query::Query<'q, Any, sqlx::any::AnyArguments<'q>>,
+    value: SqlValue,
+) -> sqlx::query::Query<'q, Any, sqlx::any::AnyArguments<'q>> {
+    match value {
+        SqlValue::Null => query.bind(Option::<String>::None),
+        SqlValue::Bool(b) => query.bind(b),
+        SqlValue::Int(i) => query.bind(i),
+        SqlValue::Float(f) => query.bind(f),
+        SqlValue::Text(s) => query.bind(s),
+        SqlValue::Bytes(b) => query.bind(b),
+    }
 }
 
 // 数据库管理器，用于管理多个数据库连接
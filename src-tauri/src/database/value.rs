@@ -0,0 +1,176 @@
+// 跨数据库的中立值模型
+
+use serde::{Deserialize, Serialize};
+use sqlx::{any::AnyRow, sqlite::SqliteRow, Column, Row, TypeInfo, ValueRef};
+
+// 与具体数据库无关的单元格值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SqlValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl SqlValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, SqlValue::Null)
+    }
+
+    // 值占用的字节数，用于估算传输的数据量
+    pub fn byte_size(&self) -> u64 {
+        match self {
+            SqlValue::Null => 0,
+            SqlValue::Bool(_) => 1,
+            SqlValue::Int(_) | SqlValue::Float(_) => 8,
+            SqlValue::Text(s) => s.len() as u64,
+            SqlValue::Bytes(b) => b.len() as u64,
+        }
+    }
+
+    // 转换为字符串表示，NULL 返回 None
+    pub fn as_string(&self) -> Option<String> {
+        match self {
+            SqlValue::Null => None,
+            SqlValue::Bool(b) => Some(b.to_string()),
+            SqlValue::Int(i) => Some(i.to_string()),
+            SqlValue::Float(f) => Some(f.to_string()),
+            SqlValue::Text(s) => Some(s.clone()),
+            SqlValue::Bytes(b) => Some(String::from_utf8_lossy(b).into_owned()),
+        }
+    }
+
+    // 转换为整数，无法转换时返回 None
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            SqlValue::Bool(b) => Some(*b as i64),
+            SqlValue::Int(i) => Some(*i),
+            SqlValue::Float(f) => Some(*f as i64),
+            SqlValue::Text(s) => s.trim().parse().ok(),
+            SqlValue::Bytes(b) => String::from_utf8_lossy(b).trim().parse().ok(),
+            SqlValue::Null => None,
+        }
+    }
+
+    // 转换为布尔值，无法转换时返回 None
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SqlValue::Bool(b) => Some(*b),
+            SqlValue::Int(i) => Some(*i != 0),
+            SqlValue::Float(f) => Some(*f != 0.0),
+            SqlValue::Text(_) | SqlValue::Bytes(_) => {
+                match self.as_string()?.trim().to_lowercase().as_str() {
+                    "1" | "t" | "true" | "y" | "yes" | "on" => Some(true),
+                    "0" | "f" | "false" | "n" | "no" | "off" => Some(false),
+                    _ => None,
+                }
+            }
+            SqlValue::Null => None,
+        }
+    }
+
+    // 从SQLite行中按存储类型读取值
+    pub fn from_sqlite_row(row: &SqliteRow, index: usize) -> Result<Self, String> {
+        let raw = row
+            .try_get_raw(index)
+            .map_err(|e| format!("Failed to read column {}: {}", index, e))?;
+        if raw.is_null() {
+            return Ok(SqlValue::Null);
+        }
+        let type_name = raw.type_info().name().to_string();
+
+        let value = match type_name.as_str() {
+            "INTEGER" | "BOOLEAN" => row.try_get::<i64, _>(index).map(SqlValue::Int),
+            "REAL" => row.try_get::<f64, _>(index).map(SqlValue::Float),
+            "BLOB" => row.try_get::<Vec<u8>, _>(index).map(SqlValue::Bytes),
+            _ => row.try_get::<String, _>(index).map(SqlValue::Text),
+        };
+
+        value.map_err(|e| format!("Failed to decode column {}: {}", index, e))
+    }
+
+    // 从Any行中按列类型读取值
+    pub fn from_any_row(row: &AnyRow, index: usize) -> Result<Self, String> {
+        // 空值在Any驱动中的值类型为NULL，与列声明类型不一致，需先判断（Any驱动的is_null恒为false）
+        let raw = row
+            .try_get_raw(index)
+            .map_err(|e| format!("Failed to read column {}: {}", index, e))?;
+        if raw.type_info().name() == "NULL" {
+            return Ok(SqlValue::Null);
+        }
+        let type_name = row.columns()[index].type_info().name().to_string();
+        let value = match type_name.as_str() {
+            "NULL" => Ok(SqlValue::Null),
+            "BOOLEAN" => row
+                .try_get::<Option<bool>, _>(index)
+                .map(|v| v.map(SqlValue::Bool).unwrap_or(SqlValue::Null)),
+            "SMALLINT" | "INTEGER" | "BIGINT" => row
+                .try_get::<Option<i64>, _>(index)
+                .map(|v| v.map(SqlValue::Int).unwrap_or(SqlValue::Null)),
+            "REAL" => row
+                .try_get::<Option<f32>, _>(index)
+                .map(|v| v.map(|f| SqlValue::Float(f as f64)).unwrap_or(SqlValue::Null)),
+            "DOUBLE" => row
+                .try_get::<Option<f64>, _>(index)
+                .map(|v| v.map(SqlValue::Float).unwrap_or(SqlValue::Null)),
+            "BLOB" => row
+                .try_get::<Option<Vec<u8>>, _>(index)
+                .map(|v| v.map(SqlValue::Bytes).unwrap_or(SqlValue::Null)),
+            _ => row
+                .try_get::<Option<String>, _>(index)
+                .map(|v| v.map(SqlValue::Text).unwrap_or(SqlValue::Null)),
+        };
+
+        value.map_err(|e| format!("Failed to decode column {}: {}", row.columns()[index].name(), e))
+    }
+}
+
+// 查询结果集：列名与按行排列的值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SqlRowSet {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<SqlValue>>,
+}
+
+impl SqlRowSet {
+    // 获取列的下标
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.eq_ignore_ascii_case(name))
+    }
+
+    // 按列名获取某一行的值
+    pub fn get<'a>(&self, row: &'a [SqlValue], name: &str) -> Option<&'a SqlValue> {
+        self.column_index(name).and_then(|i| row.get(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_value_types() {
+        assert_eq!(SqlValue::Float(1.5).as_string(), Some("1.5".to_string()));
+        assert_eq!(SqlValue::Bytes(b"ok".to_vec()).as_string(), Some("ok".to_string()));
+        assert_eq!(SqlValue::Null.as_string(), None);
+        assert_eq!(SqlValue::Text(" -3 ".to_string()).as_i64(), Some(-3));
+        assert_eq!(SqlValue::Text("1.5".to_string()).as_i64(), None);
+        assert_eq!(SqlValue::Bool(true).as_i64(), Some(1));
+        assert_eq!(SqlValue::Text(" Off ".to_string()).as_bool(), Some(false));
+        assert_eq!(SqlValue::Text("T".to_string()).as_bool(), Some(true));
+        assert_eq!(SqlValue::Text("2".to_string()).as_bool(), None);
+        assert_eq!(SqlValue::Float(0.0).as_bool(), Some(false));
+    }
+
+    #[test]
+    fn serializes_untagged() {
+        let values = vec![SqlValue::Null, SqlValue::Bool(true), SqlValue::Int(1), SqlValue::Float(1.5), SqlValue::Text("a".to_string())];
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[null,true,1,1.5,\"a\"]");
+        assert_eq!(serde_json::from_str::<Vec<SqlValue>>(&json).unwrap(), values);
+        assert_eq!(SqlValue::Text("héllo".to_string()).byte_size(), 6);
+    }
+}
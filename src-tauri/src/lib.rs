@@ -2,8 +2,9 @@
 
 // 导入必要的模块
 use chrono::Utc;
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use tokio::sync::RwLock;
 use sqlx::Row; // 导入Row trait用于try_get方法
 
@@ -16,7 +17,18 @@ mod models;
 // 导入 sqlx 数据库类型
 use database::{DatabaseConfig, DatabaseType, UnifiedConnectionManager};
 use migration::{
-    strategy::{CustomSQLMigrationStrategy, FullMigrationStrategy, IncrementalMigrationStrategy},
+    cdc::{mysql_binlog::MySqlBinlogCdcStrategy, pg_logical::PostgresLogicalCdcStrategy, sqlite_changelog::SqliteChangelog},
+    checkpoint::CheckpointStore,
+    progress::{ProgressEvent, PROGRESS_EVENT},
+    task_log::{LogLevel, TaskLogEntry, TaskLogQuery, TASK_LOG_EVENT},
+    redis_table::{RedisFieldMapping, RedisValueFormat},
+    mapping::{ColumnRules, TableMapping},
+    transform::Transformer,
+    table_diff::DiffOptions,
+    strategy::{
+        CustomSQLMigrationStrategy, FullMigrationStrategy, IncrementalMigrationStrategy, RedisMigrationStrategy,
+        RedisToSqlStrategy, SqlToRedisStrategy, WatermarkTable,
+    },
     MigrationStrategyEnum, MigrationTask,
 };
 use models::AppState;
@@ -31,7 +43,7 @@ struct InitResult {
 
 // 初始化应用数据库
 #[tauri::command]
-async fn init_app(state: State<'_, Arc<RwLock<AppState>>>) -> Result<InitResult, String> {
+async fn init_app(app: AppHandle, state: State<'_, Arc<RwLock<AppState>>>) -> Result<InitResult, String> {
     // 应用初始化逻辑
     // 获取应用程序目录
     let exe_path = current_exe().map_err(|e| format!("Failed to get executable path: {}", e))?;
@@ -55,7 +67,7 @@ async fn init_app(state: State<'_, Arc<RwLock<AppState>>>) -> Result<InitResult,
     if !std::path::Path::new(&db_path).exists() {
         std::fs::File::create(&db_path)
             .map_err(|e| format!("Failed to create SQLite database file: {}", e))?;
-        println!("SQLite database file created at: {}", db_path);
+        log::info!("SQLite database file created at: {}", db_path);
     }
 
     // 创建SQLite配置
@@ -84,6 +96,24 @@ async fn init_app(state: State<'_, Arc<RwLock<AppState>>>) -> Result<InitResult,
     // 保存连接ID到应用状态
     state.write().await.sqlite_config_connection_id = Some("config_db".to_string());
 
+    // 从配置库加载迁移任务，之后的任务状态和表复制进度都保存在配置库中
+    let pipeline = state.read().await.migration_pipeline.clone();
+    pipeline.write().await.attach_config_db("config_db").await?;
+
+    // 执行中任务的进度以事件推送给前端
+    let progress_app = app.clone();
+    pipeline.write().await.set_progress_sink(Arc::new(move |event: &ProgressEvent| {
+        if let Err(e) = progress_app.emit(PROGRESS_EVENT, event) {
+            log::warn!("Failed to emit progress event: {}", e);
+        }
+    }));
+    // 任务日志同样实时推送，前端按任务ID过滤
+    pipeline.write().await.set_log_sink(Arc::new(move |entry: &TaskLogEntry| {
+        if let Err(e) = app.emit(TASK_LOG_EVENT, entry) {
+            log::warn!("Failed to emit task log event: {}", e);
+        }
+    }));
+
     Ok(InitResult {
         success: true,
         message: "Application initialized successfully with sqlx".to_string(),
@@ -160,7 +190,7 @@ async fn save_database_config_to_db(
         .ok_or_else(|| "Missing updatedAt".to_string())?;
 
     // 记录时间戳信息用于调试
-    println!(
+    log::info!(
         "保存数据库配置 - ID: {}, 创建时间: {}, 更新时间: {}",
         id, created_at, updated_at
     );
@@ -233,7 +263,7 @@ async fn save_database_config_to_db(
         )
         .await?;
 
-    println!("保存数据库配置影响行数: {}", rows_affected);
+    log::info!("保存数据库配置影响行数: {}", rows_affected);
 
     Ok(id.to_string())
 }
@@ -250,7 +280,7 @@ async fn get_all_database_configs_from_db(
         .clone()
         .ok_or_else(|| "SQLite config connection not initialized".to_string())?;
 
-    println!("获取所有数据库配置 - 连接ID: {}", connection_id);
+    log::info!("获取所有数据库配置 - 连接ID: {}", connection_id);
 
     let conn_manager = app_state.conn_manager.clone();
     drop(app_state); // 释放读取锁
@@ -269,7 +299,7 @@ async fn get_all_database_configs_from_db(
     let table_exists = match connection.fetch_all_sqlite(check_table_sql).await {
         Ok(rows) => !rows.is_empty(),
         Err(e) => {
-            println!("检查表是否存在失败: {}", e);
+            log::warn!("检查表是否存在失败: {}", e);
             false
         }
     };
@@ -304,15 +334,15 @@ async fn get_all_database_configs_from_db(
                     });
                     configs.push(config);
                 }
-                println!("成功加载 {} 个数据库配置", configs.len());
+                log::info!("成功加载 {} 个数据库配置", configs.len());
             },
             Err(e) => {
-                println!("查询数据库配置失败: {}", e);
+                log::warn!("查询数据库配置失败: {}", e);
                 // 如果查询失败，返回空数组而不是错误
             }
         }
     } else {
-        println!("数据库配置表不存在，可能是首次运行");
+        log::info!("数据库配置表不存在，可能是首次运行");
     }
 
     // 直接返回JSON数组
@@ -474,8 +504,83 @@ async fn remove_database_connection(
     Ok(true)
 }
 
+// 获取数据库结构（表、列、主键、索引、外键）
+#[tauri::command]
+async fn get_database_schema(
+    id: String,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<serde_json::Value, String> {
+    let conn_manager = state.read().await.conn_manager.clone();
+    let connection = conn_manager
+        .get_sqlx_connection(&id)
+        .await
+        .ok_or_else(|| format!("No SQL connection found for ID: {}", id))?;
+
+    let schema = connection.introspect().await?;
+    serde_json::to_value(schema).map_err(|e| format!("Failed to serialize schema: {}", e))
+}
+
+// 获取单张表的结构
+#[tauri::command]
+async fn get_table_schema(
+    id: String,
+    table: String,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<Option<serde_json::Value>, String> {
+    let conn_manager = state.read().await.conn_manager.clone();
+    let connection = conn_manager
+        .get_sqlx_connection(&id)
+        .await
+        .ok_or_else(|| format!("No SQL connection found for ID: {}", id))?;
+
+    match connection.get_table_schema(&table).await? {
+        Some(schema) => serde_json::to_value(schema)
+            .map(Some)
+            .map_err(|e| format!("Failed to serialize table schema: {}", e)),
+        None => Ok(None),
+    }
+}
+
 // 迁移任务管理命令
 
+// 表级策略共用的行处理参数，未提供的参数为空
+struct RowOptions {
+    // 表和列的映射规则
+    mappings: Vec<TableMapping>,
+    // 按顺序作用于每一行的转换器
+    transformers: Vec<Transformer>,
+    // 按源表的WHERE条件，只迁移满足条件的行
+    filters: BTreeMap<String, String>,
+}
+
+// 创建任务时与策略无关的选项，未提供的选项为默认值
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct TaskOptions {
+    // 任务完成后自动校验两端的数据
+    verify_on_complete: bool,
+}
+
+// 解析策略参数中的一项，未提供时为默认值
+fn parse_param<T: serde::de::DeserializeOwned + Default>(params: Option<&serde_json::Value>, name: &str) -> Result<T, String> {
+    params
+        .and_then(|p| p.get(name))
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| format!("Invalid {} parameter: {}", name, e))
+        .map(Option::unwrap_or_default)
+}
+
+// 从策略参数中解析映射规则、转换器和过滤条件
+fn parse_row_options(params: Option<&serde_json::Value>) -> Result<RowOptions, String> {
+    Ok(RowOptions {
+        mappings: parse_param(params, "mappings")?,
+        transformers: parse_param(params, "transformers")?,
+        filters: parse_param(params, "filters")?,
+    })
+}
+
 // 创建迁移任务
 #[tauri::command]
 async fn create_migration_task(
@@ -485,18 +590,68 @@ async fn create_migration_task(
     target_db_id: String,
     strategy_type: String,
     strategy_params: Option<serde_json::Value>,
+    options: Option<serde_json::Value>,
     state: State<'_, Arc<RwLock<AppState>>>,
 ) -> Result<String, String> {
+    let options: TaskOptions = match options {
+        Some(options) => serde_json::from_value(options).map_err(|e| format!("Invalid task options: {}", e))?,
+        None => TaskOptions::default(),
+    };
+
     // 根据策略类型创建相应的策略
     let strategy: MigrationStrategyEnum = match strategy_type.as_str() {
-        "full" => MigrationStrategyEnum::Full(FullMigrationStrategy::new()),
+        "full" => {
+            let mut strategy = FullMigrationStrategy::new();
+            if let Some(batch_size) = strategy_params
+                .as_ref()
+                .and_then(|p| p.get("batch_size"))
+                .and_then(|v| v.as_u64())
+            {
+                strategy = strategy.with_batch_size(batch_size as usize);
+            }
+            let options = parse_row_options(strategy_params.as_ref())?;
+            strategy = strategy
+                .with_mappings(options.mappings)
+                .with_transformers(options.transformers)
+                .with_filters(options.filters);
+            MigrationStrategyEnum::Full(strategy)
+        }
         "incremental" => {
-            let last_migration_id = strategy_params.and_then(|p| {
-                p.get("last_migration_id")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            });
-            MigrationStrategyEnum::Incremental(IncrementalMigrationStrategy::new(last_migration_id))
+            let params = strategy_params
+                .as_ref()
+                .ok_or_else(|| "Missing incremental strategy parameters".to_string())?;
+            let tables: Vec<WatermarkTable> = params
+                .get("tables")
+                .cloned()
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| format!("Invalid tables parameter: {}", e))?
+                .unwrap_or_default();
+            // SQLite源表可选择通过触发器变更日志同步
+            let changelog_tables: Vec<String> = params
+                .get("changelog_tables")
+                .cloned()
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| format!("Invalid changelog_tables parameter: {}", e))?
+                .unwrap_or_default();
+            if tables.is_empty() && changelog_tables.is_empty() {
+                return Err("Missing tables or changelog_tables parameter".to_string());
+            }
+            let mut strategy = IncrementalMigrationStrategy::new(tables).with_changelog_tables(changelog_tables);
+            if let Some(batch_size) = params.get("batch_size").and_then(|v| v.as_u64()) {
+                strategy = strategy.with_batch_size(batch_size as usize);
+            }
+            // 水位标记保存在应用配置库中
+            if let Some(config_db_id) = state.read().await.sqlite_config_connection_id.clone() {
+                strategy = strategy.with_checkpoint_db(config_db_id);
+            }
+            let options = parse_row_options(Some(params))?;
+            strategy = strategy
+                .with_mappings(options.mappings)
+                .with_transformers(options.transformers)
+                .with_filters(options.filters);
+            MigrationStrategyEnum::Incremental(strategy)
         }
         "custom_sql" => {
             let source_sql = strategy_params
@@ -507,28 +662,174 @@ async fn create_migration_task(
                 })
                 .ok_or_else(|| "Missing source_sql parameter".to_string())?;
             let target_sql_template = strategy_params
+                .as_ref()
                 .and_then(|p| {
                     p.get("target_sql_template")
                         .and_then(|v| v.as_str().map(|s| s.to_string()))
                 })
                 .ok_or_else(|| "Missing target_sql_template parameter".to_string())?;
-            MigrationStrategyEnum::CustomSQL(CustomSQLMigrationStrategy::new(
+            let mut strategy = CustomSQLMigrationStrategy::new(
                 name.clone(),
                 source_sql.to_string(),
                 target_sql_template.to_string(),
-            ))
+            );
+            if let Some(params) = strategy_params.as_ref() {
+                if let Some(batch_size) = params.get("batch_size").and_then(|v| v.as_u64()) {
+                    strategy = strategy.with_batch_size(batch_size as usize);
+                }
+                if let Some(max_failed_rows) = params.get("max_failed_rows").and_then(|v| v.as_u64()) {
+                    strategy = strategy.with_max_failed_rows(max_failed_rows);
+                }
+                // 源查询结果的列映射规则
+                if let Some(mapping) = params.get("mapping") {
+                    let mapping: ColumnRules = serde_json::from_value(mapping.clone())
+                        .map_err(|e| format!("Invalid mapping parameter: {}", e))?;
+                    strategy = strategy.with_mapping(mapping);
+                }
+                // 源查询结果的行转换器，在列映射规则之前应用
+                strategy = strategy.with_transformers(parse_param(Some(params), "transformers")?);
+            }
+            MigrationStrategyEnum::CustomSQL(strategy)
+        }
+        "mysql_binlog_cdc" => {
+            let mut strategy = MySqlBinlogCdcStrategy::new();
+            if let Some(params) = strategy_params.as_ref() {
+                if let Some(tables) = params.get("tables") {
+                    let tables: Vec<String> = serde_json::from_value(tables.clone())
+                        .map_err(|e| format!("Invalid tables parameter: {}", e))?;
+                    strategy = strategy.with_tables(tables);
+                }
+                if let Some(server_id) = params.get("server_id").and_then(|v| v.as_u64()) {
+                    let server_id = u32::try_from(server_id)
+                        .map_err(|_| format!("Invalid server_id: {}", server_id))?;
+                    strategy = strategy.with_server_id(server_id);
+                }
+                if let Some(initial_copy) = params.get("initial_copy").and_then(|v| v.as_bool()) {
+                    strategy = strategy.with_initial_copy(initial_copy);
+                }
+                if let Some(batch_size) = params.get("batch_size").and_then(|v| v.as_u64()) {
+                    strategy = strategy.with_batch_size(batch_size as usize);
+                }
+            }
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy)
+        }
+        "postgres_logical_cdc" => {
+            let mut strategy = PostgresLogicalCdcStrategy::new();
+            if let Some(params) = strategy_params.as_ref() {
+                if let Some(tables) = params.get("tables") {
+                    let tables: Vec<String> = serde_json::from_value(tables.clone())
+                        .map_err(|e| format!("Invalid tables parameter: {}", e))?;
+                    strategy = strategy.with_tables(tables);
+                }
+                if let Some(slot_name) = params.get("slot_name").and_then(|v| v.as_str()) {
+                    strategy = strategy.with_slot_name(slot_name.to_string());
+                }
+                if let Some(publication) = params.get("publication").and_then(|v| v.as_str()) {
+                    strategy = strategy.with_publication(publication.to_string());
+                }
+                if let Some(initial_copy) = params.get("initial_copy").and_then(|v| v.as_bool()) {
+                    strategy = strategy.with_initial_copy(initial_copy);
+                }
+                if let Some(batch_size) = params.get("batch_size").and_then(|v| v.as_u64()) {
+                    strategy = strategy.with_batch_size(batch_size as usize);
+                }
+            }
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy)
+        }
+        "redis" => {
+            let mut strategy = RedisMigrationStrategy::new();
+            if let Some(params) = strategy_params.as_ref() {
+                if let Some(key_pattern) = params.get("key_pattern").and_then(|v| v.as_str()) {
+                    strategy = strategy.with_key_pattern(key_pattern.to_string());
+                }
+                if let Some(batch_size) = params.get("batch_size").and_then(|v| v.as_u64()) {
+                    strategy = strategy.with_batch_size(batch_size as usize);
+                }
+                if let Some(replace_existing) = params.get("replace_existing").and_then(|v| v.as_bool()) {
+                    strategy = strategy.with_replace_existing(replace_existing);
+                }
+            }
+            MigrationStrategyEnum::Redis(strategy)
+        }
+        "redis_to_sql" => {
+            let params = strategy_params
+                .as_ref()
+                .ok_or_else(|| "Missing redis_to_sql strategy parameters".to_string())?;
+            let table = params
+                .get("table")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing table parameter".to_string())?;
+            let columns: Vec<RedisFieldMapping> = params
+                .get("columns")
+                .cloned()
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| format!("Invalid columns parameter: {}", e))?
+                .unwrap_or_default();
+            let mut strategy = RedisToSqlStrategy::new(table.to_string()).with_columns(columns);
+            if let Some(key_pattern) = params.get("key_pattern").and_then(|v| v.as_str()) {
+                strategy = strategy.with_key_pattern(key_pattern.to_string());
+            }
+            if let Some(key_column) = params.get("key_column").and_then(|v| v.as_str()) {
+                strategy = strategy.with_key_column(key_column.to_string());
+            }
+            if let Some(batch_size) = params.get("batch_size").and_then(|v| v.as_u64()) {
+                strategy = strategy.with_batch_size(batch_size as usize);
+            }
+            if let Some(sample_size) = params.get("sample_size").and_then(|v| v.as_u64()) {
+                strategy = strategy.with_sample_size(sample_size as usize);
+            }
+            MigrationStrategyEnum::RedisToSql(strategy)
+        }
+        "sql_to_redis" => {
+            let params = strategy_params
+                .as_ref()
+                .ok_or_else(|| "Missing sql_to_redis strategy parameters".to_string())?;
+            let source_sql = params
+                .get("source_sql")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing source_sql parameter".to_string())?;
+            let key_template = params
+                .get("key_template")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing key_template parameter".to_string())?;
+            let mut strategy = SqlToRedisStrategy::new(source_sql.to_string(), key_template.to_string());
+            if let Some(value_format) = params.get("value_format") {
+                let value_format: RedisValueFormat = serde_json::from_value(value_format.clone())
+                    .map_err(|e| format!("Invalid value_format parameter: {}", e))?;
+                strategy = strategy.with_value_format(value_format);
+            }
+            let member_template = params.get("member_template").and_then(|v| v.as_str());
+            let score_column = params.get("score_column").and_then(|v| v.as_str());
+            if let (Some(member_template), Some(score_column)) = (member_template, score_column) {
+                strategy = strategy.with_sorted_set(member_template.to_string(), score_column.to_string());
+            }
+            // TTL可以是秒数或模板表达式
+            if let Some(ttl) = params.get("ttl") {
+                let ttl = match ttl {
+                    serde_json::Value::Number(n) => n.to_string(),
+                    other => other.as_str().ok_or_else(|| "Invalid ttl parameter".to_string())?.to_string(),
+                };
+                strategy = strategy.with_ttl(ttl);
+            }
+            if let Some(batch_size) = params.get("batch_size").and_then(|v| v.as_u64()) {
+                strategy = strategy.with_batch_size(batch_size as usize);
+            }
+            MigrationStrategyEnum::SqlToRedis(strategy)
         }
         _ => return Err(format!("Unknown strategy type: {}", strategy_type)),
     };
 
     // 创建任务
-    let task = MigrationTask::new(name, description, source_db_id, target_db_id, strategy);
+    let task = MigrationTask::new(name, description, source_db_id, target_db_id, strategy)
+        .with_verify_on_complete(options.verify_on_complete);
 
     let task_id = task.id.clone();
 
-    // 添加任务到流水线
+    // 添加任务到流水线：复制流水线后释放锁，校验时读取两端表结构不阻塞其他任务命令
     let pipeline = state.read().await.migration_pipeline.clone();
-    pipeline.write().await.add_task(task).await;
+    let pipeline = pipeline.read().await.clone();
+    pipeline.add_task(task).await?;
 
     Ok(task_id)
 }
@@ -555,6 +856,17 @@ async fn pause_migration_task(
     Ok(true)
 }
 
+// 恢复暂停的迁移任务
+#[tauri::command]
+async fn resume_migration_task(
+    id: String,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<bool, String> {
+    let pipeline = state.read().await.migration_pipeline.clone();
+    pipeline.write().await.resume_task(&id).await?;
+    Ok(true)
+}
+
 // 取消迁移任务
 #[tauri::command]
 async fn cancel_migration_task(
@@ -615,8 +927,145 @@ async fn get_all_migration_tasks(
     Ok(tasks_json)
 }
 
+// 演练迁移任务，返回将要创建的表、迁移的行数和执行的语句，不写入目标库
+#[tauri::command]
+async fn plan_migration_task(
+    id: String,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<serde_json::Value, String> {
+    let pipeline = state.read().await.migration_pipeline.clone();
+    // 复制流水线后释放锁，演练期间的查询不阻塞其他任务命令
+    let pipeline = pipeline.read().await.clone();
+    let plan = pipeline.plan_task(&id).await?;
+
+    serde_json::to_value(plan).map_err(|e| format!("Failed to serialize migration plan: {}", e))
+}
+
+// 校验迁移任务的源库和目标库数据，返回逐表的行数和校验和比较结果
+#[tauri::command]
+async fn verify_migration_task(
+    id: String,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<serde_json::Value, String> {
+    let pipeline = state.read().await.migration_pipeline.clone();
+    // 复制流水线后释放锁，全表扫描期间不阻塞暂停、取消等命令
+    let pipeline = pipeline.read().await.clone();
+    let report = pipeline.verify_task(&id).await?;
+
+    serde_json::to_value(report).map_err(|e| format!("Failed to serialize verification report: {}", e))
+}
+
+// 按主键范围比较迁移任务源库和目标库的差异，repair为true时按源表修复目标表
+#[tauri::command]
+async fn diff_migration_task(
+    id: String,
+    options: Option<serde_json::Value>,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let options: DiffOptions = match options {
+        Some(options) => serde_json::from_value(options).map_err(|e| format!("Invalid diff options: {}", e))?,
+        None => DiffOptions::default(),
+    };
+    let pipeline = state.read().await.migration_pipeline.clone();
+    // 复制流水线后释放锁，比较和修复期间不阻塞暂停、取消等命令
+    let pipeline = pipeline.read().await.clone();
+    let diffs = pipeline.diff_task(&id, &options).await?;
+
+    diffs.into_iter()
+        .map(|diff| serde_json::to_value(diff))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to serialize table diffs: {}", e))
+}
+
+// 获取迁移任务的执行记录，最近的在前
+#[tauri::command]
+async fn get_migration_task_runs(
+    task_id: String,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let pipeline = state.read().await.migration_pipeline.clone();
+    let runs = pipeline.read().await.get_task_runs(&task_id).await?;
+
+    runs.into_iter()
+        .map(|run| serde_json::to_value(run))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to serialize task runs: {}", e))
+}
+
+// 获取一次迁移任务执行的记录
+#[tauri::command]
+async fn get_migration_task_run(
+    run_id: String,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<Option<serde_json::Value>, String> {
+    let pipeline = state.read().await.migration_pipeline.clone();
+    let run = pipeline.read().await.get_task_run(&run_id).await?;
+
+    run.map(|run| serde_json::to_value(run))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize task run: {}", e))
+}
+
+// 分页查询迁移任务的日志，最新的在前，可按最低级别和执行记录过滤
+#[tauri::command]
+async fn get_migration_task_logs(
+    task_id: String,
+    min_level: Option<LogLevel>,
+    run_id: Option<String>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<serde_json::Value, String> {
+    let pipeline = state.read().await.migration_pipeline.clone();
+    let defaults = TaskLogQuery::default();
+    let query = TaskLogQuery {
+        min_level,
+        run_id,
+        offset: offset.unwrap_or(defaults.offset),
+        limit: limit.unwrap_or(defaults.limit),
+    };
+    let page = pipeline.read().await.get_task_logs(&task_id, &query).await?;
+
+    serde_json::to_value(page).map_err(|e| format!("Failed to serialize task logs: {}", e))
+}
+
+// 重置增量迁移任务的水位标记，表为目标表名，未指定表时重置该任务的全部标记
+#[tauri::command]
+async fn reset_incremental_watermarks(
+    task_id: String,
+    table: Option<String>,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<u64, String> {
+    let state = state.read().await;
+    let config_db_id = state
+        .sqlite_config_connection_id
+        .clone()
+        .ok_or_else(|| "SQLite config connection not initialized".to_string())?;
+
+    let store = CheckpointStore::open(&state.conn_manager, &config_db_id).await?;
+    store.clear_watermarks(&task_id, table.as_deref()).await
+}
+
+// 卸载SQLite源库的变更捕获触发器，未指定表时同时删除变更日志表
+#[tauri::command]
+async fn uninstall_sqlite_changelog(
+    db_id: String,
+    table: Option<String>,
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<Vec<String>, String> {
+    let conn_manager = state.read().await.conn_manager.clone();
+    let conn = conn_manager
+        .get_sqlx_connection(&db_id)
+        .await
+        .ok_or_else(|| format!("SQL database connection not found: {}", db_id))?;
+    SqliteChangelog::new(conn)?.uninstall(table.as_deref()).await
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 任务之外的运行日志输出到控制台，默认只输出本应用Info及以上的日志，可用RUST_LOG调整；任务相关的日志由TaskLogger写入任务日志
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("bodhi_migration=info")).init();
+
     // 创建应用状态
     let app_state = Arc::new(RwLock::new(AppState::new()));
 
@@ -633,6 +1082,9 @@ pub fn run() {
             add_redis_connection,
             test_database_connection,
             remove_database_connection,
+            // 数据库结构查询命令
+            get_database_schema,
+            get_table_schema,
             // 数据库配置持久化命令
             save_database_config_to_db,
             get_all_database_configs_from_db,
@@ -641,10 +1093,19 @@ pub fn run() {
             create_migration_task,
             start_migration_task,
             pause_migration_task,
+            resume_migration_task,
             cancel_migration_task,
             retry_migration_task,
             get_migration_task,
             get_all_migration_tasks,
+            plan_migration_task,
+            verify_migration_task,
+            diff_migration_task,
+            get_migration_task_runs,
+            get_migration_task_run,
+            get_migration_task_logs,
+            reset_incremental_watermarks,
+            uninstall_sqlite_changelog,
 
         ])
         .run(tauri::generate_context!())
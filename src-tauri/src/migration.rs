@@ -6,6 +6,36 @@ pub mod task;
 pub mod strategy;
 // 导出迁移流水线
 pub mod pipeline;
+// 导出表数据复制工具
+pub mod table_copy;
+// 导出迁移检查点存储
+pub mod checkpoint;
+// 导出迁移任务持久化
+pub mod task_store;
+// 导出进度上报
+pub mod progress;
+// 导出任务日志
+pub mod task_log;
+// 导出迁移演练计划
+pub mod plan;
+// 导出迁移校验
+pub mod verify;
+// 导出表差异比较与修复
+pub mod table_diff;
+// 导出表和列映射规则
+pub mod mapping;
+// 导出行转换器
+pub mod transform;
+// 导出按表的行过滤条件
+pub mod filter;
+// 导出自定义SQL模板
+pub mod sql_template;
+// 导出变更数据捕获
+pub mod cdc;
+// 导出Redis键复制工具
+pub mod redis_copy;
+// 导出Redis与SQL表转换工具
+pub mod redis_table;
 
 // 重新导出常用的类型和函数
 pub use task::MigrationTask;
@@ -0,0 +1,187 @@
+// 变更数据捕获模块 - 把源库的行级变更持续同步到目标库
+
+pub mod mysql_binlog;
+pub mod pg_logical;
+pub mod sqlite_changelog;
+
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::database::{ColumnSchema, DatabaseType, SqlValue, SqlxDatabaseConnection, TableSchema};
+use crate::migration::table_copy;
+use crate::migration::task::TaskContext;
+
+// CDC同步位置，保存在迁移任务上用于断点续传
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CdcCheckpoint {
+    // MySQL binlog文件名和位置
+    MySqlBinlog { file: String, position: u64 },
+    // PostgreSQL复制槽名称和已确认的LSN
+    PostgresLsn { slot: String, lsn: String },
+}
+
+// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+}
+
+// 一条行级变更，列值以源表列名标识
+#[derive(Debug, Clone)]
+pub struct ChangeEvent {
+    pub table: String,
+    pub kind: ChangeKind,
+    // 变更前的行（更新和删除）
+    pub before: Option<Vec<(String, SqlValue)>>,
+    // 变更后的行（插入和更新）
+    pub after: Option<Vec<(String, SqlValue)>>,
+}
+
+impl ChangeEvent {
+    // 变更携带的数据量（字节），删除按变更前的行计算
+    pub fn byte_size(&self) -> u64 {
+        self.after
+            .as_ref()
+            .or(self.before.as_ref())
+            .map(|row| row.iter().map(|(_, v)| v.byte_size()).sum())
+            .unwrap_or(0)
+    }
+}
+
+// 把变更应用到目标库，插入和更新按主键写入，删除按主键定位
+pub struct ChangeApplier {
+    target_conn: SqlxDatabaseConnection,
+    tables: HashMap<String, TableSchema>,
+}
+
+impl ChangeApplier {
+    pub fn new(target_conn: SqlxDatabaseConnection) -> Self {
+        Self {
+            target_conn,
+            tables: HashMap::new(),
+        }
+    }
+
+    // 目标表结构变化后清除缓存，未指定表时全部清除
+    pub fn invalidate(&mut self, table: Option<&str>) {
+        match table {
+            Some(table) => {
+                self.tables.remove(table);
+            }
+            None => self.tables.clear(),
+        }
+    }
+
+    // 在目标库的一个事务中应用一组变更并计入任务的执行统计，返回应用的变更数
+    pub async fn apply(&mut self, events: &[ChangeEvent], ctx: &TaskContext) -> Result<u64, String> {
+        for event in events {
+            self.load_table(&event.table).await?;
+        }
+        let db_type = self.target_conn.db_type();
+        let mut statements = Vec::new();
+        for event in events {
+            statements.extend(change_statements(&db_type, &self.tables[&event.table], event)?);
+        }
+        if statements.is_empty() {
+            return Ok(0);
+        }
+        self.target_conn.execute_batch(statements).await?;
+        let bytes = events.iter().map(|event| event.byte_size()).sum();
+        ctx.record_rows(events.len() as u64, events.len() as u64, bytes);
+        Ok(events.len() as u64)
+    }
+
+    async fn load_table(&mut self, name: &str) -> Result<(), String> {
+        if !self.tables.contains_key(name) {
+            let schema = self
+                .target_conn
+                .get_table_schema(name)
+                .await?
+                .ok_or_else(|| format!("Target table not found: {}", name))?;
+            self.tables.insert(name.to_string(), schema);
+        }
+        Ok(())
+    }
+}
+
+// 把一条变更转换为目标库语句
+fn change_statements(
+    db_type: &DatabaseType,
+    table: &TableSchema,
+    event: &ChangeEvent,
+) -> Result<Vec<(String, Vec<SqlValue>)>, String> {
+    let key_columns: Vec<String> = table.primary_key.clone();
+    let mut statements = Vec::new();
+
+    if event.kind != ChangeKind::Insert && key_columns.is_empty() {
+        return Err(format!(
+            "Target table {} has no primary key; updates and deletes cannot be applied",
+            table.name
+        ));
+    }
+
+    // 主键发生变化或删除时，先按旧主键删除
+    let delete_before = match (event.kind, &event.before, &event.after) {
+        (ChangeKind::Delete, Some(_), _) => true,
+        (ChangeKind::Update, Some(before), Some(after)) => {
+            key_values(table, before)? != key_values(table, after)?
+        }
+        _ => false,
+    };
+    if delete_before {
+        if let Some(before) = &event.before {
+            let keys = table.primary_key_columns();
+            let values = key_values(table, before)?;
+            let mut params = Vec::with_capacity(keys.len());
+            let mut conditions = Vec::with_capacity(keys.len());
+            for (column, value) in keys.iter().zip(values) {
+                params.push(table_copy::coerce_value(value, column.kind));
+                conditions.push(format!(
+                    "{} = {}",
+                    db_type.quote_identifier(&column.name),
+                    table_copy::insert_placeholder(db_type, column, params.len())
+                ));
+            }
+            let sql = format!(
+                "DELETE FROM {} WHERE {}",
+                db_type.quote_identifier(&table.name),
+                conditions.join(" AND ")
+            );
+            statements.push((sql, params));
+        }
+    }
+
+    if let Some(after) = &event.after {
+        let pairs: Vec<(&ColumnSchema, SqlValue)> = after
+            .iter()
+            .filter_map(|(name, value)| table.column(name).map(|c| (c, value.clone())))
+            .collect();
+        let columns: Vec<&ColumnSchema> = pairs.iter().map(|(c, _)| *c).collect();
+        let row: Vec<SqlValue> = pairs.into_iter().map(|(_, v)| v).collect();
+        statements.extend(table_copy::insert_statements(
+            db_type,
+            &table.name,
+            &columns,
+            &key_columns,
+            &[row],
+        ));
+    }
+
+    Ok(statements)
+}
+
+// 按目标表主键顺序取出一行中的主键值
+fn key_values(table: &TableSchema, row: &[(String, SqlValue)]) -> Result<Vec<SqlValue>, String> {
+    table
+        .primary_key
+        .iter()
+        .map(|key| {
+            row.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.clone())
+                .ok_or_else(|| format!("Change on {} is missing primary key column {}", table.name, key))
+        })
+        .collect()
+}
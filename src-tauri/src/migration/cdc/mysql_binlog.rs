@@ -0,0 +1,659 @@
+// MySQL binlog变更捕获 - 以从库身份读取行格式binlog并应用到目标库
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::DateTime;
+use futures::StreamExt;
+use mysql_async::binlog::events::{EventData, RowsEventData, TableMapEvent};
+use mysql_async::binlog::row::BinlogRow;
+use mysql_async::binlog::value::BinlogValue;
+use mysql_async::prelude::Queryable;
+use mysql_async::{BinlogStream, BinlogStreamRequest, Conn, Opts, OptsBuilder, SslOpts, Value};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::database::{ColumnKind, ColumnSchema, DatabaseType, SqlValue, SqlxDatabaseConnection, TableSchema, UnifiedConnectionManager};
+use crate::migration::cdc::{CdcCheckpoint, ChangeApplier, ChangeEvent, ChangeKind};
+use crate::migration::checkpoint::TableCheckpoint;
+use crate::migration::plan::MigrationPlan;
+use crate::migration::strategy::{self, MigrationStrategy};
+use crate::migration::table_copy::{self, RowRules};
+use crate::migration::task::TaskContext;
+
+// 等待binlog事件的超时时间，超时后检查任务是否已暂停或取消
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// 全量复制前记录的binlog位置在检查点存储中的名称
+const INITIAL_POSITION_CHECKPOINT: &str = "@binlog_position";
+
+fn default_initial_copy() -> bool {
+    true
+}
+
+fn default_batch_size() -> usize {
+    table_copy::DEFAULT_BATCH_SIZE
+}
+
+// MySQL binlog CDC策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MySqlBinlogCdcStrategy {
+    name: String,
+    // 需要同步的表，为空时同步源库的所有表
+    #[serde(default)]
+    tables: Vec<String>,
+    // 作为从库注册时使用的server_id，未设置时由任务ID生成
+    #[serde(default)]
+    server_id: Option<u32>,
+    // 首次启动时是否先全量复制
+    #[serde(default = "default_initial_copy")]
+    initial_copy: bool,
+    // 大事务拆分应用的批次大小
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+}
+
+impl MySqlBinlogCdcStrategy {
+    pub fn new() -> Self {
+        Self {
+            name: "MySQL Binlog CDC".to_string(),
+            tables: Vec::new(),
+            server_id: None,
+            initial_copy: default_initial_copy(),
+            batch_size: default_batch_size(),
+        }
+    }
+
+    pub fn with_tables(mut self, tables: Vec<String>) -> Self {
+        self.tables = tables;
+        self
+    }
+
+    pub fn with_server_id(mut self, server_id: u32) -> Self {
+        self.server_id = Some(server_id);
+        self
+    }
+
+    pub fn with_initial_copy(mut self, initial_copy: bool) -> Self {
+        self.initial_copy = initial_copy;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationStrategy for MySqlBinlogCdcStrategy {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    // 校验同步的表，目标端落后于源端时结果不一致
+    fn verification_tables(&self) -> Option<Vec<String>> {
+        Some(self.tables.clone())
+    }
+
+    async fn execute(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<(), String> {
+        let (source_conn, target_conn) =
+            strategy::get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        if source_conn.db_type() != DatabaseType::MySql {
+            return Err("Binlog CDC requires a MySQL source database".to_string());
+        }
+
+        let opts = binlog_opts(&source_conn)?;
+        let mut conn = Conn::new(opts.clone())
+            .await
+            .map_err(|e| format!("Failed to connect to MySQL source: {}", e))?;
+        check_binlog_settings(&mut conn).await?;
+        let database: String = conn
+            .query_first::<Option<String>, _>("SELECT DATABASE()")
+            .await
+            .map_err(|e| format!("Failed to read current database: {}", e))?
+            .flatten()
+            .ok_or_else(|| "MySQL source connection has no default database".to_string())?;
+
+        let (file, position) = match ctx.cdc_checkpoint().await {
+            Some(CdcCheckpoint::MySqlBinlog { file, position }) => {
+                ctx.logger().info(format!("从binlog位置 {}:{} 继续同步", file, position)).await;
+                (file, position)
+            }
+            Some(other) => {
+                return Err(format!("Task checkpoint is not a MySQL binlog position: {:?}", other));
+            }
+            None => {
+                // 先记录位置再全量复制，复制期间的变更会在之后重放；全量复制中断后沿用最初的位置
+                let (file, position) = match ctx.table_checkpoint(INITIAL_POSITION_CHECKPOINT).await? {
+                    Some(checkpoint) => match checkpoint.last_key.as_slice() {
+                        [SqlValue::Text(file), SqlValue::Int(position)] => (file.clone(), *position as u64),
+                        other => return Err(format!("Invalid initial binlog position checkpoint: {:?}", other)),
+                    },
+                    None => {
+                        let (file, position) = current_binlog_position(&mut conn).await?;
+                        let checkpoint = TableCheckpoint {
+                            last_key: vec![SqlValue::Text(file.clone()), SqlValue::Int(position as i64)],
+                            ..Default::default()
+                        };
+                        ctx.save_table_checkpoint(INITIAL_POSITION_CHECKPOINT, &checkpoint).await?;
+                        (file, position)
+                    }
+                };
+                if self.initial_copy {
+                    strategy::copy_tables(&source_conn, &target_conn, &self.tables, RowRules::new(&[], &[], &BTreeMap::new()), self.batch_size, ctx).await?;
+                }
+                ctx.save_cdc_checkpoint(CdcCheckpoint::MySqlBinlog { file: file.clone(), position })
+                    .await;
+                ctx.logger().info(format!("从binlog位置 {}:{} 开始同步", file, position)).await;
+                (file, position)
+            }
+        };
+        let _ = conn.disconnect().await;
+
+        // 持续同步阶段没有总量，进度为已应用的变更数
+        ctx.progress().begin_stage(format!("Streaming binlog from {}:{}", file, position)).await;
+
+        let server_id = self.server_id.unwrap_or_else(|| default_server_id(ctx.task_id()));
+        let mut stream = open_stream(&opts, server_id, &file, position).await?;
+
+        let mut reader = BinlogReader {
+            source_conn,
+            database,
+            tables: self.tables.clone(),
+            schemas: HashMap::new(),
+            applier: ChangeApplier::new(target_conn),
+            pending: Vec::new(),
+            file,
+            applied: 0,
+        };
+
+        loop {
+            if ctx.is_canceled() {
+                break;
+            }
+            if ctx.is_paused() {
+                // 暂停期间关闭binlog流，避免服务端写超时断开；未提交的事务恢复后从检查点重放
+                let _ = stream.close().await;
+                if ctx.wait_if_paused().await.is_err() {
+                    ctx.logger().info(format!("binlog同步已停止，共应用 {} 条变更", reader.applied)).await;
+                    return Ok(());
+                }
+                let (file, position) = match ctx.cdc_checkpoint().await {
+                    Some(CdcCheckpoint::MySqlBinlog { file, position }) => (file, position),
+                    other => return Err(format!("Task checkpoint is not a MySQL binlog position: {:?}", other)),
+                };
+                reader.pending.clear();
+                reader.file = file.clone();
+                stream = open_stream(&opts, server_id, &file, position).await?;
+                ctx.logger().info(format!("从binlog位置 {}:{} 恢复同步", file, position)).await;
+                continue;
+            }
+
+            let event = match tokio::time::timeout(POLL_INTERVAL, stream.next()).await {
+                Err(_) => continue,
+                Ok(None) => return Err("Binlog stream closed by the MySQL server".to_string()),
+                Ok(Some(event)) => event.map_err(|e| format!("Failed to read binlog event: {}", e))?,
+            };
+            let log_pos = event.header().log_pos() as u64;
+            let data = event
+                .read_data()
+                .map_err(|e| format!("Failed to decode binlog event: {}", e))?;
+
+            match data {
+                Some(EventData::RotateEvent(rotate)) => {
+                    reader.file = rotate.name().to_string();
+                    ctx.save_cdc_checkpoint(CdcCheckpoint::MySqlBinlog {
+                        file: reader.file.clone(),
+                        position: rotate.position(),
+                    })
+                    .await;
+                }
+                Some(EventData::QueryEvent(query)) => {
+                    let sql = query.query().trim().to_string();
+                    if sql.eq_ignore_ascii_case("BEGIN") {
+                        continue;
+                    }
+                    if sql.eq_ignore_ascii_case("COMMIT") {
+                        reader.commit(ctx, log_pos).await?;
+                    } else {
+                        // DDL不会同步到目标库，只刷新表结构缓存
+                        ctx.logger().warning(format!("binlog中的DDL不会同步到目标库: {}", sql)).await;
+                        reader.schemas.clear();
+                        reader.applier.invalidate(None);
+                        reader.commit(ctx, log_pos).await?;
+                    }
+                }
+                Some(EventData::XidEvent(_)) => {
+                    reader.commit(ctx, log_pos).await?;
+                }
+                Some(EventData::RowsEvent(rows_event)) => {
+                    let rows_event = rows_event.into_owned();
+                    let (database, table) = match stream.get_tme(rows_event.table_id()) {
+                        Some(tme) => (tme.database_name().to_string(), tme.table_name().to_string()),
+                        None => {
+                            return Err(format!(
+                                "Binlog rows event references unknown table id {}",
+                                rows_event.table_id()
+                            ));
+                        }
+                    };
+                    if !reader.is_tracked(&database, &table) {
+                        continue;
+                    }
+                    let columns_count = stream
+                        .get_tme(rows_event.table_id())
+                        .map(|tme| tme.columns_count() as usize)
+                        .unwrap_or(0);
+                    reader.load_schema(&table, columns_count).await?;
+
+                    let tme = stream
+                        .get_tme(rows_event.table_id())
+                        .ok_or_else(|| format!("Table map for {} disappeared", table))?;
+                    let events = decode_rows(&rows_event, tme, &reader.schemas[&table])?;
+                    reader.pending.extend(events);
+
+                    // 大事务分批应用，避免占用过多内存
+                    if reader.pending.len() >= self.batch_size {
+                        reader.flush(ctx).await?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // 未提交的事务会在下次启动时从检查点重放
+        let _ = stream.close().await;
+        ctx.logger().info(format!("binlog同步已停止，共应用 {} 条变更", reader.applied)).await;
+        Ok(())
+    }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        let (source_conn, target_conn) =
+            strategy::get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        if source_conn.db_type() != DatabaseType::MySql {
+            return Err("Binlog CDC requires a MySQL source database".to_string());
+        }
+
+        // 持续同步阶段的变更数无法预估，计划只包含初始复制
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+        let mut conn = Conn::new(binlog_opts(&source_conn)?)
+            .await
+            .map_err(|e| format!("Failed to connect to MySQL source: {}", e))?;
+        if let Err(e) = check_binlog_settings(&mut conn).await {
+            plan.warn(e);
+        }
+        let _ = conn.disconnect().await;
+
+        match ctx.cdc_checkpoint().await {
+            Some(CdcCheckpoint::MySqlBinlog { .. }) => {}
+            Some(other) => {
+                return Err(format!("Task checkpoint is not a MySQL binlog position: {:?}", other));
+            }
+            None => {
+                if self.initial_copy {
+                    strategy::plan_tables(&mut plan, &source_conn, &target_conn, &self.tables, RowRules::new(&[], &[], &BTreeMap::new()), self.batch_size, ctx)
+                        .await?;
+                }
+            }
+        }
+        Ok(plan)
+    }
+}
+
+// binlog读取状态
+struct BinlogReader {
+    source_conn: SqlxDatabaseConnection,
+    database: String,
+    tables: Vec<String>,
+    schemas: HashMap<String, TableSchema>,
+    applier: ChangeApplier,
+    pending: Vec<ChangeEvent>,
+    file: String,
+    applied: u64,
+}
+
+impl BinlogReader {
+    // 只处理源库中选定的表
+    fn is_tracked(&self, database: &str, table: &str) -> bool {
+        database.eq_ignore_ascii_case(&self.database)
+            && (self.tables.is_empty() || self.tables.iter().any(|t| t.eq_ignore_ascii_case(table)))
+    }
+
+    // 加载源表结构，列数与binlog不一致时重新读取
+    async fn load_schema(&mut self, table: &str, columns_count: usize) -> Result<(), String> {
+        let stale = self
+            .schemas
+            .get(table)
+            .is_none_or(|schema| schema.columns.len() != columns_count);
+        if stale {
+            let schema = self
+                .source_conn
+                .get_table_schema(table)
+                .await?
+                .ok_or_else(|| format!("Source table not found: {}", table))?;
+            if schema.columns.len() != columns_count {
+                return Err(format!(
+                    "Binlog for {} has {} columns but the source table has {}; the table was altered after this event",
+                    table,
+                    columns_count,
+                    schema.columns.len()
+                ));
+            }
+            self.schemas.insert(table.to_string(), schema);
+        }
+        Ok(())
+    }
+
+    // 应用缓存的变更
+    async fn flush(&mut self, ctx: &TaskContext) -> Result<(), String> {
+        if !self.pending.is_empty() {
+            self.applied += self.applier.apply(&self.pending, ctx).await?;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+
+    // 事务提交后应用变更并保存下一个事件的位置
+    async fn commit(&mut self, ctx: &TaskContext, log_pos: u64) -> Result<(), String> {
+        self.flush(ctx).await?;
+        if log_pos == 0 {
+            return Ok(());
+        }
+        ctx.save_cdc_checkpoint(CdcCheckpoint::MySqlBinlog {
+            file: self.file.clone(),
+            position: log_pos,
+        })
+        .await;
+        ctx.update_progress(self.applied, format!("binlog {}:{}", self.file, log_pos))
+            .await;
+        Ok(())
+    }
+}
+
+// 从指定位置打开binlog流
+async fn open_stream(opts: &Opts, server_id: u32, file: &str, position: u64) -> Result<BinlogStream, String> {
+    let conn = Conn::new(opts.clone())
+        .await
+        .map_err(|e| format!("Failed to connect to MySQL source: {}", e))?;
+    conn.get_binlog_stream(
+        BinlogStreamRequest::new(server_id)
+            .with_filename(file.as_bytes())
+            .with_pos(position),
+    )
+    .await
+    .map_err(|e| format!("Failed to open binlog stream: {}", e))
+}
+
+// 由sqlx连接地址生成mysql_async连接参数
+fn binlog_opts(source_conn: &SqlxDatabaseConnection) -> Result<Opts, String> {
+    let url = source_conn
+        .connection_url()
+        .ok_or_else(|| "MySQL source has no connection URL".to_string())?;
+    let (base, query) = match url.split_once('?') {
+        Some((base, query)) => (base.to_string(), query.to_string()),
+        None => (url, String::new()),
+    };
+    let opts = Opts::from_url(&base).map_err(|e| format!("Invalid MySQL connection URL: {}", e))?;
+    let mut builder = OptsBuilder::from_opts(opts);
+    if query.split('&').any(|param| param.eq_ignore_ascii_case("ssl-mode=required")) {
+        builder = builder.ssl_opts(SslOpts::default());
+    }
+    Ok(builder.into())
+}
+
+// 检查源库是否开启了行格式的完整binlog
+async fn check_binlog_settings(conn: &mut Conn) -> Result<(), String> {
+    let settings: Option<(Option<String>, Option<String>, Option<String>)> = conn
+        .query_first("SELECT CAST(@@log_bin AS CHAR), @@binlog_format, @@binlog_row_image")
+        .await
+        .map_err(|e| format!("Failed to read binlog settings: {}", e))?;
+    let (log_bin, format, row_image) = settings.unwrap_or_default();
+
+    if !matches!(log_bin.as_deref(), Some("1") | Some("ON")) {
+        return Err("Binary logging is disabled on the MySQL source (log_bin=OFF)".to_string());
+    }
+    if !format.as_deref().is_some_and(|f| f.eq_ignore_ascii_case("ROW")) {
+        return Err(format!(
+            "Binlog CDC requires binlog_format=ROW, found {}",
+            format.unwrap_or_default()
+        ));
+    }
+    if !row_image.as_deref().is_some_and(|r| r.eq_ignore_ascii_case("FULL")) {
+        return Err(format!(
+            "Binlog CDC requires binlog_row_image=FULL, found {}",
+            row_image.unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
+
+// 读取源库当前的binlog位置，兼容MySQL 8.4之前的语法
+async fn current_binlog_position(conn: &mut Conn) -> Result<(String, u64), String> {
+    let row: Option<mysql_async::Row> = match conn.query_first("SHOW BINARY LOG STATUS").await {
+        Ok(row) => row,
+        Err(_) => conn
+            .query_first("SHOW MASTER STATUS")
+            .await
+            .map_err(|e| format!("Failed to read binlog position: {}", e))?,
+    };
+    let row = row.ok_or_else(|| "MySQL source reported no binlog position".to_string())?;
+    let file: String = row
+        .get(0)
+        .ok_or_else(|| "MySQL source reported no binlog file".to_string())?;
+    let position: u64 = row
+        .get(1)
+        .ok_or_else(|| "MySQL source reported no binlog position".to_string())?;
+    Ok((file, position))
+}
+
+// 由任务ID的SHA-256生成server_id，升级工具链后不变；最高位置1，避免与真实从库冲突
+fn default_server_id(task_id: &str) -> u32 {
+    let digest = Sha256::digest(task_id.as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) | 0x8000_0000
+}
+
+// 把行事件解码为变更
+fn decode_rows(
+    rows_event: &RowsEventData<'_>,
+    tme: &TableMapEvent<'_>,
+    schema: &TableSchema,
+) -> Result<Vec<ChangeEvent>, String> {
+    let kind = match rows_event {
+        RowsEventData::WriteRowsEvent(_) | RowsEventData::WriteRowsEventV1(_) => ChangeKind::Insert,
+        RowsEventData::UpdateRowsEvent(_) | RowsEventData::UpdateRowsEventV1(_) => ChangeKind::Update,
+        RowsEventData::DeleteRowsEvent(_) | RowsEventData::DeleteRowsEventV1(_) => ChangeKind::Delete,
+        RowsEventData::PartialUpdateRowsEvent(_) => {
+            return Err(format!(
+                "Partial JSON updates on {} are not supported; set binlog_row_value_options to ''",
+                schema.name
+            ));
+        }
+    };
+    let before_image: Vec<usize> = rows_event
+        .columns_before_image()
+        .map(|bits| bits.iter_ones().collect())
+        .unwrap_or_default();
+    let after_image: Vec<usize> = rows_event
+        .columns_after_image()
+        .map(|bits| bits.iter_ones().collect())
+        .unwrap_or_default();
+
+    let mut events = Vec::new();
+    for row in rows_event.rows(tme) {
+        let (before, after) = row.map_err(|e| format!("Failed to decode binlog row for {}: {}", schema.name, e))?;
+        events.push(ChangeEvent {
+            table: schema.name.clone(),
+            kind,
+            before: before.map(|row| row_values(&row, &before_image, schema)).transpose()?,
+            after: after.map(|row| row_values(&row, &after_image, schema)).transpose()?,
+        });
+    }
+    Ok(events)
+}
+
+// binlog行中的值按列序号对应源表列，只有FULL元数据才带列名
+fn row_values(row: &BinlogRow, image: &[usize], schema: &TableSchema) -> Result<Vec<(String, SqlValue)>, String> {
+    image
+        .iter()
+        .enumerate()
+        .map(|(i, &ordinal)| {
+            let column = schema
+                .columns
+                .get(ordinal)
+                .ok_or_else(|| format!("Binlog column {} is out of range for {}", ordinal, schema.name))?;
+            Ok((column.name.clone(), binlog_value(row.as_ref(i), column)?))
+        })
+        .collect()
+}
+
+// 转换binlog中的列值
+fn binlog_value(value: Option<&BinlogValue<'_>>, column: &ColumnSchema) -> Result<SqlValue, String> {
+    match value {
+        None => Ok(SqlValue::Null),
+        Some(BinlogValue::Value(value)) => Ok(mysql_value(value, column)),
+        Some(BinlogValue::Jsonb(jsonb)) => serde_json::Value::try_from(jsonb.clone())
+            .map(|json| SqlValue::Text(json.to_string()))
+            .map_err(|e| format!("Failed to decode JSON column {}: {}", column.name, e)),
+        Some(BinlogValue::JsonDiff(_)) => Err(format!(
+            "Partial JSON update on column {} is not supported",
+            column.name
+        )),
+    }
+}
+
+fn mysql_value(value: &Value, column: &ColumnSchema) -> SqlValue {
+    let declared = column.data_type.to_lowercase();
+    let base = declared.split(['(', ' ']).next().unwrap_or("").to_string();
+
+    match value {
+        Value::NULL => SqlValue::Null,
+        // ENUM以从1开始的序号记录
+        Value::Int(index) if base == "enum" => enum_labels(&column.data_type)
+            .get((*index as usize).wrapping_sub(1))
+            .cloned()
+            .map(SqlValue::Text)
+            .unwrap_or_else(|| SqlValue::Text(String::new())),
+        // 无符号整数以同宽度的有符号数记录
+        Value::Int(i) if column.unsigned && *i < 0 => match base.as_str() {
+            "tinyint" => SqlValue::Int(*i + (1 << 8)),
+            "smallint" => SqlValue::Int(*i + (1 << 16)),
+            "mediumint" => SqlValue::Int(*i + (1 << 24)),
+            "int" | "integer" => SqlValue::Int(*i + (1 << 32)),
+            _ => SqlValue::Text((*i as u64).to_string()),
+        },
+        Value::Int(i) if column.kind == ColumnKind::Bool => SqlValue::Bool(*i != 0),
+        Value::Int(i) if column.kind == ColumnKind::Decimal => SqlValue::Text(i.to_string()),
+        Value::Int(i) => SqlValue::Int(*i),
+        Value::UInt(u) => i64::try_from(*u)
+            .map(SqlValue::Int)
+            .unwrap_or_else(|_| SqlValue::Text(u.to_string())),
+        Value::Float(f) => SqlValue::Float(*f as f64),
+        Value::Double(d) => SqlValue::Float(*d),
+        Value::Bytes(bytes) => match base.as_str() {
+            // SET以位图记录
+            "set" => {
+                let mask = bytes.iter().rev().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+                let labels: Vec<String> = enum_labels(&column.data_type)
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, label)| label)
+                    .collect();
+                SqlValue::Text(labels.join(","))
+            }
+            "bit" => {
+                let bits = bytes.iter().fold(0i64, |acc, b| (acc << 8) | *b as i64);
+                if column.kind == ColumnKind::Bool {
+                    SqlValue::Bool(bits != 0)
+                } else {
+                    SqlValue::Int(bits)
+                }
+            }
+            // TIMESTAMP以UTC秒数记录
+            "timestamp" => {
+                let text = String::from_utf8_lossy(bytes);
+                let (seconds, micros) = text.split_once('.').unwrap_or((&text, ""));
+                let nanos = micros.parse::<u32>().unwrap_or(0) * 1000;
+                match seconds.parse::<i64>().ok().and_then(|s| DateTime::from_timestamp(s, nanos)) {
+                    Some(ts) if micros.is_empty() => SqlValue::Text(ts.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    Some(ts) => SqlValue::Text(ts.format("%Y-%m-%d %H:%M:%S%.6f").to_string()),
+                    None => SqlValue::Text(text.into_owned()),
+                }
+            }
+            _ if column.kind == ColumnKind::Bytes => SqlValue::Bytes(bytes.clone()),
+            _ => SqlValue::Text(String::from_utf8_lossy(bytes).into_owned()),
+        },
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            let date = format!("{:04}-{:02}-{:02}", year, month, day);
+            if column.kind == ColumnKind::Date {
+                SqlValue::Text(date)
+            } else if *micros == 0 {
+                SqlValue::Text(format!("{} {:02}:{:02}:{:02}", date, hour, minute, second))
+            } else {
+                SqlValue::Text(format!("{} {:02}:{:02}:{:02}.{:06}", date, hour, minute, second, micros))
+            }
+        }
+        Value::Time(negative, days, hours, minutes, seconds, micros) => {
+            let sign = if *negative { "-" } else { "" };
+            let hours = *days * 24 + *hours as u32;
+            if *micros == 0 {
+                SqlValue::Text(format!("{}{:02}:{:02}:{:02}", sign, hours, minutes, seconds))
+            } else {
+                SqlValue::Text(format!("{}{:02}:{:02}:{:02}.{:06}", sign, hours, minutes, seconds, micros))
+            }
+        }
+    }
+}
+
+// 解析 enum('a','b') / set('a','b') 声明中的取值
+fn enum_labels(declared: &str) -> Vec<String> {
+    let inner = match (declared.find('('), declared.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &declared[start + 1..end],
+        _ => return Vec::new(),
+    };
+    let mut labels = Vec::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut label = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    label.push('\'');
+                    continue;
+                }
+                break;
+            }
+            label.push(c);
+        }
+        labels.push(label);
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_id_is_fixed_per_task() {
+        assert_eq!(default_server_id("3f2b8c1e-5d4a-4e6b-9c7d-1a2b3c4d5e6f"), 3_847_159_808);
+        assert_ne!(default_server_id("a"), default_server_id("b"));
+        assert!(default_server_id("a") >= 0x8000_0000);
+    }
+}
@@ -0,0 +1,684 @@
+// PostgreSQL逻辑复制变更捕获 - 通过pgoutput复制槽读取变更并应用到目标库
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::database::{DatabaseType, SqlValue, SqlxDatabaseConnection, UnifiedConnectionManager};
+use crate::migration::cdc::{CdcCheckpoint, ChangeApplier, ChangeEvent, ChangeKind};
+use crate::migration::plan::{MigrationPlan, StatementKind};
+use crate::migration::strategy::{self, MigrationStrategy};
+use crate::migration::table_copy::{self, RowRules};
+use crate::migration::task::TaskContext;
+use crate::migration::task_log::TaskLogger;
+
+// 复制槽中没有新变更时的等待时间
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// 读取但不消费复制槽中的变更，确认由目标库提交后的 pg_replication_slot_advance 完成
+const PEEK_CHANGES_SQL: &str = "SELECT CAST(lsn AS TEXT) AS lsn, data \
+    FROM pg_logical_slot_peek_binary_changes($1, NULL, CAST($2 AS INTEGER), 'proto_version', '1', 'publication_names', $3)";
+
+fn default_initial_copy() -> bool {
+    true
+}
+
+fn default_batch_size() -> usize {
+    table_copy::DEFAULT_BATCH_SIZE
+}
+
+// PostgreSQL逻辑复制CDC策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostgresLogicalCdcStrategy {
+    name: String,
+    // 需要同步的表，为空时发布源库的所有表
+    #[serde(default)]
+    tables: Vec<String>,
+    // 复制槽名称，未设置时由任务ID生成
+    #[serde(default)]
+    slot_name: Option<String>,
+    // 发布名称，未设置时与复制槽同名
+    #[serde(default)]
+    publication: Option<String>,
+    // 首次启动时是否先全量复制
+    #[serde(default = "default_initial_copy")]
+    initial_copy: bool,
+    // 每次从复制槽读取的变更数
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+}
+
+impl PostgresLogicalCdcStrategy {
+    pub fn new() -> Self {
+        Self {
+            name: "PostgreSQL Logical CDC".to_string(),
+            tables: Vec::new(),
+            slot_name: None,
+            publication: None,
+            initial_copy: default_initial_copy(),
+            batch_size: default_batch_size(),
+        }
+    }
+
+    pub fn with_tables(mut self, tables: Vec<String>) -> Self {
+        self.tables = tables;
+        self
+    }
+
+    pub fn with_slot_name(mut self, slot_name: String) -> Self {
+        self.slot_name = Some(slot_name);
+        self
+    }
+
+    pub fn with_publication(mut self, publication: String) -> Self {
+        self.publication = Some(publication);
+        self
+    }
+
+    pub fn with_initial_copy(mut self, initial_copy: bool) -> Self {
+        self.initial_copy = initial_copy;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationStrategy for PostgresLogicalCdcStrategy {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    // 校验同步的表，目标端落后于源端时结果不一致
+    fn verification_tables(&self) -> Option<Vec<String>> {
+        Some(self.tables.clone())
+    }
+
+    async fn execute(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<(), String> {
+        let (source_conn, target_conn) =
+            strategy::get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        if source_conn.db_type() != DatabaseType::PostgreSql {
+            return Err("Logical replication CDC requires a PostgreSQL source database".to_string());
+        }
+
+        let slot = match &self.slot_name {
+            Some(slot) => validate_name(slot)?,
+            None => default_slot_name(ctx.task_id()),
+        };
+        let publication = match &self.publication {
+            Some(publication) => validate_name(publication)?,
+            None => slot.clone(),
+        };
+
+        check_wal_level(&source_conn).await?;
+        ensure_publication(&source_conn, &publication, &self.tables, ctx.logger()).await?;
+
+        match ctx.cdc_checkpoint().await {
+            Some(CdcCheckpoint::PostgresLsn { slot: saved_slot, lsn }) if saved_slot == slot => {
+                if slot_lsn(&source_conn, &slot).await?.is_none() {
+                    return Err(format!(
+                        "Replication slot {} no longer exists; changes after {} were lost. Clear the task checkpoint to start over",
+                        slot, lsn
+                    ));
+                }
+                ctx.logger().info(format!("从复制槽 {} 的位置 {} 继续同步", slot, lsn)).await;
+            }
+            Some(other) => {
+                return Err(format!("Task checkpoint does not belong to replication slot {}: {:?}", slot, other));
+            }
+            None => {
+                // 先创建复制槽再全量复制，复制期间的变更会在之后重放
+                let lsn = match slot_lsn(&source_conn, &slot).await? {
+                    Some(lsn) => {
+                        ctx.logger().info(format!("复用已存在的复制槽 {}", slot)).await;
+                        lsn
+                    }
+                    None => {
+                        let lsn = create_slot(&source_conn, &slot).await?;
+                        ctx.logger().info(format!("已创建复制槽 {}", slot)).await;
+                        lsn
+                    }
+                };
+                if self.initial_copy {
+                    strategy::copy_tables(&source_conn, &target_conn, &self.tables, RowRules::new(&[], &[], &BTreeMap::new()), self.batch_size, ctx).await?;
+                }
+                ctx.save_cdc_checkpoint(CdcCheckpoint::PostgresLsn { slot: slot.clone(), lsn: lsn.clone() })
+                    .await;
+                ctx.logger().info(format!("从复制槽 {} 的位置 {} 开始同步", slot, lsn)).await;
+            }
+        }
+
+        // 持续同步阶段没有总量，进度为已应用的变更数
+        ctx.progress().begin_stage(format!("Streaming from slot {}", slot)).await;
+
+        let mut applier = ChangeApplier::new(target_conn);
+        let mut relations: HashMap<u32, Relation> = HashMap::new();
+        let mut pending: Vec<ChangeEvent> = Vec::new();
+        let mut applied: u64 = 0;
+
+        // 暂停时等待恢复，复制槽会保留未确认的变更；取消时结束同步
+        while ctx.wait_if_paused().await.is_ok() {
+            let result = source_conn
+                .fetch_rows(
+                    PEEK_CHANGES_SQL,
+                    vec![
+                        SqlValue::Text(slot.clone()),
+                        SqlValue::Int(self.batch_size as i64),
+                        SqlValue::Text(publication.clone()),
+                    ],
+                )
+                .await?;
+            if result.rows.is_empty() {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+
+            let mut commit_lsn = None;
+            for row in &result.rows {
+                let data = match result.get(row, "data") {
+                    Some(SqlValue::Bytes(data)) => data,
+                    other => return Err(format!("Unexpected logical replication payload: {:?}", other)),
+                };
+                match decode_message(data)? {
+                    Message::Relation(relation) => {
+                        relations.insert(relation.id, relation);
+                    }
+                    Message::Change { relation_id, kind, old, new } => {
+                        let relation = relations
+                            .get(&relation_id)
+                            .ok_or_else(|| format!("Change references unknown relation {}", relation_id))?;
+                        if !self.tables.is_empty() && !self.tables.contains(&relation.name) {
+                            continue;
+                        }
+                        pending.push(ChangeEvent {
+                            table: relation.name.clone(),
+                            kind,
+                            before: old.map(|tuple| relation.values(tuple)),
+                            after: new.map(|tuple| relation.values(tuple)),
+                        });
+                        // 大事务分批应用，确认位置仍以事务提交为准
+                        if pending.len() >= self.batch_size {
+                            applied += applier.apply(&pending, ctx).await?;
+                            pending.clear();
+                        }
+                    }
+                    Message::Commit { end_lsn } => commit_lsn = Some(end_lsn),
+                    Message::Truncate(relation_ids) => {
+                        let names: Vec<&str> = relation_ids
+                            .iter()
+                            .filter_map(|id| relations.get(id).map(|r| r.name.as_str()))
+                            .collect();
+                        ctx.logger().warning(format!("TRUNCATE不会同步到目标库: {}", names.join(", "))).await;
+                    }
+                    Message::Other => {}
+                }
+            }
+
+            applied += applier.apply(&pending, ctx).await?;
+            pending.clear();
+
+            // 目标库提交后才推进复制槽
+            if let Some(end_lsn) = commit_lsn {
+                let lsn = format_lsn(end_lsn);
+                source_conn
+                    .fetch_rows(
+                        "SELECT CAST(end_lsn AS TEXT) AS lsn FROM pg_replication_slot_advance($1, CAST($2 AS pg_lsn))",
+                        vec![SqlValue::Text(slot.clone()), SqlValue::Text(lsn.clone())],
+                    )
+                    .await?;
+                ctx.save_cdc_checkpoint(CdcCheckpoint::PostgresLsn { slot: slot.clone(), lsn: lsn.clone() })
+                    .await;
+                ctx.update_progress(applied, format!("slot {} at {}", slot, lsn)).await;
+            }
+        }
+
+        ctx.logger().info(format!("逻辑复制同步已停止，共应用 {} 条变更", applied)).await;
+        Ok(())
+    }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        let (source_conn, target_conn) =
+            strategy::get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        if source_conn.db_type() != DatabaseType::PostgreSql {
+            return Err("Logical replication CDC requires a PostgreSQL source database".to_string());
+        }
+
+        let slot = match &self.slot_name {
+            Some(slot) => validate_name(slot)?,
+            None => default_slot_name(ctx.task_id()),
+        };
+        let publication = match &self.publication {
+            Some(publication) => validate_name(publication)?,
+            None => slot.clone(),
+        };
+
+        // 持续同步阶段的变更数无法预估，计划只包含准备语句和初始复制
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+        if let Err(e) = check_wal_level(&source_conn).await {
+            plan.warn(e);
+        }
+        for sql in publication_statements(&source_conn, &publication, &self.tables).await? {
+            plan.add_statement(StatementKind::SourceSetup, None, sql, Some(1));
+        }
+
+        match ctx.cdc_checkpoint().await {
+            Some(CdcCheckpoint::PostgresLsn { slot: saved_slot, lsn }) if saved_slot == slot => {
+                if slot_lsn(&source_conn, &slot).await?.is_none() {
+                    plan.warn(format!(
+                        "复制槽 {} 已不存在，{} 之后的变更已丢失，需要清除任务检查点后重新开始",
+                        slot, lsn
+                    ));
+                }
+            }
+            Some(other) => {
+                return Err(format!("Task checkpoint does not belong to replication slot {}: {:?}", slot, other));
+            }
+            None => {
+                if slot_lsn(&source_conn, &slot).await?.is_none() {
+                    plan.add_statement(
+                        StatementKind::SourceSetup,
+                        None,
+                        format!("SELECT pg_create_logical_replication_slot('{}', 'pgoutput')", slot),
+                        Some(1),
+                    );
+                }
+                if self.initial_copy {
+                    strategy::plan_tables(&mut plan, &source_conn, &target_conn, &self.tables, RowRules::new(&[], &[], &BTreeMap::new()), self.batch_size, ctx)
+                        .await?;
+                }
+            }
+        }
+        Ok(plan)
+    }
+}
+
+// 复制槽和发布名称只允许小写字母、数字和下划线
+fn validate_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name.len() <= 63
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "Invalid replication slot or publication name {:?}: use up to 63 lowercase letters, digits and underscores",
+            name
+        ))
+    }
+}
+
+// 由任务ID生成复制槽名称
+fn default_slot_name(task_id: &str) -> String {
+    let suffix: String = task_id
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let mut name = if suffix.is_empty() {
+        "bodhi_cdc".to_string()
+    } else {
+        format!("bodhi_cdc_{}", suffix)
+    };
+    name.truncate(63);
+    name
+}
+
+// 检查源库是否开启逻辑复制
+async fn check_wal_level(conn: &SqlxDatabaseConnection) -> Result<(), String> {
+    let result = conn
+        .fetch_rows("SELECT current_setting('wal_level') AS wal_level", vec![])
+        .await?;
+    let wal_level = result
+        .rows
+        .first()
+        .and_then(|row| result.get(row, "wal_level"))
+        .and_then(|v| v.as_string())
+        .unwrap_or_default();
+    if wal_level != "logical" {
+        return Err(format!(
+            "Logical replication CDC requires wal_level=logical, found {}",
+            wal_level
+        ));
+    }
+    Ok(())
+}
+
+// 创建或复用发布，指定表时补充缺少的表
+async fn ensure_publication(
+    conn: &SqlxDatabaseConnection,
+    publication: &str,
+    tables: &[String],
+    logger: &TaskLogger,
+) -> Result<(), String> {
+    for sql in publication_statements(conn, publication, tables).await? {
+        conn.execute_raw(&sql).await?;
+        logger.info(format!("已更新发布 {}: {}", publication, sql)).await;
+    }
+    Ok(())
+}
+
+// 创建发布或补充缺少的表所需的语句，发布已满足要求时为空
+async fn publication_statements(
+    conn: &SqlxDatabaseConnection,
+    publication: &str,
+    tables: &[String],
+) -> Result<Vec<String>, String> {
+    let db_type = conn.db_type();
+    let table_list = || {
+        tables
+            .iter()
+            .map(|t| db_type.quote_identifier(t))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let existing = conn
+        .fetch_rows(
+            "SELECT CAST(puballtables AS INTEGER) AS all_tables FROM pg_publication WHERE pubname = $1",
+            vec![SqlValue::Text(publication.to_string())],
+        )
+        .await?;
+    let all_tables = match existing.rows.first() {
+        Some(row) => matches!(existing.get(row, "all_tables"), Some(SqlValue::Int(1))),
+        None => {
+            let sql = if tables.is_empty() {
+                format!("CREATE PUBLICATION {} FOR ALL TABLES", db_type.quote_identifier(publication))
+            } else {
+                format!("CREATE PUBLICATION {} FOR TABLE {}", db_type.quote_identifier(publication), table_list())
+            };
+            return Ok(vec![sql]);
+        }
+    };
+    if all_tables || tables.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let published = conn
+        .fetch_rows(
+            "SELECT CAST(tablename AS TEXT) AS table_name FROM pg_publication_tables WHERE pubname = $1",
+            vec![SqlValue::Text(publication.to_string())],
+        )
+        .await?;
+    let published: Vec<String> = published
+        .rows
+        .iter()
+        .filter_map(|row| published.get(row, "table_name").and_then(|v| v.as_string()))
+        .collect();
+    let missing: Vec<String> = tables
+        .iter()
+        .filter(|t| !published.contains(t))
+        .map(|t| db_type.quote_identifier(t))
+        .collect();
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(vec![format!(
+        "ALTER PUBLICATION {} ADD TABLE {}",
+        db_type.quote_identifier(publication),
+        missing.join(", ")
+    )])
+}
+
+// 读取复制槽已确认的位置，复制槽不存在时返回None
+async fn slot_lsn(conn: &SqlxDatabaseConnection, slot: &str) -> Result<Option<String>, String> {
+    let result = conn
+        .fetch_rows(
+            "SELECT CAST(plugin AS TEXT) AS plugin, CAST(confirmed_flush_lsn AS TEXT) AS lsn \
+             FROM pg_replication_slots WHERE slot_name = $1",
+            vec![SqlValue::Text(slot.to_string())],
+        )
+        .await?;
+    let row = match result.rows.first() {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let plugin = result.get(row, "plugin").and_then(|v| v.as_string()).unwrap_or_default();
+    if plugin != "pgoutput" {
+        return Err(format!(
+            "Replication slot {} uses output plugin {:?}; pgoutput is required",
+            slot, plugin
+        ));
+    }
+    Ok(Some(result.get(row, "lsn").and_then(|v| v.as_string()).unwrap_or_default()))
+}
+
+// 创建pgoutput逻辑复制槽，返回其起始位置
+async fn create_slot(conn: &SqlxDatabaseConnection, slot: &str) -> Result<String, String> {
+    let result = conn
+        .fetch_rows(
+            "SELECT CAST(lsn AS TEXT) AS lsn FROM pg_create_logical_replication_slot($1, 'pgoutput')",
+            vec![SqlValue::Text(slot.to_string())],
+        )
+        .await?;
+    Ok(result
+        .rows
+        .first()
+        .and_then(|row| result.get(row, "lsn"))
+        .and_then(|v| v.as_string())
+        .unwrap_or_default())
+}
+
+// 按 X/Y 形式格式化LSN
+fn format_lsn(lsn: u64) -> String {
+    format!("{:X}/{:X}", lsn >> 32, lsn & 0xFFFF_FFFF)
+}
+
+// pgoutput关系消息描述的表结构
+struct Relation {
+    id: u32,
+    name: String,
+    // 列名和类型OID
+    columns: Vec<(String, u32)>,
+}
+
+impl Relation {
+    // 未变化的TOAST列不在元组中，写入时保留目标库原值
+    fn values(&self, tuple: Vec<TupleValue>) -> Vec<(String, SqlValue)> {
+        self.columns
+            .iter()
+            .zip(tuple)
+            .filter_map(|((name, type_oid), value)| match value {
+                TupleValue::Null => Some((name.clone(), SqlValue::Null)),
+                TupleValue::Unchanged => None,
+                TupleValue::Text(text) => Some((name.clone(), text_value(*type_oid, text))),
+            })
+            .collect()
+    }
+}
+
+enum TupleValue {
+    Null,
+    Unchanged,
+    Text(String),
+}
+
+// 解码后的pgoutput消息
+enum Message {
+    Relation(Relation),
+    Change {
+        relation_id: u32,
+        kind: ChangeKind,
+        old: Option<Vec<TupleValue>>,
+        new: Option<Vec<TupleValue>>,
+    },
+    Commit { end_lsn: u64 },
+    Truncate(Vec<u32>),
+    Other,
+}
+
+// 按pgoutput协议第1版解码一条消息
+fn decode_message(data: &[u8]) -> Result<Message, String> {
+    let mut reader = ByteReader { data, pos: 0 };
+    let message = match reader.u8()? {
+        b'R' => {
+            let id = reader.u32()?;
+            let _namespace = reader.cstring()?;
+            let name = reader.cstring()?;
+            let _replica_identity = reader.u8()?;
+            let count = reader.u16()?;
+            let mut columns = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let _flags = reader.u8()?;
+                let column = reader.cstring()?;
+                let type_oid = reader.u32()?;
+                let _type_modifier = reader.u32()?;
+                columns.push((column, type_oid));
+            }
+            Message::Relation(Relation { id, name, columns })
+        }
+        b'I' => {
+            let relation_id = reader.u32()?;
+            reader.expect(b'N')?;
+            Message::Change {
+                relation_id,
+                kind: ChangeKind::Insert,
+                old: None,
+                new: Some(reader.tuple()?),
+            }
+        }
+        b'U' => {
+            let relation_id = reader.u32()?;
+            // 旧元组仅在主键变化或REPLICA IDENTITY FULL时出现
+            let old = match reader.u8()? {
+                b'K' | b'O' => {
+                    let old = reader.tuple()?;
+                    reader.expect(b'N')?;
+                    Some(old)
+                }
+                b'N' => None,
+                other => return Err(format!("Unexpected update tuple marker {:?}", other as char)),
+            };
+            Message::Change {
+                relation_id,
+                kind: ChangeKind::Update,
+                old,
+                new: Some(reader.tuple()?),
+            }
+        }
+        b'D' => {
+            let relation_id = reader.u32()?;
+            let _marker = reader.u8()?;
+            Message::Change {
+                relation_id,
+                kind: ChangeKind::Delete,
+                old: Some(reader.tuple()?),
+                new: None,
+            }
+        }
+        b'C' => {
+            let _flags = reader.u8()?;
+            let _commit_lsn = reader.u64()?;
+            Message::Commit { end_lsn: reader.u64()? }
+        }
+        b'T' => {
+            let count = reader.u32()?;
+            let _options = reader.u8()?;
+            let relation_ids = (0..count).map(|_| reader.u32()).collect::<Result<_, _>>()?;
+            Message::Truncate(relation_ids)
+        }
+        _ => Message::Other,
+    };
+    Ok(message)
+}
+
+// 大端字节读取
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos + len;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or_else(|| "Truncated logical replication message".to_string())?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn expect(&mut self, marker: u8) -> Result<(), String> {
+        match self.u8()? {
+            b if b == marker => Ok(()),
+            b => Err(format!("Expected tuple marker {:?}, found {:?}", marker as char, b as char)),
+        }
+    }
+
+    fn cstring(&mut self) -> Result<String, String> {
+        let len = self.data[self.pos..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| "Unterminated string in logical replication message".to_string())?;
+        let text = String::from_utf8_lossy(self.take(len)?).into_owned();
+        self.pos += 1;
+        Ok(text)
+    }
+
+    fn tuple(&mut self) -> Result<Vec<TupleValue>, String> {
+        let count = self.u16()?;
+        let mut values = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let value = match self.u8()? {
+                b'n' => TupleValue::Null,
+                b'u' => TupleValue::Unchanged,
+                b't' => {
+                    let len = self.u32()? as usize;
+                    TupleValue::Text(String::from_utf8_lossy(self.take(len)?).into_owned())
+                }
+                other => return Err(format!("Unsupported tuple value kind {:?}", other as char)),
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+// 按类型OID转换pgoutput的文本值
+fn text_value(type_oid: u32, text: String) -> SqlValue {
+    match type_oid {
+        // bool
+        16 => SqlValue::Bool(text == "t"),
+        // int8, int2, int4, oid
+        20 | 21 | 23 | 26 => text.parse().map(SqlValue::Int).unwrap_or(SqlValue::Text(text)),
+        // float4, float8
+        700 | 701 => text.parse().map(SqlValue::Float).unwrap_or(SqlValue::Text(text)),
+        // bytea以 \x 开头的十六进制文本传输
+        17 => match table_copy::decode_hex(text.trim_start_matches("\\x")) {
+            Some(bytes) => SqlValue::Bytes(bytes),
+            None => SqlValue::Text(text),
+        },
+        _ => SqlValue::Text(text),
+    }
+}
@@ -0,0 +1,314 @@
+// SQLite触发器变更捕获 - 用触发器把行变更写入变更日志表，由增量迁移读取并确认
+
+use serde_json::Value as JsonValue;
+use crate::database::{ColumnKind, ColumnSchema, DatabaseType, SqlValue, SqlxDatabaseConnection, TableSchema};
+use crate::migration::cdc::{ChangeEvent, ChangeKind};
+use crate::migration::table_copy;
+
+// 变更日志表
+pub const CHANGELOG_TABLE: &str = "_bodhi_changelog";
+
+// 触发器名称前缀，后接表名和操作
+const TRIGGER_PREFIX: &str = "_bodhi_changelog_";
+
+// SQLite函数参数上限为127，json_object每列占两个参数
+const JSON_COLUMNS_PER_CALL: usize = 60;
+
+// 变更日志中的一条记录
+#[derive(Debug, Clone)]
+pub struct ChangeRecord {
+    // 日志序号，确认时删除不大于该序号的记录
+    pub seq: i64,
+    pub event: ChangeEvent,
+}
+
+// SQLite变更日志
+pub struct SqliteChangelog {
+    conn: SqlxDatabaseConnection,
+}
+
+impl SqliteChangelog {
+    pub fn new(conn: SqlxDatabaseConnection) -> Result<Self, String> {
+        if conn.db_type() != DatabaseType::Sqlite {
+            return Err("Trigger-based change capture is only available for SQLite sources".to_string());
+        }
+        Ok(Self { conn })
+    }
+
+    // 已安装触发器的表
+    pub async fn installed_tables(&self) -> Result<Vec<String>, String> {
+        let result = self
+            .conn
+            .fetch_rows(
+                "SELECT DISTINCT tbl_name FROM sqlite_master WHERE type = 'trigger' AND substr(name, 1, ?) = ? ORDER BY tbl_name",
+                vec![SqlValue::Int(TRIGGER_PREFIX.len() as i64), SqlValue::Text(TRIGGER_PREFIX.to_string())],
+            )
+            .await?;
+        Ok(result
+            .rows
+            .iter()
+            .filter_map(|row| result.get(row, "tbl_name").and_then(|v| v.as_string()))
+            .collect())
+    }
+
+    // 为表安装插入、更新、删除触发器，已安装时重新创建
+    pub async fn install(&self, table: &TableSchema) -> Result<(), String> {
+        self.conn
+            .execute_batch(Self::install_statements(table).into_iter().map(|sql| (sql, Vec::new())).collect())
+            .await?;
+        Ok(())
+    }
+
+    // 安装触发器的语句：创建日志表，重建插入、更新、删除触发器
+    pub fn install_statements(table: &TableSchema) -> Vec<String> {
+        let db_type = DatabaseType::Sqlite;
+        let quoted_table = db_type.quote_identifier(&table.name);
+        let table_literal = sql_literal(&table.name);
+
+        let mut statements = vec![format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                old_row TEXT,
+                new_row TEXT,
+                changed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+            )",
+            CHANGELOG_TABLE
+        )];
+        statements.extend(drop_trigger_statements(&table.name));
+        for (operation, old_row, new_row) in [
+            ("insert", None, Some("NEW")),
+            ("update", Some("OLD"), Some("NEW")),
+            ("delete", Some("OLD"), None),
+        ] {
+            let image = |alias: Option<&str>| match alias {
+                Some(alias) => row_json(alias, &table.columns),
+                None => "NULL".to_string(),
+            };
+            statements.push(format!(
+                "CREATE TRIGGER {} AFTER {} ON {} BEGIN \
+                 INSERT INTO {} (table_name, operation, old_row, new_row) VALUES ({}, '{}', {}, {}); \
+                 END",
+                db_type.quote_identifier(&trigger_name(&table.name, operation)),
+                operation.to_uppercase(),
+                quoted_table,
+                CHANGELOG_TABLE,
+                table_literal,
+                operation,
+                image(old_row),
+                image(new_row)
+            ));
+        }
+        statements
+    }
+
+    // 删除触发器和对应的日志记录，未指定表时全部删除并移除日志表，返回卸载的表
+    pub async fn uninstall(&self, table: Option<&str>) -> Result<Vec<String>, String> {
+        let installed = self.installed_tables().await?;
+        let tables: Vec<String> = match table {
+            Some(table) => installed.into_iter().filter(|t| t == table).collect(),
+            None => installed,
+        };
+
+        let mut statements: Vec<String> = tables.iter().flat_map(|t| drop_trigger_statements(t)).collect();
+        match table {
+            Some(table) => {
+                if self.changelog_exists().await? {
+                    statements.push(format!(
+                        "DELETE FROM {} WHERE table_name = {}",
+                        CHANGELOG_TABLE,
+                        sql_literal(table)
+                    ));
+                }
+            }
+            None => statements.push(format!("DROP TABLE IF EXISTS {}", CHANGELOG_TABLE)),
+        }
+
+        self.conn
+            .execute_batch(statements.into_iter().map(|sql| (sql, Vec::new())).collect())
+            .await?;
+        log::info!("已卸载变更捕获触发器: {}", tables.join(", "));
+        Ok(tables)
+    }
+
+    // 按序号读取指定表的未确认变更
+    pub async fn read(&self, tables: &[&TableSchema], limit: usize) -> Result<Vec<ChangeRecord>, String> {
+        if tables.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; tables.len()].join(", ");
+        let sql = format!(
+            "SELECT seq, table_name, operation, old_row, new_row FROM {} \
+             WHERE table_name IN ({}) ORDER BY seq LIMIT {}",
+            CHANGELOG_TABLE,
+            placeholders,
+            limit.max(1)
+        );
+        let params = tables.iter().map(|t| SqlValue::Text(t.name.clone())).collect();
+        let result = self.conn.fetch_rows(&sql, params).await?;
+
+        let mut records = Vec::with_capacity(result.rows.len());
+        for row in &result.rows {
+            let text = |name: &str| result.get(row, name).and_then(|v| v.as_string());
+            let seq = match result.get(row, "seq") {
+                Some(SqlValue::Int(seq)) => *seq,
+                other => return Err(format!("Invalid changelog sequence: {:?}", other)),
+            };
+            let table_name = text("table_name").unwrap_or_default();
+            let table = tables
+                .iter()
+                .find(|t| t.name == table_name)
+                .ok_or_else(|| format!("Changelog references unknown table {}", table_name))?;
+            let kind = match text("operation").as_deref() {
+                Some("insert") => ChangeKind::Insert,
+                Some("update") => ChangeKind::Update,
+                Some("delete") => ChangeKind::Delete,
+                other => return Err(format!("Invalid changelog operation at seq {}: {:?}", seq, other)),
+            };
+            records.push(ChangeRecord {
+                seq,
+                event: ChangeEvent {
+                    table: table.name.clone(),
+                    kind,
+                    before: text("old_row").map(|json| decode_row(table, &json)).transpose()?,
+                    after: text("new_row").map(|json| decode_row(table, &json)).transpose()?,
+                },
+            });
+        }
+        Ok(records)
+    }
+
+    // 确认变更已写入目标库，删除不大于该序号的记录
+    pub async fn acknowledge(&self, tables: &[&TableSchema], up_to_seq: i64) -> Result<u64, String> {
+        if tables.is_empty() {
+            return Ok(0);
+        }
+        let placeholders = vec!["?"; tables.len()].join(", ");
+        let sql = format!(
+            "DELETE FROM {} WHERE seq <= ? AND table_name IN ({})",
+            CHANGELOG_TABLE, placeholders
+        );
+        let mut params = vec![SqlValue::Int(up_to_seq)];
+        params.extend(tables.iter().map(|t| SqlValue::Text(t.name.clone())));
+        self.conn.execute_values(&sql, params).await
+    }
+
+    // 指定表尚未应用的变更数
+    pub async fn pending(&self, tables: &[&TableSchema]) -> Result<u64, String> {
+        if tables.is_empty() || !self.changelog_exists().await? {
+            return Ok(0);
+        }
+        let placeholders = vec!["?"; tables.len()].join(", ");
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE table_name IN ({})",
+            CHANGELOG_TABLE, placeholders
+        );
+        let params = tables.iter().map(|t| SqlValue::Text(t.name.clone())).collect();
+        let result = self.conn.fetch_rows(&sql, params).await?;
+        Ok(result
+            .rows
+            .first()
+            .and_then(|row| row.first())
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0) as u64)
+    }
+
+    async fn changelog_exists(&self) -> Result<bool, String> {
+        let result = self
+            .conn
+            .fetch_rows(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+                vec![SqlValue::Text(CHANGELOG_TABLE.to_string())],
+            )
+            .await?;
+        Ok(!result.rows.is_empty())
+    }
+}
+
+fn trigger_name(table: &str, operation: &str) -> String {
+    format!("{}{}_{}", TRIGGER_PREFIX, table, operation)
+}
+
+fn drop_trigger_statements(table: &str) -> Vec<String> {
+    ["insert", "update", "delete"]
+        .iter()
+        .map(|operation| {
+            format!(
+                "DROP TRIGGER IF EXISTS {}",
+                DatabaseType::Sqlite.quote_identifier(&trigger_name(table, operation))
+            )
+        })
+        .collect()
+}
+
+fn sql_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+// 把 NEW/OLD 行编码为JSON对象，BLOB值以十六进制文本保存
+fn row_json(alias: &str, columns: &[ColumnSchema]) -> String {
+    let pair = |column: &ColumnSchema| {
+        let value = format!("{}.{}", alias, DatabaseType::Sqlite.quote_identifier(&column.name));
+        (
+            sql_literal(&column.name),
+            format!("CASE typeof({}) WHEN 'blob' THEN hex({}) ELSE {} END", value, value, value),
+        )
+    };
+
+    let mut chunks = columns.chunks(JSON_COLUMNS_PER_CALL);
+    let first: Vec<String> = chunks
+        .next()
+        .unwrap_or(&[])
+        .iter()
+        .map(|c| {
+            let (key, value) = pair(c);
+            format!("{}, {}", key, value)
+        })
+        .collect();
+    let mut json = format!("json_object({})", first.join(", "));
+    // 列数超过单次调用上限时用json_insert追加，保留NULL值
+    for chunk in chunks {
+        let rest: Vec<String> = chunk
+            .iter()
+            .map(|c| {
+                let (_, value) = pair(c);
+                let path = sql_literal(&format!("$.\"{}\"", c.name));
+                format!("{}, {}", path, value)
+            })
+            .collect();
+        json = format!("json_insert({}, {})", json, rest.join(", "));
+    }
+    json
+}
+
+// 按源表列类型解码日志中的JSON行
+fn decode_row(table: &TableSchema, json: &str) -> Result<Vec<(String, SqlValue)>, String> {
+    let object = match serde_json::from_str::<JsonValue>(json) {
+        Ok(JsonValue::Object(object)) => object,
+        Ok(_) => return Err(format!("Changelog row for {} is not a JSON object", table.name)),
+        Err(e) => return Err(format!("Invalid changelog row for {}: {}", table.name, e)),
+    };
+    Ok(table
+        .columns
+        .iter()
+        .filter_map(|column| object.get(&column.name).map(|value| (column.name.clone(), json_value(column, value))))
+        .collect())
+}
+
+fn json_value(column: &ColumnSchema, value: &JsonValue) -> SqlValue {
+    match value {
+        JsonValue::Null => SqlValue::Null,
+        JsonValue::Bool(b) => SqlValue::Bool(*b),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Int(i),
+            None => SqlValue::Float(n.as_f64().unwrap_or_default()),
+        },
+        JsonValue::String(text) if column.kind == ColumnKind::Bytes => match table_copy::decode_hex(text) {
+            Some(bytes) => SqlValue::Bytes(bytes),
+            None => SqlValue::Text(text.clone()),
+        },
+        JsonValue::String(text) => SqlValue::Text(text.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
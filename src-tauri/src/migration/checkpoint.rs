@@ -0,0 +1,301 @@
+// 迁移检查点模块 - 在应用配置库中保存迁移进度
+
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use crate::database::{SqlValue, SqlxDatabaseConnection, UnifiedConnectionManager};
+use crate::migration::task_store::add_column_if_missing;
+
+// 应用配置库的默认连接ID
+pub const DEFAULT_CONFIG_DB_ID: &str = "config_db";
+
+// 增量迁移的高水位标记
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watermark {
+    // 水位列
+    pub column: String,
+    // 已迁移的最大水位值
+    pub value: SqlValue,
+    // 水位值相同时用于区分行的主键列
+    pub key_columns: Vec<String>,
+    // 最后一行的主键值
+    pub last_key: Vec<SqlValue>,
+    // 保存水位时源表的过滤条件，水位之前的行只按该条件复制过
+    #[serde(default)]
+    pub filter: Option<String>,
+    pub updated_at: String,
+}
+
+impl Watermark {
+    // 标记是否按同样的水位列、主键列和过滤条件保存，不一致时已复制的范围不能沿用
+    pub fn matches(&self, column: &str, key_columns: &[String], filter: Option<&str>) -> bool {
+        self.column == column && self.key_columns == key_columns && self.filter.as_deref() == filter
+    }
+}
+
+// 写入失败的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowFailure {
+    // 源结果集中的行号，从1开始
+    pub row_number: u64,
+    pub error: String,
+}
+
+// 任务中单张表（或单个数据源）的复制进度
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TableCheckpoint {
+    // 分页使用的键列，为空时按偏移量恢复
+    pub key_columns: Vec<String>,
+    // 最后一批最后一行的键值，Redis策略中为SCAN游标
+    pub last_key: Vec<SqlValue>,
+    // 已完成的批次数
+    pub batch_number: u64,
+    // 已写入目标端的行数（或键数）
+    pub rows_copied: u64,
+    // 逐行写入失败的行数和其中最先失败的若干行，中断后继续时累计
+    pub rows_failed: u64,
+    pub failed_rows: Vec<RowFailure>,
+    // 目标表由本任务创建，恢复后仍需补充索引和外键
+    pub created_target: bool,
+    pub completed: bool,
+    pub updated_at: String,
+}
+
+// 检查点存储
+#[derive(Clone)]
+pub struct CheckpointStore {
+    conn: SqlxDatabaseConnection,
+}
+
+impl CheckpointStore {
+    // 打开配置库中的检查点存储，必要时创建表
+    pub async fn open(conn_manager: &UnifiedConnectionManager, config_db_id: &str) -> Result<Self, String> {
+        let conn = conn_manager.get_sqlx_connection(config_db_id).await
+            .ok_or_else(|| format!("Checkpoint database connection not found: {}", config_db_id))?;
+
+        // 旧版本按源库和目标库共享的水位无法对应到任务，删除后各任务从头开始增量迁移，目标表按主键写入不会产生重复行
+        conn.execute_raw("DROP TABLE IF EXISTS migration_watermarks").await?;
+        let create_table_sql = r#"
+            CREATE TABLE IF NOT EXISTS migration_task_watermarks (
+                task_id TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                watermark_column TEXT NOT NULL,
+                watermark_value TEXT NOT NULL,
+                key_columns TEXT NOT NULL,
+                last_key TEXT NOT NULL,
+                row_filter TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (task_id, table_name)
+            )
+        "#;
+        conn.execute_raw(create_table_sql).await?;
+        add_column_if_missing(&conn, "migration_task_watermarks", "row_filter", "TEXT").await?;
+
+        let create_table_sql = r#"
+            CREATE TABLE IF NOT EXISTS migration_table_checkpoints (
+                task_id TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                key_columns TEXT NOT NULL,
+                last_key TEXT NOT NULL,
+                batch_number INTEGER NOT NULL,
+                rows_copied INTEGER NOT NULL,
+                rows_failed INTEGER NOT NULL DEFAULT 0,
+                failed_rows TEXT,
+                created_target INTEGER NOT NULL,
+                completed INTEGER NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (task_id, table_name)
+            )
+        "#;
+        conn.execute_raw(create_table_sql).await?;
+        add_column_if_missing(&conn, "migration_table_checkpoints", "rows_failed", "INTEGER NOT NULL DEFAULT 0").await?;
+        add_column_if_missing(&conn, "migration_table_checkpoints", "failed_rows", "TEXT").await?;
+
+        Ok(Self { conn })
+    }
+
+    // 读取任务中一张目标表的高水位标记，各任务的水位互不影响
+    pub async fn load_watermark(&self, task_id: &str, table: &str) -> Result<Option<Watermark>, String> {
+        let sql = r#"
+            SELECT watermark_column, watermark_value, key_columns, last_key, row_filter, updated_at
+            FROM migration_task_watermarks
+            WHERE task_id = ? AND table_name = ?
+        "#;
+        let result = self
+            .conn
+            .fetch_rows(sql, vec![SqlValue::Text(task_id.to_string()), SqlValue::Text(table.to_string())])
+            .await?;
+
+        let row = match result.rows.first() {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let text = |name: &str| result.get(row, name).and_then(|v| v.as_string()).unwrap_or_default();
+
+        Ok(Some(Watermark {
+            column: text("watermark_column"),
+            value: serde_json::from_str(&text("watermark_value"))
+                .map_err(|e| format!("Invalid watermark value for {}: {}", table, e))?,
+            key_columns: serde_json::from_str(&text("key_columns"))
+                .map_err(|e| format!("Invalid watermark key columns for {}: {}", table, e))?,
+            last_key: serde_json::from_str(&text("last_key"))
+                .map_err(|e| format!("Invalid watermark key for {}: {}", table, e))?,
+            filter: result.get(row, "row_filter").and_then(|v| v.as_string()),
+            updated_at: text("updated_at"),
+        }))
+    }
+
+    // 保存任务中一张目标表的高水位标记
+    pub async fn save_watermark(&self, task_id: &str, table: &str, watermark: &Watermark) -> Result<(), String> {
+        let sql = r#"
+            INSERT OR REPLACE INTO migration_task_watermarks
+            (task_id, table_name, watermark_column, watermark_value, key_columns, last_key, row_filter, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#;
+        let params = vec![
+            SqlValue::Text(task_id.to_string()),
+            SqlValue::Text(table.to_string()),
+            SqlValue::Text(watermark.column.clone()),
+            SqlValue::Text(to_json(&watermark.value)?),
+            SqlValue::Text(to_json(&watermark.key_columns)?),
+            SqlValue::Text(to_json(&watermark.last_key)?),
+            watermark.filter.clone().map(SqlValue::Text).unwrap_or(SqlValue::Null),
+            SqlValue::Text(Utc::now().to_rfc3339()),
+        ];
+        self.conn.execute_values(sql, params).await?;
+        Ok(())
+    }
+
+    // 清除任务的水位标记，未指定表时清除任务的全部标记
+    pub async fn clear_watermarks(&self, task_id: &str, table: Option<&str>) -> Result<u64, String> {
+        match table {
+            Some(table) => {
+                self.conn
+                    .execute_values(
+                        "DELETE FROM migration_task_watermarks WHERE task_id = ? AND table_name = ?",
+                        vec![SqlValue::Text(task_id.to_string()), SqlValue::Text(table.to_string())],
+                    )
+                    .await
+            }
+            None => {
+                self.conn
+                    .execute_values(
+                        "DELETE FROM migration_task_watermarks WHERE task_id = ?",
+                        vec![SqlValue::Text(task_id.to_string())],
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+impl CheckpointStore {
+    // 读取任务中一张表的复制进度
+    pub async fn load_table_checkpoint(&self, task_id: &str, table: &str) -> Result<Option<TableCheckpoint>, String> {
+        let sql = r#"
+            SELECT key_columns, last_key, batch_number, rows_copied, rows_failed, failed_rows, created_target, completed, updated_at
+            FROM migration_table_checkpoints
+            WHERE task_id = ? AND table_name = ?
+        "#;
+        let result = self
+            .conn
+            .fetch_rows(sql, vec![SqlValue::Text(task_id.to_string()), SqlValue::Text(table.to_string())])
+            .await?;
+
+        let row = match result.rows.first() {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let text = |name: &str| result.get(row, name).and_then(|v| v.as_string()).unwrap_or_default();
+        let number = |name: &str| result.get(row, name).and_then(|v| v.as_i64()).unwrap_or(0);
+
+        Ok(Some(TableCheckpoint {
+            key_columns: serde_json::from_str(&text("key_columns"))
+                .map_err(|e| format!("Invalid checkpoint key columns for {}: {}", table, e))?,
+            last_key: serde_json::from_str(&text("last_key"))
+                .map_err(|e| format!("Invalid checkpoint key for {}: {}", table, e))?,
+            batch_number: number("batch_number") as u64,
+            rows_copied: number("rows_copied") as u64,
+            rows_failed: number("rows_failed") as u64,
+            failed_rows: match result.get(row, "failed_rows").and_then(|v| v.as_string()) {
+                Some(json) => serde_json::from_str(&json)
+                    .map_err(|e| format!("Invalid checkpoint failed rows for {}: {}", table, e))?,
+                None => Vec::new(),
+            },
+            created_target: number("created_target") != 0,
+            completed: number("completed") != 0,
+            updated_at: text("updated_at"),
+        }))
+    }
+
+    // 保存任务中一张表的复制进度
+    pub async fn save_table_checkpoint(&self, task_id: &str, table: &str, checkpoint: &TableCheckpoint) -> Result<(), String> {
+        let sql = r#"
+            INSERT OR REPLACE INTO migration_table_checkpoints
+            (task_id, table_name, key_columns, last_key, batch_number, rows_copied, rows_failed, failed_rows, created_target, completed, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#;
+        let params = vec![
+            SqlValue::Text(task_id.to_string()),
+            SqlValue::Text(table.to_string()),
+            SqlValue::Text(to_json(&checkpoint.key_columns)?),
+            SqlValue::Text(to_json(&checkpoint.last_key)?),
+            SqlValue::Int(checkpoint.batch_number as i64),
+            SqlValue::Int(checkpoint.rows_copied as i64),
+            SqlValue::Int(checkpoint.rows_failed as i64),
+            SqlValue::Text(to_json(&checkpoint.failed_rows)?),
+            SqlValue::Int(checkpoint.created_target as i64),
+            SqlValue::Int(checkpoint.completed as i64),
+            SqlValue::Text(Utc::now().to_rfc3339()),
+        ];
+        self.conn.execute_values(sql, params).await?;
+        Ok(())
+    }
+
+    // 清除任务的全部复制进度，任务完成或重试时调用
+    pub async fn clear_table_checkpoints(&self, task_id: &str) -> Result<u64, String> {
+        self.conn
+            .execute_values(
+                "DELETE FROM migration_table_checkpoints WHERE task_id = ?",
+                vec![SqlValue::Text(task_id.to_string())],
+            )
+            .await
+    }
+}
+
+// 序列化为JSON文本
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("Failed to serialize checkpoint: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mark(filter: Option<&str>) -> Watermark {
+        Watermark {
+            column: "updated_at".to_string(),
+            value: SqlValue::Text("2024-01-01 00:00:00".to_string()),
+            key_columns: vec!["id".to_string()],
+            last_key: vec![SqlValue::Int(42)],
+            filter: filter.map(str::to_string),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn watermark_matches_same_configuration() {
+        let keys = vec!["id".to_string()];
+        assert!(mark(None).matches("updated_at", &keys, None));
+        assert!(mark(Some("region = 'eu'")).matches("updated_at", &keys, Some("region = 'eu'")));
+        assert!(!mark(None).matches("id", &keys, None));
+        assert!(!mark(None).matches("updated_at", &[], None));
+    }
+
+    #[test]
+    fn watermark_is_stale_after_filter_change() {
+        let keys = vec!["id".to_string()];
+        assert!(!mark(Some("region = 'eu'")).matches("updated_at", &keys, Some("region = 'us'")));
+        assert!(!mark(Some("region = 'eu'")).matches("updated_at", &keys, None));
+        assert!(!mark(None).matches("updated_at", &keys, Some("region = 'eu'")));
+    }
+}
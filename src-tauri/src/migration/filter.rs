@@ -0,0 +1,237 @@
+// 行过滤模块 - 按表配置的WHERE条件只迁移源表的部分行，条件下推到源库的查询中执行
+
+use std::collections::BTreeMap;
+use crate::database::{DatabaseSchema, SqlxDatabaseConnection, TableSchema};
+
+// 条件中可以使用的关键字
+const KEYWORDS: &[&str] = &[
+    "AND", "OR", "NOT", "IS", "NULL", "IN", "BETWEEN", "LIKE", "ILIKE", "ESCAPE", "TRUE", "FALSE",
+    "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP", "INTERVAL",
+];
+
+// 条件中可以调用的函数
+const FUNCTIONS: &[&str] = &[
+    "LOWER", "UPPER", "TRIM", "LENGTH", "COALESCE", "ABS", "ROUND", "SUBSTR", "SUBSTRING", "DATE", "DATETIME", "NOW",
+];
+
+// 源表配置的过滤条件，表名不区分大小写
+pub fn filter_for<'a>(filters: &'a BTreeMap<String, String>, table: &str) -> Option<&'a str> {
+    filters
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(table))
+        .map(|(_, predicate)| predicate.as_str())
+}
+
+// 把过滤条件与其他条件合并为WHERE子句，都为空时返回空字符串
+pub fn where_clause(filter: Option<&str>, condition: &str) -> String {
+    match (filter, condition.is_empty()) {
+        (Some(filter), true) => format!(" WHERE ({})", filter),
+        (Some(filter), false) => format!(" WHERE ({}) AND ({})", filter, condition),
+        (None, true) => String::new(),
+        (None, false) => format!(" WHERE {}", condition),
+    }
+}
+
+// 检查条件只由表的列、字面量、运算符、白名单中的关键字和函数组成：不允许子查询、多条语句、注释和绑定参数
+pub fn check_filter(table: &TableSchema, predicate: &str) -> Result<(), String> {
+    let invalid = |reason: String| Err(format!("Invalid filter for table {}: {}", table.name, reason));
+    let chars: Vec<char> = predicate.chars().collect();
+    if chars.iter().all(|c| c.is_whitespace()) {
+        return invalid("filter is empty".to_string());
+    }
+    let column = |name: &str| table.columns.iter().any(|c| c.name.eq_ignore_ascii_case(name));
+
+    let mut depth = 0i32;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '\'' => {
+                // 字符串中两个单引号表示一个单引号；MySQL还把反斜杠当作转义符，各方言理解不同，一律拒绝
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return invalid("unterminated string literal".to_string()),
+                        Some('\\') => return invalid("backslashes are not allowed in string literals".to_string()),
+                        Some('\'') if chars.get(i + 1) == Some(&'\'') => i += 2,
+                        Some('\'') => break,
+                        Some(_) => i += 1,
+                    }
+                }
+                i += 1;
+            }
+            '"' | '`' => {
+                let end = match chars[i + 1..].iter().position(|&q| q == c) {
+                    Some(offset) => i + 1 + offset,
+                    None => return invalid("unterminated quoted identifier".to_string()),
+                };
+                let name: String = chars[i + 1..end].iter().collect();
+                if !column(&name) {
+                    return invalid(format!("unknown column {}", name));
+                }
+                i = end + 1;
+            }
+            _ if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) => {
+                while chars.get(i).is_some_and(|d| d.is_ascii_alphanumeric() || *d == '.') {
+                    i += 1;
+                }
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while chars.get(i).is_some_and(|d| d.is_alphanumeric() || *d == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let upper = word.to_ascii_uppercase();
+                let next = chars[i..].iter().find(|d| !d.is_whitespace());
+                // 关键字后可以跟括号，如IN (...)；子查询的SELECT不在白名单中
+                if KEYWORDS.contains(&upper.as_str()) {
+                    continue;
+                }
+                if next == Some(&'(') {
+                    if !FUNCTIONS.contains(&upper.as_str()) {
+                        return invalid(format!("function {} is not allowed", word));
+                    }
+                } else if !column(&word) {
+                    return invalid(format!("unknown column or keyword {}", word));
+                }
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => return invalid("comments are not allowed".to_string()),
+            '/' if chars.get(i + 1) == Some(&'*') => return invalid("comments are not allowed".to_string()),
+            '(' => {
+                depth += 1;
+                i += 1;
+            }
+            ')' => {
+                depth -= 1;
+                if depth < 0 {
+                    return invalid("unbalanced parentheses".to_string());
+                }
+                i += 1;
+            }
+            '=' | '<' | '>' | '!' | '+' | '-' | '*' | '/' | '%' | '|' | ',' => i += 1,
+            _ => return invalid(format!("unexpected character {:?}", c)),
+        }
+    }
+    if depth != 0 {
+        return invalid("unbalanced parentheses".to_string());
+    }
+    Ok(())
+}
+
+// 校验全部过滤条件：源表存在且在迁移范围内，条件安全，并在源库上试执行一次确认语法和类型正确；tables为空时为源库全部表
+pub async fn validate_filters(
+    filters: &BTreeMap<String, String>,
+    schema: &DatabaseSchema,
+    tables: &[String],
+    source_conn: &SqlxDatabaseConnection,
+) -> Result<(), String> {
+    let db_type = source_conn.db_type();
+    for (name, predicate) in filters {
+        let table = schema
+            .table(name)
+            .ok_or_else(|| format!("Filtered source table not found: {}", name))?;
+        if !tables.is_empty() && !tables.iter().any(|t| t.eq_ignore_ascii_case(&table.name)) {
+            return Err(format!("Filtered table {} is not migrated by this task", table.name));
+        }
+        check_filter(table, predicate)?;
+        let sql = format!(
+            "SELECT 1 FROM {}{} LIMIT 1",
+            db_type.quote_identifier(&table.name),
+            where_clause(Some(predicate), "")
+        );
+        source_conn
+            .fetch_rows(&sql, vec![])
+            .await
+            .map_err(|e| format!("Invalid filter for table {}: {}", table.name, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{ColumnSchema, DatabaseType};
+
+    fn orders() -> TableSchema {
+        let db_type = DatabaseType::MySql;
+        TableSchema {
+            name: "orders".to_string(),
+            columns: vec![
+                ColumnSchema::new(&db_type, "id".to_string(), "INT".to_string(), false),
+                ColumnSchema::new(&db_type, "region".to_string(), "VARCHAR(10)".to_string(), true),
+                ColumnSchema::new(&db_type, "note".to_string(), "TEXT".to_string(), true),
+            ],
+            primary_key: vec!["id".to_string()],
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+        }
+    }
+
+    fn rejects(predicate: &str, reason: &str) {
+        let err = check_filter(&orders(), predicate).unwrap_err();
+        assert!(err.contains(reason), "{}: {}", predicate, err);
+    }
+
+    #[test]
+    fn accepts_simple_predicates() {
+        for predicate in [
+            "id <= 150 AND region = 'eu'",
+            "note LIKE 'it''s %' OR note IS NULL",
+            "LOWER(\"region\") IN ('eu', 'us') AND `id` BETWEEN 1 AND 10",
+            "NOT (id % 2 = 0) AND id <> -1.5",
+        ] {
+            assert!(check_filter(&orders(), predicate).is_ok(), "{}", predicate);
+        }
+    }
+
+    #[test]
+    fn rejects_backslash_escapes() {
+        rejects(r"region = '\' OR region=' OR (SELECT 1)=1 -- '", "backslashes");
+        rejects(r"note = 'a\'", "backslashes");
+    }
+
+    #[test]
+    fn quote_escapes_stay_inside_the_literal() {
+        assert!(check_filter(&orders(), "note = ''' OR 1=1 --'").is_ok());
+        rejects("note = 'x'' OR 1=1", "unterminated string literal");
+    }
+
+    #[test]
+    fn rejects_comments_statements_and_subqueries() {
+        rejects("id = 1 -- x", "comments");
+        rejects("id = 1 /* x */", "comments");
+        rejects("id = 1 # x", "unexpected character");
+        rejects("1=1; DROP TABLE orders", "unexpected character ';'");
+        rejects("id IN (SELECT id FROM users)", "unknown column or keyword SELECT");
+        rejects("EXISTS (SELECT 1)", "function EXISTS is not allowed");
+        rejects("id = ?", "unexpected character '?'");
+        rejects("id = $1", "unexpected character '$'");
+    }
+
+    #[test]
+    fn rejects_unknown_names_and_malformed_input() {
+        rejects("ghost = 1", "unknown column or keyword ghost");
+        rejects("\"ghost\" = 1", "unknown column ghost");
+        rejects("pg_sleep(1) IS NULL", "function pg_sleep is not allowed");
+        rejects("(id = 1", "unbalanced parentheses");
+        rejects("id = 1)", "unbalanced parentheses");
+        rejects("   ", "filter is empty");
+    }
+
+    #[test]
+    fn where_clause_combines_filter_and_condition() {
+        assert_eq!(where_clause(Some("a = 1"), "b > 2"), " WHERE (a = 1) AND (b > 2)");
+        assert_eq!(where_clause(Some("a = 1"), ""), " WHERE (a = 1)");
+        assert_eq!(where_clause(None, "b > 2"), " WHERE b > 2");
+        assert_eq!(where_clause(None, ""), "");
+    }
+
+    #[test]
+    fn filter_lookup_ignores_case() {
+        let filters = BTreeMap::from([("ORDERS".to_string(), "id = 1".to_string())]);
+        assert_eq!(filter_for(&filters, "orders"), Some("id = 1"));
+        assert_eq!(filter_for(&filters, "users"), None);
+    }
+}
@@ -0,0 +1,355 @@
+// 表和列映射模块 - 按任务配置的规则把源表、源列映射到名称或结构不同的目标表
+
+use serde::{Deserialize, Serialize};
+use crate::database::{ColumnSchema, DatabaseSchema, DatabaseType, ForeignKeySchema, IndexSchema, SqlRowSet, SqlValue, SqlxDatabaseConnection, TableSchema};
+use crate::migration::cdc::ChangeEvent;
+
+// 源列改名后写入的目标列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnRename {
+    pub source: String,
+    pub target: String,
+}
+
+// 不来自源列的目标列：value为常量时每行写入该值，为空时不写入，由目标列的默认值填充
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilledColumn {
+    pub column: String,
+    #[serde(default)]
+    pub value: Option<SqlValue>,
+}
+
+// 一张表的列映射规则，未提及的源列按原名写入
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ColumnRules {
+    #[serde(default)]
+    pub rename_columns: Vec<ColumnRename>,
+    // 不迁移的源列
+    #[serde(default)]
+    pub drop_columns: Vec<String>,
+    #[serde(default)]
+    pub fill_columns: Vec<FilledColumn>,
+}
+
+impl ColumnRules {
+    pub fn is_empty(&self) -> bool {
+        self.rename_columns.is_empty() && self.drop_columns.is_empty() && self.fill_columns.is_empty()
+    }
+
+    // 源列对应的目标列名，删除的列返回None
+    pub fn target_column<'a>(&'a self, source: &'a str) -> Option<&'a str> {
+        if self.drop_columns.iter().any(|c| c.eq_ignore_ascii_case(source)) {
+            return None;
+        }
+        Some(
+            self.rename_columns
+                .iter()
+                .find(|r| r.source.eq_ignore_ascii_case(source))
+                .map(|r| r.target.as_str())
+                .unwrap_or(source),
+        )
+    }
+
+    // 按规则配对源表与目标表的列，目标表中没有的列不写入
+    pub fn column_pairs<'a>(
+        &self,
+        source: &'a TableSchema,
+        target: &'a TableSchema,
+    ) -> Vec<(&'a ColumnSchema, &'a ColumnSchema)> {
+        source
+            .columns
+            .iter()
+            .filter_map(|s| self.target_column(&s.name).and_then(|name| target.column(name)).map(|t| (s, t)))
+            .collect()
+    }
+
+    // 写入常量的目标列及其值
+    pub fn constant_columns<'a>(&self, target: &'a TableSchema) -> Vec<(&'a ColumnSchema, SqlValue)> {
+        self.fill_columns
+            .iter()
+            .filter_map(|f| Some((target.column(&f.column)?, f.value.clone()?)))
+            .collect()
+    }
+
+    // 按规则改写一行的列名，删除的列去掉；with_constants为true时追加常量列
+    fn map_row(&self, row: Vec<(String, SqlValue)>, with_constants: bool) -> Vec<(String, SqlValue)> {
+        let mut mapped: Vec<(String, SqlValue)> = row
+            .into_iter()
+            .filter_map(|(name, value)| self.target_column(&name).map(|target| (target.to_string(), value)))
+            .collect();
+        if with_constants {
+            for filled in &self.fill_columns {
+                if let Some(value) = &filled.value {
+                    mapped.push((filled.column.clone(), value.clone()));
+                }
+            }
+        }
+        mapped
+    }
+
+    // 按规则改写查询结果：删除的列去掉，改名的列换成目标列名，并追加常量列
+    pub fn apply_to_rows(&self, rows: &mut SqlRowSet) {
+        if self.is_empty() || rows.columns.is_empty() {
+            return;
+        }
+        let kept: Vec<(usize, String)> = rows
+            .columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| self.target_column(name).map(|target| (i, target.to_string())))
+            .collect();
+        let constants: Vec<&FilledColumn> = self.fill_columns.iter().filter(|f| f.value.is_some()).collect();
+
+        rows.columns = kept
+            .iter()
+            .map(|(_, name)| name.clone())
+            .chain(constants.iter().map(|f| f.column.clone()))
+            .collect();
+        for row in rows.rows.iter_mut() {
+            let mut values = std::mem::take(row);
+            *row = kept
+                .iter()
+                .map(|(i, _)| std::mem::replace(&mut values[*i], SqlValue::Null))
+                .chain(constants.iter().filter_map(|f| f.value.clone()))
+                .collect();
+        }
+    }
+
+    // 检查规则与源列是否一致：改名和删除的列必须存在，目标列名不能重复
+    pub fn validate(&self, table: &str, source_columns: &[String]) -> Result<(), String> {
+        let exists = |name: &str| source_columns.iter().any(|c| c.eq_ignore_ascii_case(name));
+        for rename in &self.rename_columns {
+            if !exists(&rename.source) {
+                return Err(format!("Renamed column {}.{} not found in source", table, rename.source));
+            }
+            if rename.target.trim().is_empty() {
+                return Err(format!("Column {}.{} is renamed to an empty name", table, rename.source));
+            }
+            if self.drop_columns.iter().any(|c| c.eq_ignore_ascii_case(&rename.source)) {
+                return Err(format!("Column {}.{} is both renamed and dropped", table, rename.source));
+            }
+            if self.rename_columns.iter().filter(|r| r.source.eq_ignore_ascii_case(&rename.source)).count() > 1 {
+                return Err(format!("Column {}.{} is renamed more than once", table, rename.source));
+            }
+        }
+        for column in &self.drop_columns {
+            if !exists(column) {
+                return Err(format!("Dropped column {}.{} not found in source", table, column));
+            }
+        }
+        for filled in &self.fill_columns {
+            if filled.column.trim().is_empty() {
+                return Err(format!("Filled column of table {} has an empty name", table));
+            }
+        }
+
+        // 改名后的源列和填充列共同构成目标列，不能重名
+        let mut names: Vec<&str> = source_columns.iter().filter_map(|c| self.target_column(c)).collect();
+        names.extend(self.fill_columns.iter().map(|f| f.column.as_str()));
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                return Err(format!("Target column {}.{} is mapped more than once", table, name));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 一张源表的映射规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableMapping {
+    pub source_table: String,
+    // 目标表名，为空时与源表同名
+    #[serde(default)]
+    pub target_table: Option<String>,
+    #[serde(flatten)]
+    pub columns: ColumnRules,
+}
+
+impl TableMapping {
+    // 不做任何改写的映射
+    pub fn identity(source_table: &str) -> Self {
+        Self {
+            source_table: source_table.to_string(),
+            target_table: None,
+            columns: ColumnRules::default(),
+        }
+    }
+
+    pub fn target_table(&self) -> &str {
+        self.target_table.as_deref().unwrap_or(&self.source_table)
+    }
+}
+
+// 源表的映射规则，没有配置时按原表名、原列名迁移
+pub fn mapping_for(mappings: &[TableMapping], source_table: &str) -> TableMapping {
+    match mappings.iter().find(|m| m.source_table.eq_ignore_ascii_case(source_table)) {
+        Some(mapping) => TableMapping {
+            source_table: source_table.to_string(),
+            ..mapping.clone()
+        },
+        None => TableMapping::identity(source_table),
+    }
+}
+
+// 把以源表、源列标识的变更改写为目标表、目标列，插入和更新后的行追加常量列
+pub fn map_event(mappings: &[TableMapping], event: ChangeEvent) -> ChangeEvent {
+    let mapping = match mappings.iter().find(|m| m.source_table.eq_ignore_ascii_case(&event.table)) {
+        Some(mapping) => mapping,
+        None => return event,
+    };
+    ChangeEvent {
+        table: mapping.target_table.clone().unwrap_or(event.table),
+        kind: event.kind,
+        before: event.before.map(|row| mapping.columns.map_row(row, false)),
+        after: event.after.map(|row| mapping.columns.map_row(row, true)),
+    }
+}
+
+// 按映射规则改写整个源库的结构，用于解析外键引用的目标表主键
+pub fn project_schema(mappings: &[TableMapping], schema: &DatabaseSchema) -> DatabaseSchema {
+    DatabaseSchema {
+        db_type: schema.db_type.clone(),
+        tables: schema.tables.iter().map(|table| project_table(mappings, schema, table)).collect(),
+    }
+}
+
+// 按映射规则推断目标表在源库方言下的结构，用于生成建表DDL：表名和列名已改写，删除的列及引用它的主键、索引和外键不再出现
+pub fn project_table(mappings: &[TableMapping], schema: &DatabaseSchema, table: &TableSchema) -> TableSchema {
+    let mapping = mapping_for(mappings, &table.name);
+    let rules = &mapping.columns;
+    let rename_all = |rules: &ColumnRules, names: &[String]| {
+        names
+            .iter()
+            .map(|name| rules.target_column(name).map(str::to_string))
+            .collect::<Option<Vec<String>>>()
+    };
+
+    let mut columns: Vec<ColumnSchema> = table
+        .columns
+        .iter()
+        .filter_map(|column| {
+            Some(ColumnSchema {
+                name: rules.target_column(&column.name)?.to_string(),
+                ..column.clone()
+            })
+        })
+        .collect();
+    for filled in &rules.fill_columns {
+        if let Some(value) = &filled.value {
+            columns.push(constant_column(&schema.db_type, &filled.column, value));
+        }
+    }
+
+    let indexes = table
+        .indexes
+        .iter()
+        .filter_map(|index| {
+            Some(IndexSchema {
+                columns: rename_all(rules, &index.columns)?,
+                ..index.clone()
+            })
+        })
+        .collect();
+    let foreign_keys = table
+        .foreign_keys
+        .iter()
+        .filter_map(|fk| {
+            let referenced = mapping_for(mappings, &fk.referenced_table);
+            Some(ForeignKeySchema {
+                columns: rename_all(rules, &fk.columns)?,
+                referenced_table: referenced.target_table().to_string(),
+                referenced_columns: rename_all(&referenced.columns, &fk.referenced_columns)?,
+                ..fk.clone()
+            })
+        })
+        .collect();
+
+    TableSchema {
+        name: mapping.target_table().to_string(),
+        columns,
+        // 主键列被删除时目标表不设主键
+        primary_key: rename_all(rules, &table.primary_key).unwrap_or_default(),
+        indexes,
+        foreign_keys,
+    }
+}
+
+// 常量列在源库方言下的声明类型，按常量值的类型推断
+fn constant_column(db_type: &DatabaseType, name: &str, value: &SqlValue) -> ColumnSchema {
+    let data_type = match (value, db_type) {
+        (SqlValue::Bool(_), DatabaseType::MySql) => "TINYINT(1)",
+        (SqlValue::Bool(_), _) => "BOOLEAN",
+        (SqlValue::Int(_), DatabaseType::Sqlite) => "INTEGER",
+        (SqlValue::Int(_), _) => "BIGINT",
+        (SqlValue::Float(_), DatabaseType::PostgreSql) => "DOUBLE PRECISION",
+        (SqlValue::Float(_), DatabaseType::MySql) => "DOUBLE",
+        (SqlValue::Float(_), _) => "REAL",
+        (SqlValue::Bytes(_), DatabaseType::PostgreSql) => "BYTEA",
+        (SqlValue::Bytes(_), _) => "BLOB",
+        _ => "TEXT",
+    };
+    ColumnSchema::new(db_type, name.to_string(), data_type.to_string(), true)
+}
+
+// 按两端的表结构校验映射规则：源表和源列必须存在，目标表名不能重复；目标表已存在时改名和填充的列必须存在，
+// 目标表需新建时不能使用默认值填充。tables为迁移的源表，为空时为源库全部表
+pub async fn validate_mappings(
+    mappings: &[TableMapping],
+    tables: &[String],
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+) -> Result<(), String> {
+    let schema = source_conn.introspect().await?;
+    let selected = |name: &str| tables.is_empty() || tables.iter().any(|t| t.eq_ignore_ascii_case(name));
+
+    for mapping in mappings {
+        let source = schema
+            .table(&mapping.source_table)
+            .ok_or_else(|| format!("Mapped source table not found: {}", mapping.source_table))?;
+        if !selected(&source.name) {
+            return Err(format!("Mapped table {} is not migrated by this task", source.name));
+        }
+        if mapping.target_table().trim().is_empty() {
+            return Err(format!("Table {} is mapped to an empty target table name", source.name));
+        }
+        let source_columns: Vec<String> = source.columns.iter().map(|c| c.name.clone()).collect();
+        let rules = &mapping.columns;
+        rules.validate(&source.name, &source_columns)?;
+
+        let target_name = mapping.target_table();
+        match target_conn.get_table_schema(target_name).await? {
+            Some(target) => {
+                let columns = rules
+                    .rename_columns
+                    .iter()
+                    .map(|r| r.target.as_str())
+                    .chain(rules.fill_columns.iter().map(|f| f.column.as_str()));
+                for column in columns {
+                    if target.column(column).is_none() {
+                        return Err(format!("Target column {}.{} not found", target.name, column));
+                    }
+                }
+            }
+            None => {
+                if let Some(filled) = rules.fill_columns.iter().find(|f| f.value.is_none()) {
+                    return Err(format!(
+                        "Column {}.{} is filled by its default value but target table {} does not exist",
+                        target_name, filled.column, target_name
+                    ));
+                }
+            }
+        }
+    }
+
+    // 多张源表不能写入同一张目标表
+    let mut targets: Vec<(&str, String)> = Vec::new();
+    for table in schema.tables.iter().filter(|t| selected(&t.name)) {
+        let target = mapping_for(mappings, &table.name).target_table().to_string();
+        if let Some((other, _)) = targets.iter().find(|(_, t)| t.eq_ignore_ascii_case(&target)) {
+            return Err(format!("Tables {} and {} are both mapped to target table {}", other, table.name, target));
+        }
+        targets.push((&table.name, target));
+    }
+    Ok(())
+}
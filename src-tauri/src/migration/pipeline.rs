@@ -2,16 +2,37 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use crate::database::UnifiedConnectionManager;
 use crate::migration::MigrationTask;
+use crate::migration::checkpoint::CheckpointStore;
+use crate::migration::plan::MigrationPlan;
+use crate::migration::progress::ProgressSink;
+use crate::migration::strategy;
+use crate::migration::table_copy::RowRules;
+use crate::migration::table_diff::{self, DiffOptions, TableDiff};
 use crate::migration::task::TaskStatus;
 use crate::migration::task::TaskProgress;
+use crate::migration::task::{TaskContext, TaskRun, TaskSignal};
+use crate::migration::task_log::{LogSink, TaskLogPage, TaskLogQuery, TaskLogStore, TaskLogger};
+use crate::migration::task_store::TaskStore;
+use crate::migration::verify::{self, VerificationReport};
 
 // 迁移流水线
 pub struct MigrationPipeline {
     tasks: Arc<RwLock<HashMap<String, MigrationTask>>>,
+    // 正在执行的任务的控制信号，加锁顺序为先 controls 后 tasks
+    controls: Arc<RwLock<HashMap<String, watch::Sender<TaskSignal>>>>,
     conn_manager: Arc<UnifiedConnectionManager>,
+    // 保存表复制进度的配置库连接ID，应用初始化后设置
+    checkpoint_db_id: Option<String>,
+    // 任务的持久化存储，任务状态变化后写入
+    task_store: Option<TaskStore>,
+    // 执行中任务的进度事件接收方
+    progress_sink: Option<ProgressSink>,
+    // 任务日志的存储和实时推送
+    log_store: Option<TaskLogStore>,
+    log_sink: Option<LogSink>,
 }
 
 impl MigrationPipeline {
@@ -19,16 +40,70 @@ impl MigrationPipeline {
     pub fn new(conn_manager: Arc<UnifiedConnectionManager>) -> Self {
         Self {
             tasks: Arc::new(RwLock::new(HashMap::new())),
+            controls: Arc::new(RwLock::new(HashMap::new())),
             conn_manager,
+            checkpoint_db_id: None,
+            task_store: None,
+            progress_sink: None,
+            log_store: None,
+            log_sink: None,
         }
     }
     
+    // 设置进度事件的接收方，之后开始执行的任务把进度推送给它
+    pub fn set_progress_sink(&mut self, sink: ProgressSink) {
+        self.progress_sink = Some(sink);
+    }
+    
+    // 设置任务日志的接收方，用于实时查看日志
+    pub fn set_log_sink(&mut self, sink: LogSink) {
+        self.log_sink = Some(sink);
+    }
+    
+    // 关联应用配置库：加载已保存的任务，之后的任务变化和表复制进度都写入该库，返回加载的任务数
+    pub async fn attach_config_db(&mut self, config_db_id: &str) -> Result<usize, String> {
+        let store = TaskStore::open(&self.conn_manager, config_db_id).await?;
+        let saved = store.load_tasks().await?;
+        
+        // 内存中已有的任务（如重复初始化）以内存中的状态为准
+        let mut loaded = Vec::new();
+        {
+            let mut tasks = self.tasks.write().await;
+            for task in saved {
+                if !tasks.contains_key(&task.id) {
+                    loaded.push(task.id.clone());
+                    tasks.insert(task.id.clone(), task);
+                }
+            }
+        }
+        // 重启前执行中的任务已标记为失败，写回存储
+        for id in &loaded {
+            store.sync_task(&self.tasks, id).await?;
+        }
+        store.fail_interrupted_runs().await?;
+        
+        let log_store = TaskLogStore::open(&self.conn_manager, config_db_id).await?;
+        log_store.prune(None).await?;
+        
+        self.checkpoint_db_id = Some(config_db_id.to_string());
+        self.task_store = Some(store);
+        self.log_store = Some(log_store);
+        log::info!("已从配置库加载 {} 个迁移任务", loaded.len());
+        Ok(loaded.len())
+    }
+    
     // 添加任务到流水线
-    pub async fn add_task(&self, task: MigrationTask) -> String {
+    pub async fn add_task(&self, task: MigrationTask) -> Result<String, String> {
+        // 映射规则或转换器与两端的表结构不符时拒绝创建；校验在加锁前完成，任务表只在插入时加锁
+        task.strategy
+            .validate(&task.source_db_id, &task.target_db_id, self.conn_manager.clone())
+            .await?;
         let id = task.id.clone();
         let mut tasks = self.tasks.write().await;
         tasks.insert(id.clone(), task);
-        id
+        drop(tasks);
+        self.persist_task(&id).await?;
+        Ok(id)
     }
     
     // 获取任务
@@ -52,62 +127,104 @@ impl MigrationPipeline {
             return Err(format!("Task not found: {}", id));
         }
         
-        tasks.insert(id, task);
-        Ok(())
+        tasks.insert(id.clone(), task);
+        drop(tasks);
+        self.persist_task(&id).await
     }
     
-    // 开始任务
+    // 开始任务，暂停中的任务仍在执行时直接恢复；失败或中断的任务从表复制检查点继续
     pub async fn start_task(&self, id: &str) -> Result<(), String> {
-        let mut task = self.get_task(id).await
+        let mut controls = self.controls.write().await;
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(id)
             .ok_or_else(|| format!("Task not found: {}", id))?;
         
-        // 开始任务
-        task.start(self.conn_manager.clone()).await?;
+        if let Some(control) = controls.get(id) {
+            task.resume()?;
+            control.send_replace(TaskSignal::Run);
+            drop(tasks);
+            drop(controls);
+            self.task_logger(id, None).info("任务已恢复").await;
+            return self.persist_task(id).await;
+        }
         
-        // 更新任务状态
-        self.update_task(task).await?;
+        // 开始任务
+        task.start()?;
+        let (control, signal) = watch::channel(TaskSignal::Run);
+        controls.insert(id.to_string(), control);
+        drop(tasks);
+        drop(controls);
+        self.persist_task(id).await?;
         
-        // 在实际应用中，这里会启动一个单独的异步任务来执行迁移
         let pipeline = self.clone();
         let id = id.to_string();
         tokio::spawn(async move {
-            if let Err(err) = pipeline.execute_task(&id).await {
-                // 记录错误，实际应用中应该有更好的错误处理
-                eprintln!("Error executing task {}: {}", id, err);
+            if let Err(err) = pipeline.execute_task(&id, signal).await {
+                pipeline.task_logger(&id, None).error(format!("任务执行出错: {}", err)).await;
             }
         });
         
         Ok(())
     }
     
-    // 暂停任务
+    // 恢复暂停的任务，执行已结束时（如应用重启后）重新启动，CDC任务从检查点继续
+    pub async fn resume_task(&self, id: &str) -> Result<(), String> {
+        let task = self.get_task(id).await
+            .ok_or_else(|| format!("Task not found: {}", id))?;
+        if task.status != TaskStatus::Paused {
+            return Err(format!("Cannot resume task in {} status", task.status_to_string()));
+        }
+        self.start_task(id).await
+    }
+    
+    // 暂停任务，策略在当前批次结束后停下
     pub async fn pause_task(&self, id: &str) -> Result<(), String> {
-        let mut task = self.get_task(id).await
+        let controls = self.controls.read().await;
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(id)
             .ok_or_else(|| format!("Task not found: {}", id))?;
         
         task.pause()?;
-        self.update_task(task).await?;
+        if let Some(control) = controls.get(id) {
+            control.send_replace(TaskSignal::Pause);
+        }
+        drop(tasks);
+        drop(controls);
         
-        Ok(())
+        self.task_logger(id, None).info("已请求暂停任务，当前批次结束后暂停").await;
+        self.persist_task(id).await
     }
     
-    // 取消任务
+    // 取消任务，策略在当前批次结束后退出
     pub async fn cancel_task(&self, id: &str) -> Result<(), String> {
-        let mut task = self.get_task(id).await
+        let controls = self.controls.read().await;
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(id)
             .ok_or_else(|| format!("Task not found: {}", id))?;
         
         task.cancel()?;
-        self.update_task(task).await?;
+        if let Some(control) = controls.get(id) {
+            control.send_replace(TaskSignal::Cancel);
+        }
+        drop(tasks);
+        drop(controls);
         
-        Ok(())
+        self.task_logger(id, None).info("任务已取消").await;
+        self.persist_task(id).await
     }
     
     // 重试任务
     pub async fn retry_task(&self, id: &str) -> Result<(), String> {
+        if self.controls.read().await.contains_key(id) {
+            return Err(format!("Task is still executing: {}", id));
+        }
         let mut task = self.get_task(id).await
             .ok_or_else(|| format!("Task not found: {}", id))?;
         
-        // 重置任务状态
+        // 重置任务状态，丢弃上次执行的表复制进度
+        if let Some(store) = self.checkpoint_store().await? {
+            store.clear_table_checkpoints(id).await?;
+        }
         task.status = TaskStatus::Created;
         task.error = None;
         task.progress = TaskProgress::new();
@@ -123,32 +240,275 @@ impl MigrationPipeline {
     }
     
     // 执行任务（内部方法）
-    async fn execute_task(&self, id: &str) -> Result<(), String> {
-        let mut task = self.get_task(id).await
+    async fn execute_task(&self, id: &str, signal: watch::Receiver<TaskSignal>) -> Result<(), String> {
+        let task = self.get_task(id).await
             .ok_or_else(|| format!("Task not found: {}", id))?;
         
-        // 确保任务状态是Running
-        if task.status != TaskStatus::Running {
+        // 确保任务状态是Running，启动后立即暂停的任务由策略在第一个批次前等待
+        if task.status != TaskStatus::Running && task.status != TaskStatus::Paused {
+            self.controls.write().await.remove(id);
             return Err(format!("Task is not running: {}", id));
         }
         
-        // 执行迁移策略
-        match task.strategy.execute(&task.source_db_id, &task.target_db_id, self.conn_manager.clone()).await {
+        let mut run = self.start_run(id).await;
+        let logger = self.task_logger(id, Some(&run.id));
+        logger.info(format!("开始第 {} 次执行", run.run_number)).await;
+        
+        // 执行迁移策略，配置库不可用时仍可执行，但中断后只能从头开始
+        let mut ctx = TaskContext::new(id.to_string(), self.tasks.clone(), signal).with_logger(logger.clone());
+        let checkpoints = match self.checkpoint_store().await {
+            Ok(checkpoints) => checkpoints,
+            Err(err) => {
+                logger.warning(format!("无法打开检查点存储，任务 {} 中断后将从头开始: {}", id, err)).await;
+                None
+            }
+        };
+        if let Some(store) = &checkpoints {
+            ctx = ctx.with_checkpoints(store.clone());
+        }
+        if let Some(store) = &self.task_store {
+            ctx = ctx.with_task_store(store.clone());
+        }
+        if let Some(sink) = &self.progress_sink {
+            ctx = ctx.with_progress_sink(sink.clone());
+        }
+        let result = task.strategy.execute(&task.source_db_id, &task.target_db_id, self.conn_manager.clone(), &ctx).await;
+        
+        // 执行期间任务可能被暂停、取消或回写了检查点，以最新的任务状态为准
+        let mut controls = self.controls.write().await;
+        controls.remove(id);
+        let mut tasks = self.tasks.write().await;
+        let current = tasks.get_mut(id)
+            .ok_or_else(|| format!("Task not found: {}", id))?;
+        if current.status == TaskStatus::Canceled {
+            run.finish(TaskStatus::Canceled, result.err(), ctx.run_stats());
+            drop(tasks);
+            drop(controls);
+            self.finish_run(&run).await;
+            ctx.progress().flush().await;
+            logger.info("任务已停止执行").await;
+            return self.persist_task(id).await;
+        }
+        
+        let completed = match result {
             Ok(_) => {
-                // 任务成功完成
-                task.complete()?;
+                // 任务成功完成，下次执行不再从检查点继续
+                current.complete()?;
+                true
             },
             Err(err) => {
                 // 任务失败
-                task.fail(err)?;
+                current.fail(err)?;
+                false
             },
-        }
+        };
+        run.finish(current.status.clone(), current.error.clone(), ctx.run_stats());
+        drop(tasks);
+        drop(controls);
         
-        // 更新任务状态
-        self.update_task(task).await?;
+        self.finish_run(&run).await;
+        ctx.progress().flush().await;
+        match &run.error {
+            Some(err) => logger.error(format!("任务执行失败: {}", err)).await,
+            None => logger.info(format!(
+                "任务执行完成，读取 {} 行，写入 {} 行",
+                run.stats.rows_read, run.stats.rows_written
+            )).await,
+        }
+        if !run.stats.masked_columns.is_empty() {
+            let columns: Vec<String> = run
+                .stats
+                .masked_columns
+                .iter()
+                .map(|c| format!("{}.{} ({})", c.table, c.column, c.policy))
+                .collect();
+            logger.info(format!("本次执行已脱敏的列: {}", columns.join(", "))).await;
+        }
+        self.persist_task(id).await?;
+        if completed {
+            if let Some(store) = &checkpoints {
+                store.clear_table_checkpoints(id).await?;
+            }
+            if task.verify_on_complete {
+                if task.strategy.verification_tables().is_some() {
+                    self.run_verification(id, &logger).await?;
+                } else {
+                    logger.warning(format!("{} 策略不支持数据校验，跳过", task.strategy.get_name())).await;
+                }
+            }
+        }
+        if let Some(store) = &self.log_store {
+            if let Err(err) = store.prune(Some(id)).await {
+                logger.warning(format!("清理任务日志失败: {}", err)).await;
+            }
+        }
         
         Ok(())
     }
+    
+    // 演练任务：只读取源库和目标库，返回将要执行的计划，不写入任何数据
+    pub async fn plan_task(&self, id: &str) -> Result<MigrationPlan, String> {
+        let task = self.get_task(id).await
+            .ok_or_else(|| format!("Task not found: {}", id))?;
+        
+        // 读取检查点以扣除继续执行时跳过的行，演练过程不会写入检查点
+        let (_, signal) = watch::channel(TaskSignal::Run);
+        let mut ctx = TaskContext::new(id.to_string(), self.tasks.clone(), signal);
+        if let Some(store) = self.checkpoint_store().await? {
+            ctx = ctx.with_checkpoints(store);
+        }
+        task.strategy.plan(&task.source_db_id, &task.target_db_id, self.conn_manager.clone(), &ctx).await
+    }
+    
+    // 校验任务的源库和目标库数据，结果保存在任务上；执行中的任务不能校验
+    pub async fn verify_task(&self, id: &str) -> Result<VerificationReport, String> {
+        let task = self.get_task(id).await
+            .ok_or_else(|| format!("Task not found: {}", id))?;
+        if task.status == TaskStatus::Running || task.status == TaskStatus::Paused {
+            return Err(format!("Cannot verify task in {} status", task.status_to_string()));
+        }
+        if task.strategy.verification_tables().is_none() {
+            return Err(format!("Verification is not supported for {} strategy", task.strategy.get_name()));
+        }
+        
+        let logger = self.task_logger(id, None);
+        self.run_verification(id, &logger).await
+    }
+    
+    // 按主键范围比较任务源库和目标库的差异，需要时按源表修复目标表；执行中的任务不能比较
+    pub async fn diff_task(&self, id: &str, options: &DiffOptions) -> Result<Vec<TableDiff>, String> {
+        let task = self.get_task(id).await
+            .ok_or_else(|| format!("Task not found: {}", id))?;
+        if task.status == TaskStatus::Running || task.status == TaskStatus::Paused {
+            return Err(format!("Cannot diff task in {} status", task.status_to_string()));
+        }
+        // 未指定表时比较校验范围内的表
+        let tables = if options.tables.is_empty() {
+            task.strategy.verification_tables()
+                .ok_or_else(|| format!("Diff is not supported for {} strategy", task.strategy.get_name()))?
+        } else {
+            options.tables.clone()
+        };
+        
+        let (source_conn, target_conn) = strategy::get_sqlx_pair(&task.source_db_id, &task.target_db_id, &self.conn_manager).await?;
+        let logger = self.task_logger(id, None);
+        table_diff::diff_tables(&source_conn, &target_conn, &tables, RowRules::new(task.strategy.mappings(), task.strategy.transformers(), task.strategy.filters()), options, &logger).await
+    }
+    
+    // 执行校验并保存报告，校验过程出错时记录为未通过
+    async fn run_verification(&self, id: &str, logger: &TaskLogger) -> Result<VerificationReport, String> {
+        let task = self.get_task(id).await
+            .ok_or_else(|| format!("Task not found: {}", id))?;
+        logger.info("开始校验迁移数据").await;
+        let report = verify::verify_migration(
+            &task.strategy,
+            &task.source_db_id,
+            &task.target_db_id,
+            self.conn_manager.clone(),
+            logger,
+        ).await.unwrap_or_else(VerificationReport::failed);
+        match &report.error {
+            Some(err) => logger.error(format!("数据校验出错: {}", err)).await,
+            None if report.passed => logger.info(format!("数据校验通过，共 {} 张表", report.tables.len())).await,
+            None => logger.error(format!("数据校验未通过，{} 张表不一致", report.mismatched())).await,
+        }
+        
+        if let Some(task) = self.tasks.write().await.get_mut(id) {
+            task.set_verification(report.clone());
+        }
+        self.persist_task(id).await?;
+        Ok(report)
+    }
+    
+    // 获取任务的执行记录，最近的在前，未关联配置库时没有记录
+    pub async fn get_task_runs(&self, task_id: &str) -> Result<Vec<TaskRun>, String> {
+        if !self.tasks.read().await.contains_key(task_id) {
+            return Err(format!("Task not found: {}", task_id));
+        }
+        match &self.task_store {
+            Some(store) => store.list_runs(task_id).await,
+            None => Ok(Vec::new()),
+        }
+    }
+    
+    // 获取一条执行记录
+    pub async fn get_task_run(&self, run_id: &str) -> Result<Option<TaskRun>, String> {
+        match &self.task_store {
+            Some(store) => store.get_run(run_id).await,
+            None => Ok(None),
+        }
+    }
+    
+    // 分页查询任务日志，最新的在前，未关联配置库时没有日志
+    pub async fn get_task_logs(&self, task_id: &str, query: &TaskLogQuery) -> Result<TaskLogPage, String> {
+        if !self.tasks.read().await.contains_key(task_id) {
+            return Err(format!("Task not found: {}", task_id));
+        }
+        match &self.log_store {
+            Some(store) => store.query(task_id, query).await,
+            None => Ok(TaskLogPage { entries: Vec::new(), total: 0 }),
+        }
+    }
+    
+    // 任务的日志句柄，写入配置库并推送给界面
+    fn task_logger(&self, id: &str, run_id: Option<&str>) -> TaskLogger {
+        let mut logger = TaskLogger::new(id.to_string());
+        if let Some(run_id) = run_id {
+            logger = logger.with_run(run_id.to_string());
+        }
+        if let Some(store) = &self.log_store {
+            logger = logger.with_store(store.clone());
+        }
+        if let Some(sink) = &self.log_sink {
+            logger = logger.with_sink(sink.clone());
+        }
+        logger
+    }
+    
+    // 开始记录一次执行，记录写入失败不影响任务执行
+    async fn start_run(&self, id: &str) -> TaskRun {
+        let store = match &self.task_store {
+            Some(store) => store,
+            None => return TaskRun::new(id, 1),
+        };
+        let run_number = match store.next_run_number(id).await {
+            Ok(run_number) => run_number,
+            Err(err) => {
+                self.task_logger(id, None).warning(format!("无法读取执行记录: {}", err)).await;
+                1
+            }
+        };
+        let run = TaskRun::new(id, run_number);
+        if let Err(err) = store.save_run(&run).await {
+            self.task_logger(id, Some(&run.id)).warning(format!("无法保存执行记录: {}", err)).await;
+        }
+        run
+    }
+    
+    // 保存执行结束时的状态和统计
+    async fn finish_run(&self, run: &TaskRun) {
+        if let Some(store) = &self.task_store {
+            if let Err(err) = store.save_run(run).await {
+                self.task_logger(&run.task_id, Some(&run.id)).warning(format!("无法保存执行记录: {}", err)).await;
+            }
+        }
+    }
+    
+    // 打开检查点存储，未设置配置库时返回None
+    async fn checkpoint_store(&self) -> Result<Option<CheckpointStore>, String> {
+        match &self.checkpoint_db_id {
+            Some(id) => Ok(Some(CheckpointStore::open(&self.conn_manager, id).await?)),
+            None => Ok(None),
+        }
+    }
+    
+    // 把任务的最新状态写入配置库，未关联配置库时只保存在内存中
+    async fn persist_task(&self, id: &str) -> Result<(), String> {
+        match &self.task_store {
+            Some(store) => store.sync_task(&self.tasks, id).await,
+            None => Ok(()),
+        }
+    }
 }
 
 // 实现克隆特性
@@ -156,7 +516,13 @@ impl Clone for MigrationPipeline {
     fn clone(&self) -> Self {
         Self {
             tasks: self.tasks.clone(),
+            controls: self.controls.clone(),
             conn_manager: self.conn_manager.clone(),
+            checkpoint_db_id: self.checkpoint_db_id.clone(),
+            task_store: self.task_store.clone(),
+            progress_sink: self.progress_sink.clone(),
+            log_store: self.log_store.clone(),
+            log_sink: self.log_sink.clone(),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,255 @@
+// 迁移演练模块 - 策略只读取源库和目标库，生成将要执行的语句和预计迁移的行数，不写入任何数据
+
+use serde::{Deserialize, Serialize};
+use crate::database::{ColumnSchema, DatabaseType, DdlGenerator, SqlxDatabaseConnection, TableDdl, TableSchema};
+use crate::migration::mapping::ColumnRules;
+use crate::migration::table_copy::{self, RowMapper};
+use crate::migration::transform::Transformer;
+
+// 目标表的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableAction {
+    // 目标表不存在，按映射后的源表结构创建
+    Create,
+    // 写入已存在的目标表，只写入映射后两端共有的列
+    Existing,
+}
+
+// 计划中语句的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementKind {
+    // 在源库执行的准备语句，如安装触发器、创建发布和复制槽
+    SourceSetup,
+    CreateTable,
+    // 数据导入后执行的序列调整和索引创建
+    PostLoad,
+    AddForeignKey,
+    // 按批次执行的写入，SQL以单行的形式展示
+    Write,
+}
+
+// 计划执行的一条语句
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedStatement {
+    pub kind: StatementKind,
+    // 语句所属的表，与表无关的语句为空
+    pub table: Option<String>,
+    // SQL语句，写入Redis时为命令
+    pub sql: String,
+    // 预计执行的次数，写入语句按批次计算，无法预估时为空
+    pub executions: Option<u64>,
+}
+
+// 单张表的计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TablePlan {
+    // 源表，Redis源为键模式
+    pub source: String,
+    pub target: String,
+    pub action: TableAction,
+    // 将要写入的目标列
+    pub columns: Vec<String>,
+    // 源表中有但目标表中没有的列，不会写入
+    pub skipped_columns: Vec<String>,
+    // 映射规则中删除的源列
+    pub dropped_columns: Vec<String>,
+    // 由转换器改写或新增的目标列
+    pub transformed_columns: Vec<String>,
+    // 过滤条件，只迁移满足条件的行
+    pub filter: Option<String>,
+    // 预计迁移的行数，无法预估时为空
+    pub estimated_rows: Option<u64>,
+    // 检查点中已复制的行数，继续执行时跳过
+    pub resumed_rows: u64,
+}
+
+impl TablePlan {
+    // 按源表、目标表结构、列映射规则和转换器确定写入的列，常量列排在最后
+    pub fn new(
+        source: &TableSchema,
+        target: &TableSchema,
+        rules: &ColumnRules,
+        transformers: &[Transformer],
+        action: TableAction,
+    ) -> Result<Self, String> {
+        let mapper = RowMapper::new(source, target, rules, transformers)?;
+        let columns = mapper.target_columns().iter().map(|c| c.name.clone()).collect();
+        let (dropped_columns, skipped_columns) = source
+            .columns
+            .iter()
+            .filter(|c| rules.target_column(&c.name).and_then(|name| target.column(name)).is_none())
+            .map(|c| c.name.clone())
+            .partition(|name| rules.target_column(name).is_none());
+        Ok(Self {
+            source: source.name.clone(),
+            target: target.name.clone(),
+            action,
+            columns,
+            skipped_columns,
+            dropped_columns,
+            transformed_columns: mapper.transformed_columns(),
+            filter: None,
+            estimated_rows: None,
+            resumed_rows: 0,
+        })
+    }
+}
+
+// 迁移任务的执行计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    pub task_id: String,
+    pub strategy: String,
+    pub source_db_id: String,
+    pub target_db_id: String,
+    pub tables: Vec<TablePlan>,
+    // 按执行顺序排列的语句
+    pub statements: Vec<PlannedStatement>,
+    // 预计迁移的总行数（Redis源为键数），任一部分无法预估时为空
+    pub estimated_rows: Option<u64>,
+    pub warnings: Vec<String>,
+}
+
+impl MigrationPlan {
+    pub fn new(task_id: &str, strategy: &str, source_db_id: &str, target_db_id: &str) -> Self {
+        Self {
+            task_id: task_id.to_string(),
+            strategy: strategy.to_string(),
+            source_db_id: source_db_id.to_string(),
+            target_db_id: target_db_id.to_string(),
+            tables: Vec::new(),
+            statements: Vec::new(),
+            estimated_rows: Some(0),
+            warnings: Vec::new(),
+        }
+    }
+
+    // 加入一张表，并计入其预计行数
+    pub fn add_table(&mut self, table: TablePlan) {
+        self.add_rows(table.estimated_rows);
+        self.tables.push(table);
+    }
+
+    // 计入预计迁移的行数
+    pub fn add_rows(&mut self, rows: Option<u64>) {
+        self.estimated_rows = match (self.estimated_rows, rows) {
+            (Some(total), Some(rows)) => Some(total + rows),
+            _ => None,
+        };
+    }
+
+    pub fn add_statement(&mut self, kind: StatementKind, table: Option<&str>, sql: impl Into<String>, executions: Option<u64>) {
+        self.statements.push(PlannedStatement {
+            kind,
+            table: table.map(str::to_string),
+            sql: sql.into(),
+            executions,
+        });
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    // 记入建表DDL的类型映射提示
+    pub fn add_ddl_warnings(&mut self, ddl: &TableDdl) {
+        for warning in &ddl.warnings {
+            self.warn(format!(
+                "类型映射提示 {}.{}: {}",
+                warning.table,
+                warning.column.as_deref().unwrap_or("-"),
+                warning.message
+            ));
+        }
+    }
+
+    // 记入一张表的复制：新建表时先建表，再按批次写入，之后执行导入后的语句；外键由调用方在最后加入
+    pub fn add_table_copy(
+        &mut self,
+        table: TablePlan,
+        target: &TableSchema,
+        db_type: &DatabaseType,
+        ddl: Option<&TableDdl>,
+        batch_size: usize,
+    ) {
+        if let Some(ddl) = ddl {
+            self.add_statement(StatementKind::CreateTable, Some(&target.name), ddl.create_table.clone(), Some(1));
+        }
+        if table.columns.is_empty() {
+            self.warn(format!("表 {} 与目标表 {} 没有共同的列，执行时会失败", table.source, table.target));
+        } else {
+            let columns: Vec<_> = table.columns.iter().filter_map(|c| target.column(c)).collect();
+            let keys: Vec<String> = target
+                .primary_key
+                .iter()
+                .filter(|k| table.columns.iter().any(|c| c.eq_ignore_ascii_case(k)))
+                .cloned()
+                .collect();
+            self.add_statement(
+                StatementKind::Write,
+                Some(&target.name),
+                table_copy::write_template(db_type, &target.name, &columns, &keys),
+                batches(table.estimated_rows, batch_size),
+            );
+        }
+        if let Some(ddl) = ddl {
+            for sql in ddl.post_load.iter().chain(ddl.indexes.iter()) {
+                self.add_statement(StatementKind::PostLoad, Some(&target.name), sql.clone(), Some(1));
+            }
+        }
+        if !table.skipped_columns.is_empty() {
+            self.warn(format!(
+                "目标表 {} 没有以下列，不会写入: {}",
+                target.name,
+                table.skipped_columns.join(", ")
+            ));
+        }
+        self.add_table(table);
+    }
+
+    // 记入新建表的外键，在所有表导入完成后执行
+    pub fn add_foreign_keys(&mut self, ddl: &TableDdl) {
+        for sql in &ddl.foreign_keys {
+            self.add_statement(StatementKind::AddForeignKey, Some(&ddl.table), sql.clone(), Some(1));
+        }
+    }
+}
+
+// 演练时的目标表：已存在时返回其结构，否则按DDL推断将要创建的结构，并返回是否需要新建
+pub async fn plan_target_table(
+    generator: &DdlGenerator,
+    source: &TableSchema,
+    ddl: &TableDdl,
+    target_conn: &SqlxDatabaseConnection,
+) -> Result<(TableSchema, bool), String> {
+    match target_conn.get_table_schema(&ddl.table).await? {
+        Some(existing) => Ok((existing, false)),
+        None => Ok((projected_table(generator, &target_conn.db_type(), source, ddl), true)),
+    }
+}
+
+// 按生成的DDL推断将要创建的目标表结构，只包含列和主键
+fn projected_table(generator: &DdlGenerator, target_type: &DatabaseType, source: &TableSchema, ddl: &TableDdl) -> TableSchema {
+    let columns = source
+        .columns
+        .iter()
+        .map(|column| {
+            let (data_type, _) = generator.map_type(column, source.is_primary_key(&column.name));
+            ColumnSchema::new(target_type, column.name.clone(), data_type, column.nullable)
+        })
+        .collect();
+    TableSchema {
+        name: ddl.table.clone(),
+        columns,
+        primary_key: source.primary_key.clone(),
+        indexes: Vec::new(),
+        foreign_keys: Vec::new(),
+    }
+}
+
+// 按批次大小计算写入的次数
+pub fn batches(rows: Option<u64>, batch_size: usize) -> Option<u64> {
+    rows.map(|rows| rows.div_ceil(batch_size.max(1) as u64))
+}
@@ -0,0 +1,187 @@
+// 迁移进度上报模块 - 策略通过上报句柄更新任务进度，并按节流间隔把进度推送给界面
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use crate::migration::MigrationTask;
+use crate::migration::task::{RunStats, TableProgress, TaskProgress, TaskStatus};
+
+// 推送给界面的进度事件名
+pub const PROGRESS_EVENT: &str = "migration://progress";
+
+// 两次推送之间的最短间隔
+const EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+// 一次进度推送
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEvent {
+    pub task_id: String,
+    pub status: TaskStatus,
+    pub completed_items: u64,
+    pub total_items: Option<u64>,
+    pub percentage: Option<f64>,
+    pub current_table: Option<String>,
+    pub current_operation: String,
+    pub bytes_transferred: u64,
+    pub rows_per_second: f64,
+    pub average_rows_per_second: f64,
+    pub elapsed_ms: u64,
+    pub eta_seconds: Option<u64>,
+    pub tables: Vec<TableProgress>,
+}
+
+// 进度事件的接收方，应用中转发为Tauri事件
+pub type ProgressSink = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+// 进度上报句柄，克隆后共享同一个节流状态
+#[derive(Clone)]
+pub struct ProgressReporter {
+    task_id: String,
+    tasks: Arc<RwLock<HashMap<String, MigrationTask>>>,
+    sink: Option<ProgressSink>,
+    // 本次执行的读写统计，写入的数据量从中同步到进度
+    stats: Arc<Mutex<RunStats>>,
+    state: Arc<Mutex<ReporterState>>,
+}
+
+// 上报句柄的共享状态
+#[derive(Default)]
+struct ReporterState {
+    // 上次推送的时间，为空表示还未推送
+    last_emit: Option<Instant>,
+    // 已同步到进度的数据量
+    synced_bytes: u64,
+}
+
+impl ProgressReporter {
+    pub fn new(
+        task_id: String,
+        tasks: Arc<RwLock<HashMap<String, MigrationTask>>>,
+        stats: Arc<Mutex<RunStats>>,
+    ) -> Self {
+        Self {
+            task_id,
+            tasks,
+            sink: None,
+            stats,
+            state: Arc::new(Mutex::new(ReporterState::default())),
+        }
+    }
+
+    // 设置进度事件的接收方
+    pub fn with_sink(mut self, sink: ProgressSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    // 设置总项目数，无法预估时为None
+    pub async fn set_total(&self, total_items: Option<u64>) {
+        self.modify(false, |progress| progress.total_items = total_items).await;
+    }
+
+    // 登记待处理的表及其预估行数，界面据此显示各表进度
+    pub async fn add_table(&self, table: &str, total_items: Option<u64>) {
+        self.modify(false, |progress| progress.add_table(table, total_items)).await;
+    }
+
+    // 切换到正在处理的表，切换时立即推送
+    pub async fn set_table(&self, table: Option<&str>) {
+        let table = table.map(str::to_string);
+        self.modify(true, |progress| progress.set_current_table(table)).await;
+    }
+
+    // 增加已完成的项目数
+    pub async fn advance(&self, items: u64, current_operation: String) {
+        self.modify(false, |progress| progress.advance(items, current_operation)).await;
+    }
+
+    // 计入从检查点跳过的项目数，不影响速度
+    pub async fn skip(&self, items: u64, current_operation: String) {
+        self.modify(false, |progress| progress.skip(items, current_operation)).await;
+    }
+
+    // 开始新的阶段，完成数和速度重新计算
+    pub async fn begin_stage(&self, current_operation: String) {
+        self.modify(true, |progress| progress.begin_stage(current_operation)).await;
+    }
+
+    // 设置已完成的项目数
+    pub async fn update(&self, completed_items: u64, current_operation: String) {
+        self.modify(false, |progress| progress.update(completed_items, current_operation)).await;
+    }
+
+    // 立即推送当前进度，用于任务结束等状态变化
+    pub async fn flush(&self) {
+        self.modify(true, |_| {}).await;
+    }
+
+    // 修改任务进度，到达推送间隔或强制推送时发出事件
+    async fn modify<F>(&self, force: bool, apply: F)
+    where
+        F: FnOnce(&mut TaskProgress),
+    {
+        let event = {
+            let mut tasks = self.tasks.write().await;
+            let task = match tasks.get_mut(&self.task_id) {
+                Some(task) => task,
+                None => return,
+            };
+            // 先把之前写入的数据量计入当前表，再切换表或更新进度
+            task.progress.add_bytes(self.unsynced_bytes());
+            apply(&mut task.progress);
+            task.updated_at = SystemTime::now();
+            if self.sink.is_none() || !self.due(force) {
+                return;
+            }
+            let progress = &task.progress;
+            ProgressEvent {
+                task_id: task.id.clone(),
+                status: task.status.clone(),
+                completed_items: progress.completed_items,
+                total_items: progress.total_items,
+                percentage: progress.get_percentage(),
+                current_table: progress.current_table.clone(),
+                current_operation: progress.current_operation.clone(),
+                bytes_transferred: progress.bytes_transferred,
+                rows_per_second: progress.rows_per_second,
+                average_rows_per_second: progress.average_rows_per_second,
+                elapsed_ms: progress.elapsed_ms,
+                eta_seconds: progress.eta_seconds,
+                tables: progress.tables.clone(),
+            }
+        };
+        if let Some(sink) = &self.sink {
+            sink(&event);
+        }
+    }
+
+    // 上次同步之后写入的数据量
+    fn unsynced_bytes(&self) -> u64 {
+        let bytes = self.stats.lock().map(|stats| stats.bytes).unwrap_or(0);
+        match self.state.lock() {
+            Ok(mut state) => {
+                let unsynced = bytes.saturating_sub(state.synced_bytes);
+                state.synced_bytes = bytes;
+                unsynced
+            }
+            Err(_) => 0,
+        }
+    }
+
+    // 判断是否应推送，应推送时记录推送时间
+    fn due(&self, force: bool) -> bool {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return false,
+        };
+        let now = Instant::now();
+        let due = force || state.last_emit.is_none_or(|last| now.duration_since(last) >= EMIT_INTERVAL);
+        if due {
+            state.last_emit = Some(now);
+        }
+        due
+    }
+}
@@ -0,0 +1,299 @@
+// Redis键复制工具 - 通过SCAN遍历键，用DUMP/RESTORE或按类型读写复制值
+
+use redis::aio::MultiplexedConnection;
+use redis::{from_redis_value, RedisError, Value};
+use serde::{Serialize, Deserialize};
+use crate::migration::task_log::TaskLogger;
+
+// 按类型复制集合时每条写命令携带的元素数
+const ELEMENTS_PER_COMMAND: usize = 1000;
+
+// Redis复制统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedisCopyStats {
+    // 已复制的键
+    pub copied: u64,
+    // 目标库已存在而跳过的键
+    pub skipped: u64,
+    // 扫描后已过期或被删除的键
+    pub missing: u64,
+}
+
+impl RedisCopyStats {
+    pub fn merge(&mut self, other: &RedisCopyStats) {
+        self.copied += other.copied;
+        self.skipped += other.skipped;
+        self.missing += other.missing;
+    }
+}
+
+// 用SCAN读取一批键，返回下一个游标
+pub async fn scan_keys(
+    conn: &mut MultiplexedConnection,
+    cursor: u64,
+    pattern: &str,
+    count: usize,
+) -> Result<(u64, Vec<Vec<u8>>), String> {
+    redis::cmd("SCAN")
+        .arg(cursor)
+        .arg("MATCH")
+        .arg(pattern)
+        .arg("COUNT")
+        .arg(count)
+        .query_async(conn)
+        .await
+        .map_err(|e| format!("Failed to scan keys: {}", e))
+}
+
+// 统计匹配模式的键数，SCAN期间键有变化时结果为近似值
+pub async fn count_keys(conn: &mut MultiplexedConnection, pattern: &str, count: usize) -> Result<u64, String> {
+    let mut total = 0u64;
+    let mut cursor = 0u64;
+    loop {
+        let (next_cursor, keys) = scan_keys(conn, cursor, pattern, count).await?;
+        total += keys.len() as u64;
+        if next_cursor == 0 {
+            return Ok(total);
+        }
+        cursor = next_cursor;
+    }
+}
+
+// 键复制器，DUMP格式不兼容时自动切换为按类型复制
+pub struct RedisCopier {
+    replace_existing: bool,
+    use_dump: bool,
+}
+
+impl RedisCopier {
+    pub fn new(replace_existing: bool) -> Self {
+        Self {
+            replace_existing,
+            use_dump: true,
+        }
+    }
+
+    // 复制一批键并保留剩余过期时间
+    pub async fn copy_batch(
+        &mut self,
+        source: &mut MultiplexedConnection,
+        target: &mut MultiplexedConnection,
+        keys: Vec<Vec<u8>>,
+        logger: &TaskLogger,
+    ) -> Result<RedisCopyStats, String> {
+        let mut stats = RedisCopyStats::default();
+        if keys.is_empty() {
+            return Ok(stats);
+        }
+
+        // PTTL: -2 键不存在，-1 永不过期
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.cmd("PTTL").arg(key);
+        }
+        let ttls: Vec<i64> = pipe
+            .query_async(source)
+            .await
+            .map_err(|e| format!("Failed to read key TTLs: {}", e))?;
+        let mut entries: Vec<(Vec<u8>, u64)> = Vec::with_capacity(keys.len());
+        for (key, ttl) in keys.into_iter().zip(ttls) {
+            match ttl {
+                -2 => stats.missing += 1,
+                ttl => entries.push((key, ttl.max(0) as u64)),
+            }
+        }
+
+        if !self.replace_existing && !entries.is_empty() {
+            let mut pipe = redis::pipe();
+            for (key, _) in &entries {
+                pipe.cmd("EXISTS").arg(key);
+            }
+            let exists: Vec<bool> = pipe
+                .query_async(target)
+                .await
+                .map_err(|e| format!("Failed to check target keys: {}", e))?;
+            let before = entries.len();
+            entries = entries
+                .into_iter()
+                .zip(exists)
+                .filter_map(|(entry, exists)| if exists { None } else { Some(entry) })
+                .collect();
+            stats.skipped += (before - entries.len()) as u64;
+        }
+        if entries.is_empty() {
+            return Ok(stats);
+        }
+
+        if self.use_dump {
+            match restore_batch(source, target, &entries).await {
+                Ok((copied, missing)) => {
+                    stats.copied += copied;
+                    stats.missing += missing;
+                    return Ok(stats);
+                }
+                Err(e) if is_dump_incompatible(&e) => {
+                    logger.warning(format!("DUMP/RESTORE不可用，改为按类型复制: {}", e)).await;
+                    self.use_dump = false;
+                }
+                Err(e) => return Err(format!("Failed to restore keys: {}", e)),
+            }
+        }
+
+        let (copied, missing) = copy_by_type(source, target, &entries).await?;
+        stats.copied += copied;
+        stats.missing += missing;
+        Ok(stats)
+    }
+}
+
+// 源库和目标库版本不兼容或命令被禁用时切换为按类型复制
+fn is_dump_incompatible(error: &RedisError) -> bool {
+    let message = error.to_string().to_lowercase();
+    message.contains("payload version")
+        || message.contains("bad data format")
+        || message.contains("unknown command")
+        || message.contains("noperm")
+}
+
+// 用DUMP/RESTORE复制，返回复制和缺失的键数
+async fn restore_batch(
+    source: &mut MultiplexedConnection,
+    target: &mut MultiplexedConnection,
+    entries: &[(Vec<u8>, u64)],
+) -> Result<(u64, u64), RedisError> {
+    let mut pipe = redis::pipe();
+    for (key, _) in entries {
+        pipe.cmd("DUMP").arg(key);
+    }
+    let payloads: Vec<Option<Vec<u8>>> = pipe.query_async(source).await?;
+
+    let mut pipe = redis::pipe();
+    let mut copied = 0u64;
+    for ((key, ttl), payload) in entries.iter().zip(payloads) {
+        if let Some(payload) = payload {
+            // 不覆盖已有键时它们在调用前已被过滤
+            pipe.cmd("RESTORE").arg(key).arg(*ttl).arg(payload).arg("REPLACE").ignore();
+            copied += 1;
+        }
+    }
+    if copied > 0 {
+        pipe.query_async::<_, ()>(target).await?;
+    }
+    Ok((copied, entries.len() as u64 - copied))
+}
+
+// 按数据类型读取并重建键，返回复制和缺失的键数
+async fn copy_by_type(
+    source: &mut MultiplexedConnection,
+    target: &mut MultiplexedConnection,
+    entries: &[(Vec<u8>, u64)],
+) -> Result<(u64, u64), String> {
+    let mut pipe = redis::pipe();
+    for (key, _) in entries {
+        pipe.cmd("TYPE").arg(key);
+    }
+    let types: Vec<String> = pipe
+        .query_async(source)
+        .await
+        .map_err(|e| format!("Failed to read key types: {}", e))?;
+
+    let mut pipe = redis::pipe();
+    for ((key, _), key_type) in entries.iter().zip(&types) {
+        match key_type.as_str() {
+            "string" => pipe.cmd("GET").arg(key),
+            "list" => pipe.cmd("LRANGE").arg(key).arg(0).arg(-1),
+            "set" => pipe.cmd("SMEMBERS").arg(key),
+            "zset" => pipe.cmd("ZRANGE").arg(key).arg(0).arg(-1).arg("WITHSCORES"),
+            "hash" => pipe.cmd("HGETALL").arg(key),
+            "stream" => pipe.cmd("XRANGE").arg(key).arg("-").arg("+"),
+            "none" => pipe.cmd("EXISTS").arg(key),
+            other => {
+                return Err(format!(
+                    "Key {} has type {} which can only be copied with DUMP/RESTORE",
+                    String::from_utf8_lossy(key),
+                    other
+                ));
+            }
+        };
+    }
+    let values: Vec<Value> = pipe
+        .query_async(source)
+        .await
+        .map_err(|e| format!("Failed to read key values: {}", e))?;
+
+    let mut pipe = redis::pipe();
+    let mut copied = 0u64;
+    let mut missing = 0u64;
+    for (((key, ttl), key_type), value) in entries.iter().zip(&types).zip(values) {
+        if key_type == "none" || value == Value::Nil {
+            missing += 1;
+            continue;
+        }
+        pipe.cmd("DEL").arg(key).ignore();
+        write_value(&mut pipe, key, key_type, value)
+            .map_err(|e| format!("Failed to decode key {}: {}", String::from_utf8_lossy(key), e))?;
+        if *ttl > 0 {
+            pipe.cmd("PEXPIRE").arg(key).arg(*ttl).ignore();
+        }
+        copied += 1;
+    }
+    if copied > 0 {
+        pipe.query_async::<_, ()>(target)
+            .await
+            .map_err(|e| format!("Failed to write keys: {}", e))?;
+    }
+    Ok((copied, missing))
+}
+
+// 把读取的值追加为目标库的写命令
+fn write_value(pipe: &mut redis::Pipeline, key: &[u8], key_type: &str, value: Value) -> Result<(), RedisError> {
+    match key_type {
+        "string" => {
+            let value: Vec<u8> = from_redis_value(&value)?;
+            pipe.cmd("SET").arg(key).arg(value).ignore();
+        }
+        "list" | "set" => {
+            let items: Vec<Vec<u8>> = from_redis_value(&value)?;
+            let command = if key_type == "list" { "RPUSH" } else { "SADD" };
+            for chunk in items.chunks(ELEMENTS_PER_COMMAND) {
+                pipe.cmd(command).arg(key).arg(chunk).ignore();
+            }
+        }
+        "zset" => {
+            // 分数保留原始文本，避免浮点精度损失
+            let members: Vec<(Vec<u8>, String)> = from_redis_value(&value)?;
+            for chunk in members.chunks(ELEMENTS_PER_COMMAND) {
+                let cmd = pipe.cmd("ZADD").arg(key);
+                for (member, score) in chunk {
+                    cmd.arg(score).arg(member);
+                }
+                cmd.ignore();
+            }
+        }
+        "hash" => {
+            let fields: Vec<(Vec<u8>, Vec<u8>)> = from_redis_value(&value)?;
+            for chunk in fields.chunks(ELEMENTS_PER_COMMAND) {
+                let cmd = pipe.cmd("HSET").arg(key);
+                for (field, value) in chunk {
+                    cmd.arg(field).arg(value);
+                }
+                cmd.ignore();
+            }
+        }
+        "stream" => {
+            // 只复制消息，消费者组不会复制
+            let entries: Vec<Value> = from_redis_value(&value)?;
+            for entry in entries {
+                let (id, fields): (String, Value) = from_redis_value(&entry)?;
+                let fields: Vec<(Vec<u8>, Vec<u8>)> = from_redis_value(&fields)?;
+                let cmd = pipe.cmd("XADD").arg(key).arg(id);
+                for (field, value) in fields {
+                    cmd.arg(field).arg(value);
+                }
+                cmd.ignore();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
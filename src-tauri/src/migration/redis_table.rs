@@ -0,0 +1,756 @@
+// Redis键与SQL表之间的转换 - 把哈希或JSON对象展平为表中的行，或把查询结果的行投影为Redis键
+
+use std::collections::{BTreeSet, HashMap};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use redis::aio::MultiplexedConnection;
+use redis::{from_redis_value, Value};
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+use crate::database::{
+    ColumnKind, ColumnSchema, DatabaseType, RedisExpiry, RedisWrite, RedisWriteValue, SqlRowSet, SqlValue, TableSchema,
+};
+
+// 字段到目标列的显式映射
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisFieldMapping {
+    // 哈希字段或JSON对象的顶层键
+    pub field: String,
+    // 目标列名，为空时与字段同名
+    #[serde(default)]
+    pub column: Option<String>,
+    // 目标库方言的声明类型，为空时根据样本推断
+    #[serde(default)]
+    pub data_type: Option<String>,
+}
+
+impl RedisFieldMapping {
+    pub fn column_name(&self) -> &str {
+        self.column.as_deref().unwrap_or(&self.field)
+    }
+}
+
+// 从Redis读出的一个键，字段值统一用JSON表示
+#[derive(Debug, Clone)]
+pub struct RedisRecord {
+    pub key: String,
+    pub fields: Vec<(String, JsonValue)>,
+}
+
+impl RedisRecord {
+    fn field(&self, name: &str) -> Option<&JsonValue> {
+        self.fields
+            .iter()
+            .find(|(f, _)| f == name)
+            .or_else(|| self.fields.iter().find(|(f, _)| f.eq_ignore_ascii_case(name)))
+            .map(|(_, v)| v)
+    }
+}
+
+// 一批键的读取结果
+#[derive(Debug, Default)]
+pub struct RecordBatch {
+    pub records: Vec<RedisRecord>,
+    // 类型无法展平的键（列表、集合、非JSON对象的字符串等）
+    pub unsupported: u64,
+    // 扫描后已过期或被删除的键
+    pub missing: u64,
+}
+
+// 读取一批键：哈希用HGETALL，字符串按JSON对象解析，RedisJSON文档用JSON.GET
+pub async fn read_records(conn: &mut MultiplexedConnection, keys: &[Vec<u8>]) -> Result<RecordBatch, String> {
+    let mut batch = RecordBatch::default();
+    if keys.is_empty() {
+        return Ok(batch);
+    }
+
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("TYPE").arg(key);
+    }
+    let types: Vec<String> = pipe
+        .query_async(conn)
+        .await
+        .map_err(|e| format!("Failed to read key types: {}", e))?;
+
+    let mut pipe = redis::pipe();
+    let mut readable = Vec::new();
+    for (key, key_type) in keys.iter().zip(&types) {
+        match key_type.as_str() {
+            "hash" => pipe.cmd("HGETALL").arg(key),
+            "string" => pipe.cmd("GET").arg(key),
+            "ReJSON-RL" => pipe.cmd("JSON.GET").arg(key),
+            "none" => {
+                batch.missing += 1;
+                continue;
+            }
+            _ => {
+                batch.unsupported += 1;
+                continue;
+            }
+        };
+        readable.push((key, key_type.as_str()));
+    }
+    if readable.is_empty() {
+        return Ok(batch);
+    }
+    let values: Vec<Value> = pipe
+        .query_async(conn)
+        .await
+        .map_err(|e| format!("Failed to read key values: {}", e))?;
+
+    for ((key, key_type), value) in readable.into_iter().zip(values) {
+        let key = String::from_utf8_lossy(key).into_owned();
+        if value == Value::Nil {
+            batch.missing += 1;
+            continue;
+        }
+        let fields = if key_type == "hash" {
+            let pairs: Vec<(Vec<u8>, Vec<u8>)> = from_redis_value(&value)
+                .map_err(|e| format!("Failed to decode hash {}: {}", key, e))?;
+            if pairs.is_empty() {
+                batch.missing += 1;
+                continue;
+            }
+            pairs
+                .into_iter()
+                .map(|(field, value)| {
+                    (
+                        String::from_utf8_lossy(&field).into_owned(),
+                        JsonValue::String(String::from_utf8_lossy(&value).into_owned()),
+                    )
+                })
+                .collect()
+        } else {
+            let text: Vec<u8> = from_redis_value(&value)
+                .map_err(|e| format!("Failed to decode value of {}: {}", key, e))?;
+            match serde_json::from_slice::<JsonValue>(&text) {
+                Ok(JsonValue::Object(object)) => object.into_iter().collect(),
+                _ => {
+                    batch.unsupported += 1;
+                    continue;
+                }
+            }
+        };
+        batch.records.push(RedisRecord { key, fields });
+    }
+    Ok(batch)
+}
+
+// 去掉键模式中通配符之外的前缀和后缀，如 user:* 匹配的 user:42 得到 42
+pub fn key_suffix(pattern: &str, key: &str) -> String {
+    let is_glob = |c: char| matches!(c, '*' | '?' | '[' | '\\');
+    let Some(first) = pattern.find(is_glob) else {
+        return key.to_string();
+    };
+    let prefix = &pattern[..first];
+    let tail = match pattern.rfind('*') {
+        Some(last) if !pattern[last + 1..].contains(is_glob) => &pattern[last + 1..],
+        _ => "",
+    };
+    if key.len() >= prefix.len() + tail.len() && key.starts_with(prefix) && key.ends_with(tail) {
+        key[prefix.len()..key.len() - tail.len()].to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+// 根据样本推断目标表结构，键后缀列作为主键；同时返回需要提示用户的警告
+pub fn infer_table(
+    db_type: &DatabaseType,
+    table: &str,
+    key_column: &str,
+    key_pattern: &str,
+    mappings: &[RedisFieldMapping],
+    samples: &[RedisRecord],
+) -> Result<(TableSchema, Vec<String>), String> {
+    if samples.is_empty() && (mappings.is_empty() || mappings.iter().any(|m| m.data_type.is_none())) {
+        return Err(format!(
+            "No hash or JSON keys match {} to infer the columns of {}; declare the columns explicitly",
+            key_pattern, table
+        ));
+    }
+
+    // 键后缀全部是整数时使用整数主键
+    let key_kind = if !samples.is_empty()
+        && samples.iter().all(|r| infer_text_kind(&key_suffix(key_pattern, &r.key)) == Some(ColumnKind::Integer))
+    {
+        ColumnKind::Integer
+    } else {
+        ColumnKind::Text
+    };
+    let mut columns = vec![ColumnSchema::new(
+        db_type,
+        key_column.to_string(),
+        declared_type(db_type, key_kind, true).to_string(),
+        false,
+    )];
+
+    let fields: Vec<(String, String, Option<String>)> = if mappings.is_empty() {
+        // 按字段首次出现的顺序生成列
+        let mut seen = BTreeSet::new();
+        samples
+            .iter()
+            .flat_map(|r| r.fields.iter().map(|(f, _)| f))
+            .filter(|f| seen.insert(f.to_lowercase()))
+            .map(|f| (f.clone(), f.clone(), None))
+            .collect()
+    } else {
+        mappings
+            .iter()
+            .map(|m| (m.field.clone(), m.column_name().to_string(), m.data_type.clone()))
+            .collect()
+    };
+
+    let mut warnings = Vec::new();
+    for (field, column, data_type) in fields {
+        if column.eq_ignore_ascii_case(key_column) {
+            warnings.push(format!("字段 {} 与键列 {} 同名，已忽略", field, key_column));
+            continue;
+        }
+        if columns.iter().any(|c| c.name.eq_ignore_ascii_case(&column)) {
+            return Err(format!("Column {} is mapped more than once", column));
+        }
+        let data_type = match data_type {
+            Some(data_type) => data_type,
+            None => {
+                let kind = samples
+                    .iter()
+                    .filter_map(|r| r.field(&field).and_then(infer_kind))
+                    .reduce(merge_kind)
+                    .unwrap_or(ColumnKind::Text);
+                declared_type(db_type, kind, false).to_string()
+            }
+        };
+        columns.push(ColumnSchema::new(db_type, column, data_type, true));
+    }
+
+    let schema = TableSchema {
+        name: table.to_string(),
+        columns,
+        primary_key: vec![key_column.to_string()],
+        indexes: Vec::new(),
+        foreign_keys: Vec::new(),
+    };
+    Ok((schema, warnings))
+}
+
+// 中立类型在各方言中的声明类型
+fn declared_type(db_type: &DatabaseType, kind: ColumnKind, is_key: bool) -> &'static str {
+    match (db_type, kind) {
+        (DatabaseType::MySql, ColumnKind::Text) if is_key => "VARCHAR(255)",
+        (DatabaseType::MySql, ColumnKind::Bool) => "TINYINT(1)",
+        (DatabaseType::MySql, ColumnKind::Integer) => "BIGINT",
+        (DatabaseType::MySql, ColumnKind::Float) => "DOUBLE",
+        (DatabaseType::MySql, ColumnKind::Decimal) => "DECIMAL(65,30)",
+        (DatabaseType::MySql, ColumnKind::Bytes) => "LONGBLOB",
+        (DatabaseType::MySql, ColumnKind::Date) => "DATE",
+        (DatabaseType::MySql, ColumnKind::Time) => "TIME(6)",
+        (DatabaseType::MySql, ColumnKind::DateTime) => "DATETIME(6)",
+        (DatabaseType::MySql, ColumnKind::Json) => "JSON",
+        (DatabaseType::MySql, ColumnKind::Text) => "LONGTEXT",
+        (DatabaseType::PostgreSql, ColumnKind::Bool) => "BOOLEAN",
+        (DatabaseType::PostgreSql, ColumnKind::Integer) => "BIGINT",
+        (DatabaseType::PostgreSql, ColumnKind::Float) => "DOUBLE PRECISION",
+        (DatabaseType::PostgreSql, ColumnKind::Decimal) => "NUMERIC",
+        (DatabaseType::PostgreSql, ColumnKind::Bytes) => "BYTEA",
+        (DatabaseType::PostgreSql, ColumnKind::Date) => "DATE",
+        (DatabaseType::PostgreSql, ColumnKind::Time) => "TIME",
+        (DatabaseType::PostgreSql, ColumnKind::DateTime) => "TIMESTAMP",
+        (DatabaseType::PostgreSql, ColumnKind::Json) => "JSONB",
+        (DatabaseType::PostgreSql, ColumnKind::Text) => "TEXT",
+        (_, ColumnKind::Bool) => "BOOLEAN",
+        (_, ColumnKind::Integer) => "INTEGER",
+        (_, ColumnKind::Float) => "REAL",
+        (_, ColumnKind::Decimal) => "NUMERIC",
+        (_, ColumnKind::Bytes) => "BLOB",
+        (_, ColumnKind::Date) => "DATE",
+        (_, ColumnKind::Time) => "TIME",
+        (_, ColumnKind::DateTime) => "DATETIME",
+        (_, ColumnKind::Json) => "JSON",
+        (_, ColumnKind::Text) => "TEXT",
+    }
+}
+
+// 推断单个值的类型，空值不参与推断
+fn infer_kind(value: &JsonValue) -> Option<ColumnKind> {
+    match value {
+        JsonValue::Null => None,
+        JsonValue::Bool(_) => Some(ColumnKind::Bool),
+        JsonValue::Number(n) if n.is_i64() => Some(ColumnKind::Integer),
+        JsonValue::Number(n) if n.is_u64() => Some(ColumnKind::Decimal),
+        JsonValue::Number(_) => Some(ColumnKind::Float),
+        JsonValue::String(text) => infer_text_kind(text),
+        _ => Some(ColumnKind::Json),
+    }
+}
+
+// 哈希字段都是文本，按内容推断；带前导零或正号的数字保留为文本
+fn infer_text_kind(text: &str) -> Option<ColumnKind> {
+    if text.is_empty() {
+        return None;
+    }
+    if text == "true" || text == "false" {
+        return Some(ColumnKind::Bool);
+    }
+    if parse_integer(text).is_some() {
+        return Some(ColumnKind::Integer);
+    }
+    if parse_float(text).is_some() {
+        return Some(ColumnKind::Float);
+    }
+    if parse_date(text) {
+        return Some(ColumnKind::Date);
+    }
+    if parse_time(text) {
+        return Some(ColumnKind::Time);
+    }
+    if parse_datetime(text) {
+        return Some(ColumnKind::DateTime);
+    }
+    if (text.starts_with('{') || text.starts_with('['))
+        && serde_json::from_str::<JsonValue>(text).is_ok_and(|v| v.is_object() || v.is_array())
+    {
+        return Some(ColumnKind::Json);
+    }
+    Some(ColumnKind::Text)
+}
+
+// 合并两个样本的类型，无法兼容时退化为文本
+fn merge_kind(a: ColumnKind, b: ColumnKind) -> ColumnKind {
+    use ColumnKind::*;
+    match (a, b) {
+        (a, b) if a == b => a,
+        (Integer, Float) | (Float, Integer) => Float,
+        (Integer | Float, Decimal) | (Decimal, Integer | Float) => Decimal,
+        (Date, DateTime) | (DateTime, Date) => DateTime,
+        _ => Text,
+    }
+}
+
+fn parse_integer(text: &str) -> Option<i64> {
+    text.parse::<i64>().ok().filter(|i| i.to_string() == text)
+}
+
+fn parse_float(text: &str) -> Option<f64> {
+    let numeric = text.chars().all(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | 'e' | 'E'));
+    let digits = text.trim_start_matches('-');
+    let leading_zero = digits.starts_with('0') && !digits.starts_with("0.");
+    if !numeric || leading_zero {
+        return None;
+    }
+    text.parse::<f64>().ok().filter(|f| f.is_finite())
+}
+
+fn parse_date(text: &str) -> bool {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok()
+}
+
+fn parse_time(text: &str) -> bool {
+    NaiveTime::parse_from_str(text, "%H:%M:%S%.f").is_ok()
+}
+
+// 只接受不带时区的时间，避免写入时丢失偏移量
+fn parse_datetime(text: &str) -> bool {
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").is_ok()
+        || NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+}
+
+// 把字段值转换为目标列的值，类型不兼容时返回 None
+fn column_value(column: &ColumnSchema, value: &JsonValue) -> Option<SqlValue> {
+    let text = match value {
+        JsonValue::Null => return Some(SqlValue::Null),
+        JsonValue::String(text) => Some(text.as_str()),
+        _ => None,
+    };
+    // 非文本列的空字符串视为NULL
+    if text == Some("") && !matches!(column.kind, ColumnKind::Text | ColumnKind::Bytes) {
+        return Some(SqlValue::Null);
+    }
+
+    match column.kind {
+        ColumnKind::Text => Some(SqlValue::Text(text.map(str::to_string).unwrap_or_else(|| value.to_string()))),
+        ColumnKind::Json => Some(SqlValue::Text(match text {
+            // 字段中已是JSON文本时原样写入，否则编码为JSON字符串
+            Some(text) if serde_json::from_str::<JsonValue>(text).is_ok() => text.to_string(),
+            _ => value.to_string(),
+        })),
+        ColumnKind::Bytes => Some(SqlValue::Bytes(text.map(str::to_string).unwrap_or_else(|| value.to_string()).into_bytes())),
+        ColumnKind::Bool => match value {
+            JsonValue::Bool(b) => Some(SqlValue::Bool(*b)),
+            JsonValue::Number(n) => n.as_i64().filter(|i| *i == 0 || *i == 1).map(|i| SqlValue::Bool(i == 1)),
+            _ => SqlValue::Text(text?.to_string()).as_bool().map(SqlValue::Bool),
+        },
+        ColumnKind::Integer => match value {
+            JsonValue::Number(n) => n.as_i64().map(SqlValue::Int),
+            _ => text.and_then(|t| t.trim().parse::<i64>().ok()).map(SqlValue::Int),
+        },
+        ColumnKind::Float => match value {
+            JsonValue::Number(n) => n.as_f64().map(SqlValue::Float),
+            _ => text.and_then(|t| t.trim().parse::<f64>().ok()).map(SqlValue::Float),
+        },
+        // 定点数保留原始文本，避免精度损失
+        ColumnKind::Decimal => match value {
+            JsonValue::Number(n) => Some(SqlValue::Text(n.to_string())),
+            _ => text.filter(|t| t.trim().parse::<f64>().is_ok()).map(|t| SqlValue::Text(t.trim().to_string())),
+        },
+        ColumnKind::Date => text.filter(|t| parse_date(t)).map(|t| SqlValue::Text(t.to_string())),
+        ColumnKind::Time => text.filter(|t| parse_time(t)).map(|t| SqlValue::Text(t.to_string())),
+        ColumnKind::DateTime => text
+            .filter(|t| parse_datetime(t) || parse_date(t))
+            .map(|t| SqlValue::Text(t.replacen('T', " ", 1))),
+    }
+}
+
+// 把键展平为目标表行的方案
+pub struct FlattenPlan {
+    key_pattern: String,
+    key_column: ColumnSchema,
+    // (字段名, 目标列)
+    fields: Vec<(String, ColumnSchema)>,
+    // 冲突时按这些列更新，目标表没有主键时为空
+    conflict_columns: Vec<String>,
+}
+
+impl FlattenPlan {
+    // 没有显式映射时，目标表中与字段同名的列都会写入
+    pub fn new(
+        target: &TableSchema,
+        key_column: &str,
+        key_pattern: &str,
+        mappings: &[RedisFieldMapping],
+    ) -> Result<Self, String> {
+        let key = target
+            .column(key_column)
+            .ok_or_else(|| format!("Key column {} not found in target table {}", key_column, target.name))?
+            .clone();
+
+        let fields = if mappings.is_empty() {
+            target
+                .columns
+                .iter()
+                .filter(|c| !c.name.eq_ignore_ascii_case(key_column))
+                .map(|c| (c.name.clone(), c.clone()))
+                .collect()
+        } else {
+            let mut fields = Vec::with_capacity(mappings.len());
+            for mapping in mappings {
+                let column = target.column(mapping.column_name()).ok_or_else(|| {
+                    format!("Column {} not found in target table {}", mapping.column_name(), target.name)
+                })?;
+                if !column.name.eq_ignore_ascii_case(key_column) {
+                    fields.push((mapping.field.clone(), column.clone()));
+                }
+            }
+            fields
+        };
+
+        Ok(Self {
+            key_pattern: key_pattern.to_string(),
+            key_column: key,
+            fields,
+            conflict_columns: target.primary_key.clone(),
+        })
+    }
+
+    pub fn conflict_columns(&self) -> &[String] {
+        &self.conflict_columns
+    }
+
+    // 把一批键转换为行，只写入这批键中出现过的字段；未映射的字段记入 ignored
+    pub fn rows(
+        &self,
+        records: &[RedisRecord],
+        ignored: &mut BTreeSet<String>,
+    ) -> Result<(Vec<&ColumnSchema>, Vec<Vec<SqlValue>>), String> {
+        // SCAN可能重复返回同一个键，同一批中只保留最后一次
+        let mut latest: HashMap<String, &RedisRecord> = HashMap::new();
+        let mut order = Vec::new();
+        for record in records {
+            let suffix = key_suffix(&self.key_pattern, &record.key);
+            if latest.insert(suffix.clone(), record).is_none() {
+                order.push(suffix);
+            }
+        }
+
+        let present: Vec<&(String, ColumnSchema)> = self
+            .fields
+            .iter()
+            .filter(|(field, _)| latest.values().any(|r| r.field(field).is_some()))
+            .collect();
+        for record in latest.values() {
+            for (name, _) in &record.fields {
+                if !name.eq_ignore_ascii_case(&self.key_column.name)
+                    && !self.fields.iter().any(|(field, _)| field.eq_ignore_ascii_case(name))
+                {
+                    ignored.insert(name.clone());
+                }
+            }
+        }
+
+        let mut columns = vec![&self.key_column];
+        columns.extend(present.iter().map(|(_, column)| column));
+        let mut rows = Vec::with_capacity(order.len());
+        for suffix in order {
+            let record = latest[&suffix];
+            let key = column_value(&self.key_column, &JsonValue::String(suffix.clone()))
+                .filter(|v| !v.is_null())
+                .ok_or_else(|| {
+                    format!("Key {} does not fit key column {} ({})", record.key, self.key_column.name, self.key_column.data_type)
+                })?;
+            let mut row = vec![key];
+            for (field, column) in &present {
+                let value = match record.field(field) {
+                    Some(value) => column_value(column, value).ok_or_else(|| {
+                        format!(
+                            "Field {} of key {} does not fit column {} ({}); declare the column type explicitly",
+                            field, record.key, column.name, column.data_type
+                        )
+                    })?,
+                    None => SqlValue::Null,
+                };
+                row.push(value);
+            }
+            rows.push(row);
+        }
+        Ok((columns, rows))
+    }
+}
+
+// 行写入Redis时的值格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedisValueFormat {
+    // 每列一个哈希字段，NULL列不写入
+    #[default]
+    Hash,
+    // 整行编码为JSON对象写入字符串
+    Json,
+    // 作为有序集合的一个成员
+    SortedSet,
+}
+
+enum TemplatePart {
+    Literal(String),
+    Column(String),
+}
+
+// 键模板，{column} 替换为行中对应列的值，{{ 和 }} 表示花括号本身
+pub struct KeyTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl KeyTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("Unterminated placeholder in template {}", template)),
+                        }
+                    }
+                    let name = name.trim().to_string();
+                    if name.is_empty() {
+                        return Err(format!("Empty placeholder in template {}", template));
+                    }
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TemplatePart::Column(name));
+                }
+                '}' => return Err(format!("Unmatched }} in template {}", template)),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        if parts.is_empty() {
+            return Err("Template must not be empty".to_string());
+        }
+        Ok(Self { parts })
+    }
+
+    // 检查所有占位符都能在结果集的列中找到
+    pub fn validate(&self, rows: &SqlRowSet) -> Result<(), String> {
+        for part in &self.parts {
+            if let TemplatePart::Column(name) = part {
+                if rows.column_index(name).is_none() {
+                    return Err(format!(
+                        "Template placeholder {{{}}} has no matching column in source result (columns: {})",
+                        name,
+                        rows.columns.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 渲染一行，占位符对应的值为NULL时返回出错的列名
+    pub fn render(&self, rows: &SqlRowSet, row: &[SqlValue]) -> Result<String, String> {
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(text) => rendered.push_str(text),
+                TemplatePart::Column(name) => match rows.get(row, name).and_then(|v| v.as_string()) {
+                    Some(value) => rendered.push_str(&value),
+                    None => return Err(name.clone()),
+                },
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+// 把查询结果的行投影为Redis写入的方案
+pub struct ProjectionPlan {
+    key: KeyTemplate,
+    format: RedisValueFormat,
+    // 有序集合成员模板和分数列
+    member: Option<KeyTemplate>,
+    score_column: Option<String>,
+    // 过期表达式，渲染结果为秒数或绝对时间
+    ttl: Option<KeyTemplate>,
+}
+
+impl ProjectionPlan {
+    pub fn new(
+        key_template: &str,
+        format: RedisValueFormat,
+        member_template: Option<&str>,
+        score_column: Option<&str>,
+        ttl: Option<&str>,
+    ) -> Result<Self, String> {
+        let member = member_template.map(KeyTemplate::parse).transpose()?;
+        if format == RedisValueFormat::SortedSet && (member.is_none() || score_column.is_none()) {
+            return Err("Sorted set projection requires member_template and score_column".to_string());
+        }
+        Ok(Self {
+            key: KeyTemplate::parse(key_template)?,
+            format,
+            member,
+            score_column: score_column.map(str::to_string),
+            ttl: ttl.filter(|t| !t.trim().is_empty()).map(KeyTemplate::parse).transpose()?,
+        })
+    }
+
+    // 检查模板和分数列都能在结果集中找到
+    pub fn validate(&self, rows: &SqlRowSet) -> Result<(), String> {
+        self.key.validate(rows)?;
+        for template in self.member.iter().chain(self.ttl.iter()) {
+            template.validate(rows)?;
+        }
+        if let Some(score) = &self.score_column {
+            if rows.column_index(score).is_none() {
+                return Err(format!("Score column {} not found in source result", score));
+            }
+        }
+        Ok(())
+    }
+
+    // 把一行转换为写入，过期时间已过的行返回 None
+    pub fn write(&self, rows: &SqlRowSet, row: &[SqlValue], now: i64) -> Result<Option<RedisWrite>, String> {
+        let key = self
+            .key
+            .render(rows, row)
+            .map_err(|column| format!("Key column {} is NULL", column))?;
+
+        let expiry = match &self.ttl {
+            Some(ttl) => match ttl.render(rows, row) {
+                Ok(text) => match parse_expiry(&text, now)? {
+                    Some(expiry) => Some(expiry),
+                    None => return Ok(None),
+                },
+                // TTL列为NULL的行不设置过期时间
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        let value = match self.format {
+            RedisValueFormat::Hash => RedisWriteValue::Hash(
+                rows.columns
+                    .iter()
+                    .zip(row)
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(column, value)| (column.clone(), value_bytes(value)))
+                    .collect(),
+            ),
+            RedisValueFormat::Json => {
+                let object: serde_json::Map<String, JsonValue> = rows
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, value)| (column.clone(), json_value(value)))
+                    .collect();
+                RedisWriteValue::String(JsonValue::Object(object).to_string().into_bytes())
+            }
+            RedisValueFormat::SortedSet => {
+                let member = match &self.member {
+                    Some(member) => member
+                        .render(rows, row)
+                        .map_err(|column| format!("Member column {} is NULL", column))?,
+                    None => return Err("Sorted set projection requires member_template".to_string()),
+                };
+                let score_column = self.score_column.as_deref().unwrap_or_default();
+                let score = match rows.get(row, score_column) {
+                    Some(SqlValue::Int(i)) => *i as f64,
+                    Some(SqlValue::Float(f)) => *f,
+                    other => other
+                        .and_then(|v| v.as_string())
+                        .and_then(|s| s.trim().parse::<f64>().ok())
+                        .ok_or_else(|| format!("Score column {} is not a number: {:?}", score_column, other))?,
+                };
+                RedisWriteValue::SortedSetMember { member: member.into_bytes(), score }
+            }
+        };
+        Ok(Some(RedisWrite { key, value, expiry }))
+    }
+}
+
+// 整数表示剩余秒数，日期时间表示绝对过期时间（无时区时按UTC），已过期时返回 None
+fn parse_expiry(text: &str, now: i64) -> Result<Option<RedisExpiry>, String> {
+    let text = text.trim();
+    if let Ok(seconds) = text.parse::<i64>() {
+        return Ok((seconds > 0).then_some(RedisExpiry::After(seconds as u64)));
+    }
+    let timestamp = DateTime::parse_from_rfc3339(text)
+        .map(|t| t.timestamp())
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").map(|t| t.and_utc().timestamp()))
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").map(|t| t.and_utc().timestamp()))
+        .map_err(|_| format!("Invalid TTL value {}; expected seconds or a timestamp", text))?;
+    Ok((timestamp > now).then_some(RedisExpiry::At(timestamp)))
+}
+
+fn value_bytes(value: &SqlValue) -> Vec<u8> {
+    match value {
+        SqlValue::Bytes(bytes) => bytes.clone(),
+        other => other.as_string().unwrap_or_default().into_bytes(),
+    }
+}
+
+fn json_value(value: &SqlValue) -> JsonValue {
+    match value {
+        SqlValue::Null => JsonValue::Null,
+        SqlValue::Bool(b) => JsonValue::Bool(*b),
+        SqlValue::Int(i) => JsonValue::from(*i),
+        SqlValue::Float(f) => serde_json::Number::from_f64(*f).map(JsonValue::Number).unwrap_or(JsonValue::Null),
+        SqlValue::Text(text) => JsonValue::String(text.clone()),
+        SqlValue::Bytes(bytes) => JsonValue::String(String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
@@ -0,0 +1,108 @@
+// SQL模板模块 - 把 :name 形式的命名参数编译为目标数据库的绑定参数
+
+use crate::database::{DatabaseType, SqlRowSet, SqlValue};
+
+// 编译后的SQL模板
+#[derive(Debug, Clone)]
+pub struct SqlTemplate {
+    // 占位符已替换为目标数据库参数形式的SQL
+    pub sql: String,
+    // 按出现顺序排列的参数名
+    pub params: Vec<String>,
+}
+
+impl SqlTemplate {
+    // 解析模板，跳过字符串、引用标识符、注释以及PostgreSQL的 :: 类型转换
+    pub fn parse(template: &str, db_type: &DatabaseType) -> Result<Self, String> {
+        let chars: Vec<char> = template.chars().collect();
+        let mut sql = String::with_capacity(template.len());
+        let mut params = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                '\'' | '"' | '`' => {
+                    let end = find_quote_end(&chars, i, c, db_type)
+                        .ok_or_else(|| format!("Unterminated quote starting at position {}", i))?;
+                    sql.extend(&chars[i..=end]);
+                    i = end + 1;
+                }
+                '-' if chars.get(i + 1) == Some(&'-') => {
+                    let end = chars[i..].iter().position(|&c| c == '\n').map(|p| i + p).unwrap_or(chars.len());
+                    sql.extend(&chars[i..end]);
+                    i = end;
+                }
+                '/' if chars.get(i + 1) == Some(&'*') => {
+                    let end = (i + 2..chars.len().saturating_sub(1))
+                        .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                        .map(|j| j + 2)
+                        .ok_or_else(|| format!("Unterminated comment starting at position {}", i))?;
+                    sql.extend(&chars[i..end]);
+                    i = end;
+                }
+                ':' if chars.get(i + 1) == Some(&':') => {
+                    sql.push_str("::");
+                    i += 2;
+                }
+                ':' if chars.get(i + 1).is_some_and(|c| c.is_alphabetic() || *c == '_') => {
+                    let start = i + 1;
+                    let mut end = start;
+                    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                        end += 1;
+                    }
+                    params.push(chars[start..end].iter().collect());
+                    sql.push_str(&db_type.placeholder(params.len()));
+                    i = end;
+                }
+                _ => {
+                    sql.push(c);
+                    i += 1;
+                }
+            }
+        }
+
+        Ok(Self { sql, params })
+    }
+
+    // 检查所有参数都能在结果集的列中找到
+    pub fn validate(&self, rows: &SqlRowSet) -> Result<(), String> {
+        match self.params.iter().find(|name| rows.column_index(name).is_none()) {
+            Some(name) => Err(format!(
+                "Template parameter :{} has no matching column in source result (columns: {})",
+                name,
+                rows.columns.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // 按参数顺序取出一行中对应列的值
+    pub fn bind(&self, rows: &SqlRowSet, row: &[SqlValue]) -> Vec<SqlValue> {
+        self.params
+            .iter()
+            .map(|name| rows.get(row, name).cloned().unwrap_or(SqlValue::Null))
+            .collect()
+    }
+}
+
+// 查找引号的结束位置，连续两个引号视为转义
+fn find_quote_end(chars: &[char], start: usize, quote: char, db_type: &DatabaseType) -> Option<usize> {
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return Some(i);
+        }
+        // MySQL字符串支持反斜杠转义
+        if chars[i] == '\\' && quote != '`' && *db_type == DatabaseType::MySql {
+            i += 2;
+            continue;
+        }
+        i += 1;
+    }
+    None
+}
@@ -1,9 +1,26 @@
 // 迁移策略模块
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
-use crate::database::UnifiedConnectionManager;
+use crate::database::redis_manager;
+use crate::database::{ColumnSchema, DatabaseSchema, DatabaseType, DdlGenerator, SqlRowSet, SqlValue, SqlxDatabaseConnection, TableDdl, TableSchema, UnifiedConnectionManager};
+use crate::migration::cdc::mysql_binlog::MySqlBinlogCdcStrategy;
+use crate::migration::cdc::pg_logical::PostgresLogicalCdcStrategy;
+use crate::migration::cdc::sqlite_changelog::SqliteChangelog;
+use crate::migration::cdc::{ChangeApplier, ChangeEvent};
+use crate::migration::checkpoint::{self, CheckpointStore, RowFailure, TableCheckpoint, Watermark};
+use crate::migration::filter;
+use crate::migration::mapping::{self, ColumnRules, TableMapping};
+use crate::migration::plan::{self, MigrationPlan, StatementKind, TableAction, TablePlan};
+use crate::migration::redis_copy::{self, RedisCopier, RedisCopyStats};
+use crate::migration::redis_table::{self, FlattenPlan, ProjectionPlan, RedisFieldMapping, RedisValueFormat};
+use crate::migration::sql_template::SqlTemplate;
+use crate::migration::table_copy::{self, RowRules};
+use crate::migration::task::TaskContext;
+use crate::migration::task_log::TaskLogger;
+use crate::migration::transform::{self, EventTransform, RowTransform, Transformer};
 
 // 迁移策略特性
 #[async_trait::async_trait]
@@ -17,150 +34,1975 @@ pub trait MigrationStrategy: Send + Sync {
         source_db_id: &str,
         target_db_id: &str,
         conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
     ) -> Result<(), String>;
+
+    // 演练：只读取源库和目标库，生成将要执行的计划，不写入任何数据
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String>;
+
+    // 迁移完成后需要校验的源表，为空时校验源库全部表；返回None表示不支持校验
+    fn verification_tables(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    // 表映射规则，校验和差异比较时按它定位目标表和目标列
+    fn mappings(&self) -> &[TableMapping] {
+        &[]
+    }
+
+    // 按顺序作用于每一行的转换器
+    fn transformers(&self) -> &[Transformer] {
+        &[]
+    }
+
+    // 按源表配置的过滤条件，校验和差异比较时只比较源表中满足条件的行
+    fn filters(&self) -> &BTreeMap<String, String> {
+        &NO_FILTERS
+    }
+
+    // 映射规则、转换器和过滤条件合在一起，复制和比较表时按它处理每一行
+    fn row_rules(&self) -> RowRules<'_> {
+        RowRules::new(self.mappings(), self.transformers(), self.filters())
+    }
+
+    // 创建任务时按两端的表结构校验过滤条件、映射规则和转换器
+    async fn validate(
+        &self,
+        _source_db_id: &str,
+        _target_db_id: &str,
+        _conn_manager: Arc<UnifiedConnectionManager>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// 获取源端和目标端的SQL连接
+pub async fn get_sqlx_pair(
+    source_db_id: &str,
+    target_db_id: &str,
+    conn_manager: &UnifiedConnectionManager,
+) -> Result<(SqlxDatabaseConnection, SqlxDatabaseConnection), String> {
+    let source_conn = conn_manager.get_sqlx_connection(source_db_id).await
+        .ok_or_else(|| format!("Source SQL database connection not found: {}", source_db_id))?;
+    let target_conn = conn_manager.get_sqlx_connection(target_db_id).await
+        .ok_or_else(|| format!("Target SQL database connection not found: {}", target_db_id))?;
+    Ok((source_conn, target_conn))
+}
+
+// 没有过滤条件的策略共用的空表
+static NO_FILTERS: BTreeMap<String, String> = BTreeMap::new();
+
+fn default_batch_size() -> usize {
+    table_copy::DEFAULT_BATCH_SIZE
+}
+
+// 全量迁移策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FullMigrationStrategy {
+    name: String,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    // 表和列的映射规则
+    #[serde(default)]
+    mappings: Vec<TableMapping>,
+    // 读取和写入之间的行转换器
+    #[serde(default)]
+    transformers: Vec<Transformer>,
+    // 按源表配置的WHERE条件，只复制满足条件的行
+    #[serde(default)]
+    filters: BTreeMap<String, String>,
+}
+
+impl FullMigrationStrategy {
+    pub fn new() -> Self {
+        Self {
+            name: "Full Migration".to_string(),
+            batch_size: default_batch_size(),
+            mappings: Vec::new(),
+            transformers: Vec::new(),
+            filters: BTreeMap::new(),
+        }
+    }
+
+    // 设置每批读取的行数
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    // 设置表和列的映射规则
+    pub fn with_mappings(mut self, mappings: Vec<TableMapping>) -> Self {
+        self.mappings = mappings;
+        self
+    }
+
+    // 设置行转换器
+    pub fn with_transformers(mut self, transformers: Vec<Transformer>) -> Self {
+        self.transformers = transformers;
+        self
+    }
+
+    // 设置按源表的过滤条件
+    pub fn with_filters(mut self, filters: BTreeMap<String, String>) -> Self {
+        self.filters = filters;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationStrategy for FullMigrationStrategy {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+    
+    fn verification_tables(&self) -> Option<Vec<String>> {
+        Some(Vec::new())
+    }
+    
+    fn mappings(&self) -> &[TableMapping] {
+        &self.mappings
+    }
+    
+    fn transformers(&self) -> &[Transformer] {
+        &self.transformers
+    }
+    
+    fn filters(&self) -> &BTreeMap<String, String> {
+        &self.filters
+    }
+    
+    async fn validate(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+    ) -> Result<(), String> {
+        if self.mappings.is_empty() && self.transformers.is_empty() && self.filters.is_empty() {
+            return Ok(());
+        }
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        let schema = source_conn.introspect().await?;
+        filter::validate_filters(&self.filters, &schema, &[], &source_conn).await?;
+        transform::validate_transformers(&self.transformers, &schema, &[])?;
+        mapping::validate_mappings(&self.mappings, &[], &source_conn, &target_conn).await
+    }
+    
+    async fn execute(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<(), String> {
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        source_conn.test_connection().await?;
+        target_conn.test_connection().await?;
+
+        let total_copied = copy_tables(&source_conn, &target_conn, &[], self.row_rules(), self.batch_size, ctx).await?;
+        ctx.logger().info(format!("全量迁移完成，共复制 {} 行", total_copied)).await;
+        Ok(())
+    }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        source_conn.test_connection().await?;
+        target_conn.test_connection().await?;
+
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+        plan_tables(&mut plan, &source_conn, &target_conn, &[], self.row_rules(), self.batch_size, ctx).await?;
+        Ok(plan)
+    }
+}
+
+// 检查配置的源表都存在
+fn check_source_tables<'t>(schema: &DatabaseSchema, tables: impl IntoIterator<Item = &'t String>) -> Result<(), String> {
+    for table in tables {
+        if schema.table(table).is_none() {
+            return Err(format!("Source table not found: {}", table));
+        }
+    }
+    Ok(())
+}
+
+// 按外键依赖顺序选出要复制的源表，未指定表时为全部表
+fn select_source_tables<'s>(schema: &'s DatabaseSchema, tables: &[String]) -> Result<Vec<&'s TableSchema>, String> {
+    check_source_tables(schema, tables)?;
+    Ok(schema
+        .tables_in_dependency_order()
+        .into_iter()
+        .filter(|t| tables.is_empty() || tables.iter().any(|name| name.eq_ignore_ascii_case(&t.name)))
+        .collect())
+}
+
+// 源表经转换器和映射规则后的目标表结构，执行和演练按同样的方式解析目标表
+struct TargetShapes<'a> {
+    row_rules: RowRules<'a>,
+    generator: DdlGenerator,
+    shaped: DatabaseSchema,
+    projected: DatabaseSchema,
+}
+
+impl<'a> TargetShapes<'a> {
+    fn new(
+        source_conn: &SqlxDatabaseConnection,
+        target_conn: &SqlxDatabaseConnection,
+        schema: &DatabaseSchema,
+        row_rules: RowRules<'a>,
+    ) -> Self {
+        let shaped = transform::transform_schema(row_rules.transformers, schema);
+        let projected = mapping::project_schema(row_rules.mappings, &shaped);
+        Self {
+            row_rules,
+            generator: DdlGenerator::new(source_conn.db_type(), target_conn.db_type()),
+            shaped,
+            projected,
+        }
+    }
+
+    // 源表的映射规则、目标表结构和目标表的建表语句
+    fn resolve(&self, source_table: &TableSchema) -> (TableMapping, TableSchema, TableDdl) {
+        let mapping = mapping::mapping_for(self.row_rules.mappings, &source_table.name);
+        let shaped_table = self.shaped.table(&source_table.name).unwrap_or(source_table);
+        let target_shape = mapping::project_table(self.row_rules.mappings, &self.shaped, shaped_table);
+        let ddl = self.generator.generate_table(&target_shape, Some(&self.projected));
+        (mapping, target_shape, ddl)
+    }
+}
+
+// 把源库的表（为空时全部表）中满足过滤条件的行经转换器和映射规则复制到目标库，目标表不存在时自动创建，返回复制的行数
+pub async fn copy_tables(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    tables: &[String],
+    row_rules: RowRules<'_>,
+    batch_size: usize,
+    ctx: &TaskContext,
+) -> Result<u64, String> {
+    let schema = source_conn.introspect().await?;
+    let selected = select_source_tables(&schema, tables)?;
+    let shapes = TargetShapes::new(source_conn, target_conn, &schema, row_rules);
+    ctx.logger().info(format!("开始复制数据，共 {} 张表", selected.len())).await;
+
+    // 以源表当前满足条件的行数作为总量，复制期间的新增行可能使完成数略超总量
+    let mut total_rows = 0u64;
+    for source_table in &selected {
+        let rows = table_copy::count_rows(source_conn, &source_table.name, row_rules.filter(&source_table.name)).await?;
+        ctx.progress().add_table(&source_table.name, Some(rows)).await;
+        total_rows += rows;
+    }
+    ctx.progress().set_total(Some(total_rows)).await;
+    ctx.progress().update(0, format!("Copying {} tables", selected.len())).await;
+
+    let mut total_copied = 0u64;
+    let mut created_tables = Vec::new();
+    // 按外键依赖顺序复制，被引用的表先写入
+    for source_table in selected {
+        let (mapping, _, ddl) = shapes.resolve(source_table);
+        for warning in &ddl.warnings {
+            ctx.logger().warning(format!(
+                "类型映射提示 {}.{}: {}",
+                warning.table,
+                warning.column.as_deref().unwrap_or("-"),
+                warning.message
+            )).await;
+        }
+
+        // 目标表不存在时自动创建，中断前由本任务创建的表同样需要补充索引和外键
+        let (target_table, created) = table_copy::ensure_target_table(&ddl, target_conn, ctx.logger()).await?;
+        let mut checkpoint = match ctx.table_checkpoint(&source_table.name).await? {
+            Some(checkpoint) => checkpoint,
+            None => {
+                let checkpoint = TableCheckpoint {
+                    created_target: created,
+                    ..Default::default()
+                };
+                ctx.save_table_checkpoint(&source_table.name, &checkpoint).await?;
+                checkpoint
+            }
+        };
+        ctx.progress().set_table(Some(&source_table.name)).await;
+        if checkpoint.completed {
+            ctx.logger().info(format!("表 {} 已在上次执行中复制完成，跳过", source_table.name)).await;
+            if checkpoint.created_target {
+                created_tables.push(ddl);
+            }
+            total_copied += checkpoint.rows_copied;
+            ctx.progress().skip(checkpoint.rows_copied, format!("Skipped copied table {}", source_table.name)).await;
+            continue;
+        }
+        // 中断前已复制的行计入进度，之后由复制过程逐批累加
+        ctx.progress().skip(checkpoint.rows_copied, format!("Copying {}", source_table.name)).await;
+
+        let mapper = table_copy::RowMapper::new(source_table, &target_table, &mapping.columns, row_rules.transformers)
+            .map_err(|e| format!("Failed to copy table {}: {}", source_table.name, e))?
+            .with_filter(row_rules.filter(&source_table.name));
+        ctx.record_masked_columns(mapper.masked_columns(&source_table.name));
+        let copied = table_copy::copy_table(
+            source_conn,
+            target_conn,
+            source_table,
+            &target_table,
+            mapper,
+            batch_size,
+            ctx,
+        ).await.map_err(|e| format!("Failed to copy table {}: {}", source_table.name, e))?;
+
+        if checkpoint.created_target || created {
+            table_copy::finalize_target_table(&ddl, target_conn).await?;
+            created_tables.push(ddl);
+        }
+        checkpoint = ctx.table_checkpoint(&source_table.name).await?.unwrap_or(checkpoint);
+        checkpoint.completed = true;
+        ctx.save_table_checkpoint(&source_table.name, &checkpoint).await?;
+
+        ctx.logger().info(format!("表 {} 复制完成，共 {} 行", source_table.name, copied)).await;
+        total_copied += copied;
+    }
+
+    ctx.progress().set_table(None).await;
+
+    // 外键最后添加，避免导入顺序导致的约束冲突；添加后清除创建标记，恢复时不再重复添加
+    for ddl in &created_tables {
+        table_copy::add_foreign_keys(ddl, target_conn).await?;
+        if let Some(mut checkpoint) = ctx.table_checkpoint(&ddl.table).await? {
+            checkpoint.created_target = false;
+            ctx.save_table_checkpoint(&ddl.table, &checkpoint).await?;
+        }
+    }
+
+    Ok(total_copied)
+}
+
+// 演练copy_tables：读取两端的表结构和源表行数，把建表、写入和外键语句记入计划
+pub async fn plan_tables(
+    plan: &mut MigrationPlan,
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    tables: &[String],
+    row_rules: RowRules<'_>,
+    batch_size: usize,
+    ctx: &TaskContext,
+) -> Result<(), String> {
+    let schema = source_conn.introspect().await?;
+    let selected = select_source_tables(&schema, tables)?;
+    let shapes = TargetShapes::new(source_conn, target_conn, &schema, row_rules);
+    let target_type = target_conn.db_type();
+
+    let mut created_tables = Vec::new();
+    for source_table in selected {
+        let (mapping, target_shape, ddl) = shapes.resolve(source_table);
+        let (target_table, create) = plan::plan_target_table(&shapes.generator, &target_shape, &ddl, target_conn).await?;
+        let action = if create { TableAction::Create } else { TableAction::Existing };
+
+        // 检查点中已复制的行在继续执行时跳过
+        let checkpoint = ctx.table_checkpoint(&source_table.name).await?.unwrap_or_default();
+        let row_filter = row_rules.filter(&source_table.name);
+        let rows = table_copy::count_rows(source_conn, &source_table.name, row_filter).await?;
+        let mut table = TablePlan::new(source_table, &target_table, &mapping.columns, row_rules.transformers, action)?;
+        table.filter = row_filter.map(str::to_string);
+        table.resumed_rows = checkpoint.rows_copied;
+        table.estimated_rows = Some(if checkpoint.completed {
+            0
+        } else {
+            rows.saturating_sub(checkpoint.rows_copied)
+        });
+
+        if create {
+            plan.add_ddl_warnings(&ddl);
+        }
+        plan.add_table_copy(table, &target_table, &target_type, create.then_some(&ddl), batch_size);
+        if create || checkpoint.created_target {
+            created_tables.push(ddl);
+        }
+    }
+
+    for ddl in &created_tables {
+        plan.add_foreign_keys(ddl);
+    }
+    Ok(())
+}
+
+// 增量迁移中单张表的水位配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatermarkTable {
+    // 表名
+    pub table: String,
+    // 单调递增的水位列，如自增ID或updated_at
+    pub column: String,
+}
+
+// 增量复制一张表的读取范围：水位列加主键列构成排序键，从已复制的位置之后读取满足过滤条件的行，执行和演练共用
+struct IncrementalRange<'a> {
+    db_type: DatabaseType,
+    // 水位列在前，之后是主键列
+    order_columns: Vec<&'a ColumnSchema>,
+    key_columns: Vec<String>,
+    filter: Option<&'a str>,
+    // 已复制到的位置，为空时从头开始
+    position: Option<Watermark>,
+}
+
+impl<'a> IncrementalRange<'a> {
+    // 解析排序键并读取任务为目标表保存的水位，需要提示的问题作为警告返回，执行时写入任务日志，演练时记入计划
+    async fn load(
+        db_type: DatabaseType,
+        store: &CheckpointStore,
+        task_id: &str,
+        source_table: &'a TableSchema,
+        target_table: &str,
+        watermark_column: &str,
+        filter: Option<&'a str>,
+    ) -> Result<(Self, Vec<String>), String> {
+        // 水位值相同的行按主键继续分页
+        let watermark = source_table
+            .column(watermark_column)
+            .ok_or_else(|| format!("Watermark column {}.{} not found", source_table.name, watermark_column))?;
+        let mut order_columns = vec![watermark];
+        order_columns.extend(
+            source_table
+                .primary_key_columns()
+                .into_iter()
+                .filter(|c| !c.name.eq_ignore_ascii_case(&watermark.name)),
+        );
+        let key_columns: Vec<String> = order_columns[1..].iter().map(|c| c.name.clone()).collect();
+
+        let mut warnings = Vec::new();
+        if key_columns.is_empty() {
+            warnings.push(format!(
+                "表 {} 没有主键，水位列 {} 的值需唯一，否则批次边界上的同值行可能遗漏",
+                source_table.name, watermark.name
+            ));
+        }
+        // 水位列、主键或过滤条件变化后旧标记失效，从头开始；已写入目标表的行按主键覆盖，不会重复
+        let position = match store.load_watermark(task_id, target_table).await? {
+            Some(mark) if mark.matches(&watermark.name, &key_columns, filter) => Some(mark),
+            Some(mark) => {
+                let changed = if mark.filter.as_deref() != filter { "过滤条件" } else { "水位配置" };
+                warnings.push(format!("表 {} 的{}已变化，从头开始增量迁移", source_table.name, changed));
+                None
+            }
+            None => None,
+        };
+
+        Ok((Self { db_type, order_columns, key_columns, filter, position }, warnings))
+    }
+
+    // 已复制位置之后的WHERE子句和参数，首次迁移跳过水位为空的行
+    fn where_clause(&self) -> (String, Vec<SqlValue>) {
+        let (condition, params) = match &self.position {
+            Some(mark) => {
+                let mut values = vec![mark.value.clone()];
+                values.extend(mark.last_key.iter().cloned());
+                table_copy::keyset_predicate(&self.db_type, &self.order_columns, &values, 1)
+            }
+            None => (format!("{} IS NOT NULL", self.db_type.quote_identifier(&self.order_columns[0].name)), vec![]),
+        };
+        (filter::where_clause(self.filter, &condition), params)
+    }
+
+    // 排序键各列的值（水位列在前）对应的水位标记
+    fn watermark_at(&self, mut values: Vec<SqlValue>) -> Watermark {
+        let value = values.remove(0);
+        Watermark {
+            column: self.order_columns[0].name.clone(),
+            value,
+            key_columns: self.key_columns.clone(),
+            last_key: values,
+            filter: self.filter.map(str::to_string),
+            updated_at: String::new(),
+        }
+    }
+}
+
+// 增量复制的一张表：两端的连接和表结构、列映射规则和水位列
+struct IncrementalTable<'a> {
+    source_conn: &'a SqlxDatabaseConnection,
+    target_conn: &'a SqlxDatabaseConnection,
+    source_table: &'a TableSchema,
+    target_table: &'a TableSchema,
+    rules: &'a ColumnRules,
+    watermark_column: &'a str,
+}
+
+fn default_checkpoint_db_id() -> String {
+    checkpoint::DEFAULT_CONFIG_DB_ID.to_string()
+}
+
+// 增量迁移策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalMigrationStrategy {
+    name: String,
+    tables: Vec<WatermarkTable>,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    // 保存水位标记的配置库连接ID
+    #[serde(default = "default_checkpoint_db_id")]
+    checkpoint_db_id: String,
+    // 通过触发器变更日志同步的SQLite源表
+    #[serde(default)]
+    changelog_tables: Vec<String>,
+    // 表和列的映射规则
+    #[serde(default)]
+    mappings: Vec<TableMapping>,
+    // 读取和写入之间的行转换器，同样作用于变更日志
+    #[serde(default)]
+    transformers: Vec<Transformer>,
+    // 按源表配置的WHERE条件，只复制满足条件的行；变更日志同步的表不支持
+    #[serde(default)]
+    filters: BTreeMap<String, String>,
+}
+
+impl IncrementalMigrationStrategy {
+    pub fn new(tables: Vec<WatermarkTable>) -> Self {
+        Self {
+            name: "Incremental Migration".to_string(),
+            tables,
+            batch_size: default_batch_size(),
+            checkpoint_db_id: default_checkpoint_db_id(),
+            changelog_tables: Vec::new(),
+            mappings: Vec::new(),
+            transformers: Vec::new(),
+            filters: BTreeMap::new(),
+        }
+    }
+
+    // 设置每批读取的行数
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    // 设置保存水位标记的配置库
+    pub fn with_checkpoint_db(mut self, checkpoint_db_id: String) -> Self {
+        self.checkpoint_db_id = checkpoint_db_id;
+        self
+    }
+
+    // 设置通过触发器变更日志同步的SQLite源表
+    pub fn with_changelog_tables(mut self, changelog_tables: Vec<String>) -> Self {
+        self.changelog_tables = changelog_tables;
+        self
+    }
+
+    // 设置表和列的映射规则
+    pub fn with_mappings(mut self, mappings: Vec<TableMapping>) -> Self {
+        self.mappings = mappings;
+        self
+    }
+
+    // 设置行转换器
+    pub fn with_transformers(mut self, transformers: Vec<Transformer>) -> Self {
+        self.transformers = transformers;
+        self
+    }
+
+    // 设置按源表的过滤条件
+    pub fn with_filters(mut self, filters: BTreeMap<String, String>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    // 变更日志记录全部行的变更，无法只同步满足条件的行
+    fn check_changelog_filters(&self) -> Result<(), String> {
+        match self.changelog_tables.iter().find(|t| filter::filter_for(&self.filters, t).is_some()) {
+            Some(table) => Err(format!("Filters are not supported for changelog table {}", table)),
+            None => Ok(()),
+        }
+    }
+
+    // 读取变更日志并应用到目标库，目标库提交后确认并删除日志
+    async fn drain_changelog(
+        &self,
+        changelog: &SqliteChangelog,
+        target_conn: &SqlxDatabaseConnection,
+        tables: &[&TableSchema],
+        ctx: &TaskContext,
+    ) -> Result<u64, String> {
+        let mut applier = ChangeApplier::new(target_conn.clone());
+        let mut transform = EventTransform::new(&self.transformers);
+        let mut applied = 0u64;
+        loop {
+            ctx.wait_if_paused().await?;
+            let records = changelog.read(tables, self.batch_size).await?;
+            let last_seq = match records.last() {
+                Some(record) => record.seq,
+                None => break,
+            };
+            // 先按源表列名转换，再改写为目标表和目标列
+            let events = records
+                .into_iter()
+                .map(|record| Ok(mapping::map_event(&self.mappings, transform.apply(record.event)?)))
+                .collect::<Result<Vec<ChangeEvent>, String>>()?;
+            let count = applier.apply(&events, ctx).await?;
+            ctx.record_masked_columns(transform.masked_columns());
+            changelog.acknowledge(tables, last_seq).await?;
+            applied += count;
+            ctx.progress().advance(count, format!("Applied {} changelog records", applied)).await;
+        }
+        Ok(applied)
+    }
+
+    // 复制水位之后的行，每批写入成功后保存新的水位
+    async fn copy_table_incremental(
+        &self,
+        table: IncrementalTable<'_>,
+        store: &CheckpointStore,
+        ctx: &TaskContext,
+    ) -> Result<u64, String> {
+        let IncrementalTable { source_conn, target_conn, source_table, target_table, rules, watermark_column } = table;
+        let source_type = source_conn.db_type();
+        let mut mapper = table_copy::RowMapper::new(source_table, target_table, rules, &self.transformers)?;
+        ctx.record_masked_columns(mapper.masked_columns(&source_table.name));
+
+        let filter = filter::filter_for(&self.filters, &source_table.name);
+        let (mut range, warnings) = IncrementalRange::load(
+            source_type.clone(),
+            store,
+            ctx.task_id(),
+            source_table,
+            &target_table.name,
+            watermark_column,
+            filter,
+        )
+        .await?;
+        for warning in warnings {
+            ctx.logger().warning(warning).await;
+        }
+
+        // 排序键不在读取列中时额外读取
+        let order_indexes: Vec<usize> = range.order_columns.iter().map(|column| mapper.select_index(column)).collect();
+        let select_columns = mapper.select_columns().to_vec();
+        let target_columns = mapper.target_columns().to_vec();
+
+        // 目标表按主键写入，重复执行同一批次不会产生重复行
+        let upsert_keys: Vec<String> = target_table
+            .primary_key_columns()
+            .iter()
+            .filter(|k| target_columns.iter().any(|c| c.name == k.name))
+            .map(|k| k.name.clone())
+            .collect();
+
+        let base_sql = table_copy::select_columns_sql(&source_type, &source_table.name, &select_columns);
+        let order_by: Vec<String> = range
+            .order_columns
+            .iter()
+            .map(|c| source_type.quote_identifier(&c.name))
+            .collect();
+        let batch_size = self.batch_size.max(1);
+        let mut copied = 0u64;
+
+        loop {
+            ctx.wait_if_paused().await?;
+            let (where_clause, params) = range.where_clause();
+            let sql = format!("{}{} ORDER BY {} LIMIT {}", base_sql, where_clause, order_by.join(", "), batch_size);
+            let batch = source_conn.fetch_rows(&sql, params).await?;
+            let fetched = batch.rows.len();
+            if fetched == 0 {
+                break;
+            }
+
+            let rows: Vec<Vec<SqlValue>> = batch
+                .rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .zip(select_columns.iter())
+                        .map(|(value, column)| table_copy::decode_value(&source_type, column, value))
+                        .collect()
+                })
+                .collect();
+
+            let last = &rows[fetched - 1];
+            let next = range.watermark_at(order_indexes.iter().map(|&i| last[i].clone()).collect());
+
+            let rows = rows
+                .into_iter()
+                .map(|row| mapper.map_row(row))
+                .collect::<Result<Vec<_>, String>>()?;
+            let bytes = table_copy::rows_bytes(&rows);
+            table_copy::upsert_rows(target_conn, &target_table.name, &target_columns, &upsert_keys, rows).await?;
+            ctx.record_rows(fetched as u64, fetched as u64, bytes);
+
+            // 目标端提交后再推进水位
+            store.save_watermark(ctx.task_id(), &target_table.name, &next).await?;
+            range.position = Some(next);
+            copied += fetched as u64;
+            ctx.progress()
+                .advance(fetched as u64, format!("Copied {} new rows of {}", copied, source_table.name))
+                .await;
+
+            if fetched < batch_size {
+                break;
+            }
+        }
+
+        Ok(copied)
+    }
+
+    // 演练时统计水位之后待复制的行数，读取范围与执行时相同
+    async fn count_incremental_rows(
+        &self,
+        source_conn: &SqlxDatabaseConnection,
+        store: &CheckpointStore,
+        source_table: &TableSchema,
+        target_table: &str,
+        watermark_column: &str,
+        plan: &mut MigrationPlan,
+    ) -> Result<u64, String> {
+        let source_type = source_conn.db_type();
+        let filter = filter::filter_for(&self.filters, &source_table.name);
+        let (range, warnings) = IncrementalRange::load(
+            source_type.clone(),
+            store,
+            &plan.task_id,
+            source_table,
+            target_table,
+            watermark_column,
+            filter,
+        )
+        .await?;
+        for warning in warnings {
+            plan.warn(warning);
+        }
+
+        let (where_clause, params) = range.where_clause();
+        let sql = format!("SELECT COUNT(*) FROM {}{}", source_type.quote_identifier(&source_table.name), where_clause);
+        let result = source_conn.fetch_rows(&sql, params).await?;
+        Ok(result
+            .rows
+            .first()
+            .and_then(|row| row.first())
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0) as u64)
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationStrategy for IncrementalMigrationStrategy {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+    
+    // 只校验按水位线或变更日志同步的表
+    fn verification_tables(&self) -> Option<Vec<String>> {
+        Some(self.tables.iter().map(|t| t.table.clone()).chain(self.changelog_tables.iter().cloned()).collect())
+    }
+    
+    fn mappings(&self) -> &[TableMapping] {
+        &self.mappings
+    }
+    
+    fn transformers(&self) -> &[Transformer] {
+        &self.transformers
+    }
+    
+    fn filters(&self) -> &BTreeMap<String, String> {
+        &self.filters
+    }
+    
+    async fn validate(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+    ) -> Result<(), String> {
+        if self.mappings.is_empty() && self.transformers.is_empty() && self.filters.is_empty() {
+            return Ok(());
+        }
+        self.check_changelog_filters()?;
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        let tables: Vec<String> = self.tables.iter().map(|t| t.table.clone()).chain(self.changelog_tables.iter().cloned()).collect();
+        let schema = source_conn.introspect().await?;
+        filter::validate_filters(&self.filters, &schema, &tables, &source_conn).await?;
+        transform::validate_transformers(&self.transformers, &schema, &tables)?;
+        mapping::validate_mappings(&self.mappings, &tables, &source_conn, &target_conn).await
+    }
+    
+    async fn execute(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<(), String> {
+        if self.tables.is_empty() && self.changelog_tables.is_empty() {
+            return Err("No watermark or changelog tables configured for incremental migration".to_string());
+        }
+        self.check_changelog_filters()?;
+
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        source_conn.test_connection().await?;
+        target_conn.test_connection().await?;
+
+        let store = CheckpointStore::open(&conn_manager, &self.checkpoint_db_id).await?;
+
+        let schema = source_conn.introspect().await?;
+        check_source_tables(&schema, self.tables.iter().map(|t| &t.table).chain(&self.changelog_tables))?;
+        let changelog = if self.changelog_tables.is_empty() {
+            None
+        } else {
+            Some(SqliteChangelog::new(source_conn.clone())?)
+        };
+        let installed = match &changelog {
+            Some(changelog) => changelog.installed_tables().await?,
+            None => Vec::new(),
+        };
+        let shapes = TargetShapes::new(&source_conn, &target_conn, &schema, self.row_rules());
+        ctx.logger().info(format!("增量迁移开始，共 {} 张表", self.tables.len() + self.changelog_tables.len())).await;
+        // 增量的行数无法预估，只上报已复制的行数
+        ctx.progress().set_total(None).await;
+        ctx.progress().update(0, "Starting incremental copy".to_string()).await;
+
+        let mut total_copied = 0u64;
+        let mut created_tables = Vec::new();
+        let mut changelog_sources = Vec::new();
+        for source_table in schema.tables_in_dependency_order() {
+            let is_changelog_table = self
+                .changelog_tables
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&source_table.name));
+            if let (true, Some(changelog)) = (is_changelog_table, &changelog) {
+                if source_table.primary_key.is_empty() {
+                    return Err(format!(
+                        "Changelog table {} needs a primary key to apply updates and deletes",
+                        source_table.name
+                    ));
+                }
+                ctx.progress().set_table(Some(&source_table.name)).await;
+                let (mapping, _, ddl) = shapes.resolve(source_table);
+                let (target_table, created) = table_copy::ensure_target_table(&ddl, &target_conn, ctx.logger()).await?;
+
+                // 先安装触发器再全量同步，同步期间的变更会在之后重放；中断的初始同步从检查点继续
+                let resuming = ctx
+                    .table_checkpoint(&source_table.name)
+                    .await?
+                    .is_some_and(|checkpoint| !checkpoint.completed);
+                if !installed.contains(&source_table.name) || resuming {
+                    if !installed.contains(&source_table.name) {
+                        ctx.save_table_checkpoint(&source_table.name, &TableCheckpoint::default()).await?;
+                        changelog.install(source_table).await?;
+                        ctx.logger().info(format!("已为表 {} 安装变更捕获触发器", source_table.name)).await;
+                    }
+                    let mapper = table_copy::RowMapper::new(source_table, &target_table, &mapping.columns, &self.transformers)
+                        .map_err(|e| format!("Failed to copy table {}: {}", source_table.name, e))?;
+                    ctx.record_masked_columns(mapper.masked_columns(&source_table.name));
+                    let copied = table_copy::sync_table(
+                        &source_conn,
+                        &target_conn,
+                        source_table,
+                        &target_table,
+                        mapper,
+                        self.batch_size,
+                        ctx,
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to copy table {}: {}", source_table.name, e))?;
+                    if let Some(mut checkpoint) = ctx.table_checkpoint(&source_table.name).await? {
+                        checkpoint.completed = true;
+                        ctx.save_table_checkpoint(&source_table.name, &checkpoint).await?;
+                    }
+                    ctx.logger().info(format!("表 {} 初始同步完成，共 {} 行", source_table.name, copied)).await;
+                    total_copied += copied;
+                }
+
+                if created {
+                    table_copy::finalize_target_table(&ddl, &target_conn).await?;
+                    created_tables.push(ddl);
+                }
+                changelog_sources.push(source_table);
+                continue;
+            }
+
+            let config = match self.tables.iter().find(|t| t.table.eq_ignore_ascii_case(&source_table.name)) {
+                Some(config) => config,
+                None => continue,
+            };
+
+            ctx.progress().set_table(Some(&source_table.name)).await;
+            let (mapping, _, ddl) = shapes.resolve(source_table);
+            let (target_table, created) = table_copy::ensure_target_table(&ddl, &target_conn, ctx.logger()).await?;
+
+            let table = IncrementalTable {
+                source_conn: &source_conn,
+                target_conn: &target_conn,
+                source_table,
+                target_table: &target_table,
+                rules: &mapping.columns,
+                watermark_column: &config.column,
+            };
+            let copied = self
+                .copy_table_incremental(table, &store, ctx)
+                .await
+                .map_err(|e| format!("Failed to copy table {}: {}", source_table.name, e))?;
+
+            if created {
+                table_copy::finalize_target_table(&ddl, &target_conn).await?;
+                created_tables.push(ddl);
+            }
+
+            ctx.logger().info(format!("表 {} 增量复制完成，共 {} 行", source_table.name, copied)).await;
+            total_copied += copied;
+        }
+
+        ctx.progress().set_table(None).await;
+
+        if let Some(changelog) = &changelog {
+            let applied = self.drain_changelog(changelog, &target_conn, &changelog_sources, ctx).await?;
+            ctx.logger().info(format!("变更日志应用完成，共 {} 条变更", applied)).await;
+            total_copied += applied;
+        }
+
+        for ddl in &created_tables {
+            table_copy::add_foreign_keys(ddl, &target_conn).await?;
+        }
+
+        ctx.logger().info(format!("增量迁移完成，共复制 {} 行", total_copied)).await;
+        Ok(())
+    }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        if self.tables.is_empty() && self.changelog_tables.is_empty() {
+            return Err("No watermark or changelog tables configured for incremental migration".to_string());
+        }
+        self.check_changelog_filters()?;
+
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        source_conn.test_connection().await?;
+        target_conn.test_connection().await?;
+
+        let store = CheckpointStore::open(&conn_manager, &self.checkpoint_db_id).await?;
+
+        let schema = source_conn.introspect().await?;
+        check_source_tables(&schema, self.tables.iter().map(|t| &t.table).chain(&self.changelog_tables))?;
+        let changelog = if self.changelog_tables.is_empty() {
+            None
+        } else {
+            Some(SqliteChangelog::new(source_conn.clone())?)
+        };
+        let installed = match &changelog {
+            Some(changelog) => changelog.installed_tables().await?,
+            None => Vec::new(),
+        };
+        let shapes = TargetShapes::new(&source_conn, &target_conn, &schema, self.row_rules());
+        let target_type = target_conn.db_type();
+
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+        let mut created_tables = Vec::new();
+        let mut changelog_sources = Vec::new();
+        for source_table in schema.tables_in_dependency_order() {
+            let is_changelog_table = self
+                .changelog_tables
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&source_table.name));
+            let config = self.tables.iter().find(|t| t.table.eq_ignore_ascii_case(&source_table.name));
+            if !is_changelog_table && config.is_none() {
+                continue;
+            }
+            let (mapping, target_shape, ddl) = shapes.resolve(source_table);
+            let (estimated_rows, resumed_rows) = match (is_changelog_table, config) {
+                (true, _) => {
+                    if source_table.primary_key.is_empty() {
+                        return Err(format!(
+                            "Changelog table {} needs a primary key to apply updates and deletes",
+                            source_table.name
+                        ));
+                    }
+                    // 未安装触发器时先安装再全量同步，中断的初始同步从检查点继续
+                    let is_installed = installed.contains(&source_table.name);
+                    if !is_installed {
+                        for sql in SqliteChangelog::install_statements(source_table) {
+                            plan.add_statement(StatementKind::SourceSetup, Some(&source_table.name), sql, Some(1));
+                        }
+                    }
+                    changelog_sources.push(source_table);
+                    match ctx.table_checkpoint(&source_table.name).await? {
+                        Some(checkpoint) if is_installed && !checkpoint.completed => {
+                            let rows = table_copy::count_rows(&source_conn, &source_table.name, None).await?;
+                            (rows.saturating_sub(checkpoint.rows_copied), checkpoint.rows_copied)
+                        }
+                        _ if !is_installed => (table_copy::count_rows(&source_conn, &source_table.name, None).await?, 0),
+                        _ => (0, 0),
+                    }
+                }
+                (false, Some(config)) => {
+                    let rows = self
+                        .count_incremental_rows(&source_conn, &store, source_table, &ddl.table, &config.column, &mut plan)
+                        .await?;
+                    (rows, 0)
+                }
+                (false, None) => continue,
+            };
+
+            let (target_table, create) = plan::plan_target_table(&shapes.generator, &target_shape, &ddl, &target_conn).await?;
+            let action = if create { TableAction::Create } else { TableAction::Existing };
+            let mut table = TablePlan::new(source_table, &target_table, &mapping.columns, &self.transformers, action)?;
+            table.filter = filter::filter_for(&self.filters, &source_table.name).map(str::to_string);
+            table.estimated_rows = Some(estimated_rows);
+            table.resumed_rows = resumed_rows;
+
+            if create {
+                plan.add_ddl_warnings(&ddl);
+            }
+            plan.add_table_copy(table, &target_table, &target_type, create.then_some(&ddl), self.batch_size);
+            if create {
+                created_tables.push(ddl);
+            }
+        }
+
+        // 已记录的变更在表同步之后应用
+        if let Some(changelog) = &changelog {
+            plan.add_rows(Some(changelog.pending(&changelog_sources).await?));
+        }
+
+        for ddl in &created_tables {
+            plan.add_foreign_keys(ddl);
+        }
+        Ok(plan)
+    }
+}
+
+fn default_max_failed_rows() -> u64 {
+    100
+}
+
+// 自定义SQL迁移策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomSQLMigrationStrategy {
+    name: String,
+    source_sql: String,
+    target_sql_template: String,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    // 失败行超过该数量时中止迁移
+    #[serde(default = "default_max_failed_rows")]
+    max_failed_rows: u64,
+    // 源查询结果的列映射规则，在绑定模板参数前应用
+    #[serde(default)]
+    mapping: ColumnRules,
+    // 源查询结果的行转换器，在列映射规则之前应用，列名为源查询的列名
+    #[serde(default)]
+    transformers: Vec<Transformer>,
+}
+
+impl CustomSQLMigrationStrategy {
+    pub fn new(name: String, source_sql: String, target_sql_template: String) -> Self {
+        Self {
+            name,
+            source_sql,
+            target_sql_template,
+            batch_size: default_batch_size(),
+            max_failed_rows: default_max_failed_rows(),
+            mapping: ColumnRules::default(),
+            transformers: Vec::new(),
+        }
+    }
+
+    // 设置每批写入的行数
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    // 设置允许的最大失败行数
+    pub fn with_max_failed_rows(mut self, max_failed_rows: u64) -> Self {
+        self.max_failed_rows = max_failed_rows;
+        self
+    }
+
+    // 设置源查询结果的列映射规则
+    pub fn with_mapping(mut self, mapping: ColumnRules) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    // 设置源查询结果的行转换器
+    pub fn with_transformers(mut self, transformers: Vec<Transformer>) -> Self {
+        self.transformers = transformers;
+        self
+    }
+
+    // 对查询结果依次执行转换链和列映射规则，转换链在取得第一批结果的列名后编译
+    fn shape_rows(&self, transform: &mut Option<RowTransform>, rows: &mut SqlRowSet) -> Result<(), String> {
+        if !self.transformers.is_empty() && !rows.columns.is_empty() {
+            if transform.is_none() {
+                *transform = Some(RowTransform::new(&self.transformers, None, &rows.columns)?);
+            }
+            if let Some(transform) = transform {
+                transform.apply_to_rows(rows)?;
+            }
+        }
+        self.mapping.apply_to_rows(rows);
+        Ok(())
+    }
 }
 
-// 全量迁移策略
+#[async_trait::async_trait]
+impl MigrationStrategy for CustomSQLMigrationStrategy {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+    
+    fn transformers(&self) -> &[Transformer] {
+        &self.transformers
+    }
+    
+    // 按源查询的第一行校验转换器和映射规则，转换和映射后的列需满足模板参数；查询没有结果时无法取得列名，只检查转换器的参数
+    async fn validate(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+    ) -> Result<(), String> {
+        if self.mapping.is_empty() && self.transformers.is_empty() {
+            return Ok(());
+        }
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        let template = SqlTemplate::parse(&self.target_sql_template, &target_conn.db_type())?;
+        let sql = format!("{} LIMIT 1", wrap_source_query("*", &self.source_sql));
+        transform::check_transformers(&self.transformers)?;
+        let mut sample = source_conn.fetch_rows(&sql, vec![]).await?;
+        if sample.rows.is_empty() {
+            return Ok(());
+        }
+        let transform = RowTransform::new(&self.transformers, None, &sample.columns)?;
+        self.mapping.validate("query", transform.columns())?;
+        self.shape_rows(&mut Some(transform), &mut sample)?;
+        template.validate(&sample)
+    }
+    
+    async fn execute(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<(), String> {
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        source_conn.test_connection().await?;
+        target_conn.test_connection().await?;
+
+        // 模板中的 :name 编译为目标库的绑定参数，值不会拼接进SQL
+        let template = SqlTemplate::parse(&self.target_sql_template, &target_conn.db_type())?;
+        ctx.logger().info(format!("自定义SQL迁移开始，模板参数: {:?}", template.params)).await;
+
+        // 任务中断后跳过已写入的行，要求源查询的结果顺序稳定；之前失败的行记录在检查点中，继续执行时一并计数和报告
+        let mut checkpoint = ctx.table_checkpoint(QUERY_CHECKPOINT).await?.unwrap_or_default();
+        let mut skip = checkpoint.rows_copied;
+        if skip > 0 {
+            ctx.logger().info(format!(
+                "从检查点继续，跳过已处理的 {} 行，其中 {} 行写入失败",
+                skip, checkpoint.rows_failed
+            )).await;
+        }
+
+        let mut batches = source_conn.stream_rows(self.source_sql.clone(), vec![], self.batch_size);
+        let mut transform = None;
+        let mut validated = false;
+        let mut total_rows = skip;
+        let mut succeeded = skip.saturating_sub(checkpoint.rows_failed);
+
+        while let Some(batch) = batches.recv().await {
+            let mut batch = batch?;
+            ctx.wait_if_paused().await?;
+            self.shape_rows(&mut transform, &mut batch)?;
+            if !validated {
+                template.validate(&batch)?;
+                // 查询结果没有表名，以策略名称记录脱敏的列
+                if let Some(transform) = &transform {
+                    ctx.record_masked_columns(transform.masked_columns(&self.name));
+                }
+                validated = true;
+            }
+            if skip_rows(&mut batch, &mut skip) {
+                continue;
+            }
+
+            let first_row = total_rows + 1;
+            let params: Vec<Vec<SqlValue>> = batch.rows.iter().map(|row| template.bind(&batch, row)).collect();
+            let count = params.len() as u64;
+            total_rows += count;
+
+            let statements = params.iter().map(|p| (template.sql.clone(), p.clone())).collect();
+            let bytes = table_copy::rows_bytes(&params);
+            if let Err(batch_error) = target_conn.execute_batch(statements).await {
+                // 整批回滚后逐行重试，定位失败的行
+                ctx.logger().warning(format!("第 {} 行起的批次写入失败，逐行重试: {}", first_row, batch_error)).await;
+                for (offset, values) in params.into_iter().enumerate() {
+                    let row_bytes = table_copy::rows_bytes(std::slice::from_ref(&values));
+                    match target_conn.execute_values(&template.sql, values).await {
+                        Ok(_) => {
+                            succeeded += 1;
+                            ctx.record_rows(1, 1, row_bytes);
+                        }
+                        Err(error) => {
+                            let row_number = first_row + offset as u64;
+                            ctx.record_rows(1, 0, 0);
+                            ctx.record_failed_rows(1);
+                            ctx.logger().warning(format!("第 {} 行写入失败: {}", row_number, error)).await;
+                            checkpoint.rows_failed += 1;
+                            if checkpoint.rows_failed > self.max_failed_rows {
+                                return Err(format!(
+                                    "Custom SQL migration aborted after {} failed rows; last error at row {}: {}",
+                                    checkpoint.rows_failed,
+                                    row_number,
+                                    error
+                                ));
+                            }
+                            if checkpoint.failed_rows.len() < MAX_REPORTED_FAILED_ROWS {
+                                checkpoint.failed_rows.push(RowFailure { row_number, error });
+                            }
+                        }
+                    }
+                }
+            } else {
+                succeeded += count;
+                ctx.record_rows(count, count, bytes);
+            }
+            checkpoint.batch_number += 1;
+            checkpoint.rows_copied = total_rows;
+            ctx.save_table_checkpoint(QUERY_CHECKPOINT, &checkpoint).await?;
+            ctx.update_progress(
+                total_rows,
+                format!("Processed {} rows, {} failed", total_rows, checkpoint.rows_failed),
+            )
+            .await;
+        }
+
+        ctx.logger().info(format!(
+            "自定义SQL迁移完成，共 {} 行，成功 {} 行，失败 {} 行",
+            total_rows,
+            succeeded,
+            checkpoint.rows_failed
+        )).await;
+        if !checkpoint.failed_rows.is_empty() {
+            let rows: Vec<String> = checkpoint
+                .failed_rows
+                .iter()
+                .map(|failure| format!("第 {} 行: {}", failure.row_number, failure.error))
+                .collect();
+            ctx.logger().warning(format!("最先失败的 {} 行: {}", rows.len(), rows.join("; "))).await;
+        }
+
+        match checkpoint.failed_rows.first() {
+            Some(first) => Err(format!(
+                "Custom SQL migration finished with {} of {} rows failed; first failure at row {}: {}",
+                checkpoint.rows_failed,
+                total_rows,
+                first.row_number,
+                first.error
+            )),
+            None => Ok(()),
+        }
+    }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        let (source_conn, target_conn) = get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+        source_conn.test_connection().await?;
+        target_conn.test_connection().await?;
+
+        let template = SqlTemplate::parse(&self.target_sql_template, &target_conn.db_type())?;
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+        if let Some(mut sample) = sample_query_row(&source_conn, &self.source_sql, &mut plan).await {
+            self.shape_rows(&mut None, &mut sample)?;
+            template.validate(&sample)?;
+        }
+
+        // 模板对每一行执行一次，同一批次在一个事务中提交
+        let skip = ctx.table_checkpoint(QUERY_CHECKPOINT).await?.unwrap_or_default().rows_copied;
+        let rows = count_query_rows(&source_conn, &self.source_sql, &mut plan)
+            .await
+            .map(|rows| rows.saturating_sub(skip));
+        plan.add_rows(rows);
+        plan.add_statement(StatementKind::Write, None, template.sql.clone(), rows);
+        Ok(plan)
+    }
+}
+
+// 查询类策略在检查点存储中的名称，记录已处理的行数
+const QUERY_CHECKPOINT: &str = "@query";
+
+// 自定义SQL迁移在检查点和任务日志中保留的失败行数
+const MAX_REPORTED_FAILED_ROWS: usize = 20;
+
+// 把源查询包装为子查询，演练时用于统计行数和读取样本
+fn wrap_source_query(select: &str, source_sql: &str) -> String {
+    format!("SELECT {} FROM ({}) plan_source", select, source_sql.trim().trim_end_matches(';'))
+}
+
+// 演练时统计源查询的行数，查询无法作为子查询时记入提示并返回None
+async fn count_query_rows(conn: &SqlxDatabaseConnection, source_sql: &str, plan: &mut MigrationPlan) -> Option<u64> {
+    match conn.fetch_rows(&wrap_source_query("COUNT(*)", source_sql), vec![]).await {
+        Ok(result) => Some(
+            result
+                .rows
+                .first()
+                .and_then(|row| row.first())
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0) as u64,
+        ),
+        Err(e) => {
+            plan.warn(format!("无法统计源查询的行数: {}", e));
+            None
+        }
+    }
+}
+
+// 演练时读取源查询的第一行，用于校验模板，查询没有结果时返回None
+async fn sample_query_row(conn: &SqlxDatabaseConnection, source_sql: &str, plan: &mut MigrationPlan) -> Option<SqlRowSet> {
+    let sql = format!("{} LIMIT 1", wrap_source_query("*", source_sql));
+    match conn.fetch_rows(&sql, vec![]).await {
+        Ok(sample) if !sample.rows.is_empty() => Some(sample),
+        Ok(_) => {
+            plan.warn("源查询没有返回任何行，无法校验模板");
+            None
+        }
+        Err(e) => {
+            plan.warn(format!("无法读取源查询的样本行: {}", e));
+            None
+        }
+    }
+}
+
+// 跳过检查点之前已处理的行，整批跳过时返回true
+fn skip_rows(batch: &mut SqlRowSet, skip: &mut u64) -> bool {
+    if *skip == 0 {
+        return false;
+    }
+    let count = (*skip).min(batch.rows.len() as u64);
+    batch.rows.drain(..count as usize);
+    *skip -= count;
+    batch.rows.is_empty()
+}
+
+// Redis策略的SCAN游标在检查点存储中的名称
+fn scan_checkpoint_name(key_pattern: &str) -> String {
+    format!("@scan:{}", key_pattern)
+}
+
+// 读取上次中断时的SCAN游标，游标在源库的键被改写前持续有效
+async fn load_scan_checkpoint(ctx: &TaskContext, name: &str) -> Result<(TableCheckpoint, u64), String> {
+    let checkpoint = ctx.table_checkpoint(name).await?.unwrap_or_default();
+    let cursor = match checkpoint.last_key.first() {
+        Some(SqlValue::Text(cursor)) => cursor
+            .parse()
+            .map_err(|e| format!("Invalid SCAN cursor checkpoint {}: {}", cursor, e))?,
+        _ => 0,
+    };
+    if checkpoint.batch_number > 0 {
+        ctx.logger().info(format!("从SCAN游标 {} 继续，已处理 {} 个键", cursor, checkpoint.rows_copied)).await;
+    }
+    Ok((checkpoint, cursor))
+}
+
+// 每轮SCAN写入后保存下一个游标，u64游标以文本保存
+async fn save_scan_checkpoint(
+    ctx: &TaskContext,
+    name: &str,
+    checkpoint: &mut TableCheckpoint,
+    next_cursor: u64,
+    processed: u64,
+) -> Result<(), String> {
+    checkpoint.last_key = vec![SqlValue::Text(next_cursor.to_string())];
+    checkpoint.batch_number += 1;
+    checkpoint.rows_copied = processed;
+    checkpoint.completed = next_cursor == 0;
+    ctx.save_table_checkpoint(name, checkpoint).await
+}
+
+fn default_key_pattern() -> String {
+    "*".to_string()
+}
+
+// Redis到Redis迁移策略
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FullMigrationStrategy {
+pub struct RedisMigrationStrategy {
     name: String,
+    // SCAN匹配的键模式
+    #[serde(default = "default_key_pattern")]
+    key_pattern: String,
+    // 每次SCAN和管道处理的键数
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    // 是否覆盖目标库中已存在的键
+    #[serde(default)]
+    replace_existing: bool,
 }
 
-impl FullMigrationStrategy {
+impl RedisMigrationStrategy {
     pub fn new() -> Self {
         Self {
-            name: "Full Migration".to_string(),
+            name: "Redis Migration".to_string(),
+            key_pattern: default_key_pattern(),
+            batch_size: default_batch_size(),
+            replace_existing: false,
         }
     }
+
+    pub fn with_key_pattern(mut self, key_pattern: String) -> Self {
+        self.key_pattern = key_pattern;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_replace_existing(mut self, replace_existing: bool) -> Self {
+        self.replace_existing = replace_existing;
+        self
+    }
 }
 
 #[async_trait::async_trait]
-impl MigrationStrategy for FullMigrationStrategy {
+impl MigrationStrategy for RedisMigrationStrategy {
     fn get_name(&self) -> &str {
         &self.name
     }
-    
+
     async fn execute(
         &self,
         source_db_id: &str,
         target_db_id: &str,
         conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
     ) -> Result<(), String> {
-        // 这里实现全量迁移的逻辑
-        // 1. 从源数据库读取所有数据
-        // 2. 写入目标数据库
-        
-        // 示例实现，实际需要根据不同数据库类型进行处理
-        let source_conn = conn_manager.get_connection(source_db_id).await
-            .ok_or_else(|| format!("Source database connection not found: {}", source_db_id))?;
-        let target_conn = conn_manager.get_connection(target_db_id).await
-            .ok_or_else(|| format!("Target database connection not found: {}", target_db_id))?;
-        
-        // 测试连接
-        source_conn.test_connection().await?;
-        target_conn.test_connection().await?;
-        
-        // 实际迁移逻辑将在后续实现
+        let source = conn_manager.get_redis_connection(source_db_id).await
+            .ok_or_else(|| format!("Source Redis connection not found: {}", source_db_id))?;
+        let target = conn_manager.get_redis_connection(target_db_id).await
+            .ok_or_else(|| format!("Target Redis connection not found: {}", target_db_id))?;
+        let mut source_conn = source.get_connection().await?;
+        let mut target_conn = target.get_connection().await?;
+
+        ctx.logger().info(format!("Redis迁移开始，键模式: {}", self.key_pattern)).await;
+        let mut copier = RedisCopier::new(self.replace_existing);
+        let mut stats = RedisCopyStats::default();
+        let checkpoint_name = scan_checkpoint_name(&self.key_pattern);
+        let (mut checkpoint, mut cursor) = load_scan_checkpoint(ctx, &checkpoint_name).await?;
+        stats.copied = checkpoint.rows_copied;
+        loop {
+            let (next_cursor, keys) =
+                redis_copy::scan_keys(&mut source_conn, cursor, &self.key_pattern, self.batch_size).await?;
+            // 每批键的读取和写入分别通过一次管道完成
+            for chunk in keys.chunks(self.batch_size) {
+                ctx.wait_if_paused().await?;
+                let batch = copier.copy_batch(&mut source_conn, &mut target_conn, chunk.to_vec(), ctx.logger()).await?;
+                // 键按原始编码复制，不统计数据量
+                ctx.record_rows(chunk.len() as u64, batch.copied, 0);
+                stats.merge(&batch);
+            }
+            save_scan_checkpoint(ctx, &checkpoint_name, &mut checkpoint, next_cursor, stats.copied).await?;
+            ctx.update_progress(stats.copied, format!("Copied {} keys", stats.copied)).await;
+
+            if next_cursor == 0 {
+                break;
+            }
+            cursor = next_cursor;
+        }
+
+        ctx.logger().info(format!(
+            "Redis迁移完成，复制 {} 个键，跳过已存在的 {} 个键，{} 个键在复制前过期",
+            stats.copied, stats.skipped, stats.missing
+        )).await;
         Ok(())
     }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        let source = conn_manager.get_redis_connection(source_db_id).await
+            .ok_or_else(|| format!("Source Redis connection not found: {}", source_db_id))?;
+        let target = conn_manager.get_redis_connection(target_db_id).await
+            .ok_or_else(|| format!("Target Redis connection not found: {}", target_db_id))?;
+        let mut source_conn = source.get_connection().await?;
+        target.test_connection().await?;
+
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+        let keys = remaining_keys(&mut source_conn, &self.key_pattern, self.batch_size, ctx).await?;
+        plan.add_rows(Some(keys));
+        // 键按DUMP格式恢复，不兼容时按类型逐个写入
+        let sql = if self.replace_existing {
+            "RESTORE <key> <ttl> <payload> REPLACE"
+        } else {
+            "RESTORE <key> <ttl> <payload>"
+        };
+        plan.add_statement(StatementKind::Write, None, sql, plan::batches(Some(keys), self.batch_size));
+        if !self.replace_existing {
+            plan.warn("目标库中已存在的键会被跳过，预计键数包含这些键");
+        }
+        Ok(plan)
+    }
 }
 
-// 增量迁移策略
+// 演练时统计匹配模式且尚未处理的键数，从SCAN检查点继续时扣除已处理的键
+async fn remaining_keys(
+    conn: &mut redis::aio::MultiplexedConnection,
+    key_pattern: &str,
+    batch_size: usize,
+    ctx: &TaskContext,
+) -> Result<u64, String> {
+    let checkpoint = ctx.table_checkpoint(&scan_checkpoint_name(key_pattern)).await?.unwrap_or_default();
+    let keys = redis_copy::count_keys(conn, key_pattern, batch_size).await?;
+    Ok(keys.saturating_sub(checkpoint.rows_copied))
+}
+
+fn default_key_column() -> String {
+    "id".to_string()
+}
+
+fn default_sample_size() -> usize {
+    1000
+}
+
+// Redis到SQL迁移策略 - 把匹配模式的哈希或JSON对象展平为目标表的行
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IncrementalMigrationStrategy {
+pub struct RedisToSqlStrategy {
     name: String,
-    last_migration_id: Option<String>,
+    // SCAN匹配的键模式，通配符匹配的部分写入键列
+    #[serde(default = "default_key_pattern")]
+    key_pattern: String,
+    // 目标表，不存在时根据样本推断列类型后创建
+    table: String,
+    // 保存键后缀的列，新建表时作为主键
+    #[serde(default = "default_key_column")]
+    key_column: String,
+    // 显式的字段映射，为空时所有字段按同名列写入
+    #[serde(default)]
+    columns: Vec<RedisFieldMapping>,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    // 推断列类型时采样的键数
+    #[serde(default = "default_sample_size")]
+    sample_size: usize,
 }
 
-impl IncrementalMigrationStrategy {
-    pub fn new(last_migration_id: Option<String>) -> Self {
+impl RedisToSqlStrategy {
+    pub fn new(table: String) -> Self {
         Self {
-            name: "Incremental Migration".to_string(),
-            last_migration_id,
+            name: "Redis to SQL Migration".to_string(),
+            key_pattern: default_key_pattern(),
+            table,
+            key_column: default_key_column(),
+            columns: Vec::new(),
+            batch_size: default_batch_size(),
+            sample_size: default_sample_size(),
+        }
+    }
+
+    pub fn with_key_pattern(mut self, key_pattern: String) -> Self {
+        self.key_pattern = key_pattern;
+        self
+    }
+
+    pub fn with_key_column(mut self, key_column: String) -> Self {
+        self.key_column = key_column;
+        self
+    }
+
+    pub fn with_columns(mut self, columns: Vec<RedisFieldMapping>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.sample_size = sample_size.max(1);
+        self
+    }
+
+    // 目标表不存在时采样推断结构并创建
+    async fn prepare_target(
+        &self,
+        source_conn: &mut redis::aio::MultiplexedConnection,
+        target_conn: &SqlxDatabaseConnection,
+        logger: &TaskLogger,
+    ) -> Result<TableSchema, String> {
+        if let Some(existing) = target_conn.get_table_schema(&self.table).await? {
+            return Ok(existing);
+        }
+
+        let db_type = target_conn.db_type();
+        let (schema, warnings) = self.infer_target(source_conn, &db_type).await?;
+        for warning in warnings {
+            logger.warning(warning).await;
+        }
+        let ddl = DdlGenerator::new(db_type.clone(), db_type).generate_table(&schema, None);
+        let (created, _) = table_copy::ensure_target_table(&ddl, target_conn, logger).await?;
+        Ok(created)
+    }
+
+    // 采样源键推断目标表结构
+    async fn infer_target(
+        &self,
+        source_conn: &mut redis::aio::MultiplexedConnection,
+        db_type: &DatabaseType,
+    ) -> Result<(TableSchema, Vec<String>), String> {
+        let mut samples = Vec::new();
+        let mut cursor = 0u64;
+        loop {
+            let (next_cursor, keys) =
+                redis_copy::scan_keys(source_conn, cursor, &self.key_pattern, self.batch_size).await?;
+            samples.extend(redis_table::read_records(source_conn, &keys).await?.records);
+            if next_cursor == 0 || samples.len() >= self.sample_size {
+                break;
+            }
+            cursor = next_cursor;
         }
+        samples.truncate(self.sample_size);
+
+        redis_table::infer_table(
+            db_type,
+            &self.table,
+            &self.key_column,
+            &self.key_pattern,
+            &self.columns,
+            &samples,
+        )
     }
 }
 
 #[async_trait::async_trait]
-impl MigrationStrategy for IncrementalMigrationStrategy {
+impl MigrationStrategy for RedisToSqlStrategy {
     fn get_name(&self) -> &str {
         &self.name
     }
-    
+
     async fn execute(
         &self,
         source_db_id: &str,
         target_db_id: &str,
         conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
     ) -> Result<(), String> {
-        // 这里实现增量迁移的逻辑
-        // 1. 根据last_migration_id确定增量数据的范围
-        // 2. 读取增量数据
-        // 3. 写入目标数据库
-        
-        // 示例实现，实际需要根据不同数据库类型进行处理
-        let source_conn = conn_manager.get_connection(source_db_id).await
-            .ok_or_else(|| format!("Source database connection not found: {}", source_db_id))?;
-        let target_conn = conn_manager.get_connection(target_db_id).await
-            .ok_or_else(|| format!("Target database connection not found: {}", target_db_id))?;
-        
-        // 测试连接
-        source_conn.test_connection().await?;
+        let source = conn_manager.get_redis_connection(source_db_id).await
+            .ok_or_else(|| format!("Source Redis connection not found: {}", source_db_id))?;
+        let target_conn = conn_manager.get_sqlx_connection(target_db_id).await
+            .ok_or_else(|| format!("Target SQL database connection not found: {}", target_db_id))?;
+        let mut source_conn = source.get_connection().await?;
         target_conn.test_connection().await?;
-        
-        // 实际迁移逻辑将在后续实现
+
+        ctx.logger().info(format!("Redis到SQL迁移开始，键模式: {}，目标表: {}", self.key_pattern, self.table)).await;
+        let target = self.prepare_target(&mut source_conn, &target_conn, ctx.logger()).await?;
+        let plan = FlattenPlan::new(&target, &self.key_column, &self.key_pattern, &self.columns)?;
+        if plan.conflict_columns().is_empty() {
+            ctx.logger().warning(format!("目标表 {} 没有主键，重复执行会写入重复行", target.name)).await;
+        }
+
+        let mut unsupported = 0u64;
+        let mut missing = 0u64;
+        let mut ignored = BTreeSet::new();
+        let checkpoint_name = scan_checkpoint_name(&self.key_pattern);
+        let (mut checkpoint, mut cursor) = load_scan_checkpoint(ctx, &checkpoint_name).await?;
+        let mut written = checkpoint.rows_copied;
+        loop {
+            let (next_cursor, keys) =
+                redis_copy::scan_keys(&mut source_conn, cursor, &self.key_pattern, self.batch_size).await?;
+            for chunk in keys.chunks(self.batch_size) {
+                ctx.wait_if_paused().await?;
+                let batch = redis_table::read_records(&mut source_conn, chunk).await?;
+                unsupported += batch.unsupported;
+                missing += batch.missing;
+                let (columns, rows) = plan.rows(&batch.records, &mut ignored)?;
+                let bytes = table_copy::rows_bytes(&rows);
+                // 按提交的行数计数：MySQL的ON DUPLICATE KEY UPDATE对更新的行返回2，影响行数会多算
+                let count = rows.len() as u64;
+                table_copy::upsert_rows(&target_conn, &target.name, &columns, plan.conflict_columns(), rows).await?;
+                ctx.record_rows(chunk.len() as u64, count, bytes);
+                written += count;
+            }
+            save_scan_checkpoint(ctx, &checkpoint_name, &mut checkpoint, next_cursor, written).await?;
+            ctx.update_progress(written, format!("Wrote {} rows to {}", written, target.name)).await;
+
+            if next_cursor == 0 {
+                break;
+            }
+            cursor = next_cursor;
+        }
+
+        if !ignored.is_empty() {
+            ctx.logger().warning(format!(
+                "以下字段在目标表 {} 中没有对应列，未写入: {}",
+                target.name,
+                ignored.into_iter().collect::<Vec<_>>().join(", ")
+            )).await;
+        }
+        ctx.logger().info(format!(
+            "Redis到SQL迁移完成，写入 {} 行，跳过 {} 个无法展平的键，{} 个键在读取前过期",
+            written, unsupported, missing
+        )).await;
         Ok(())
     }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        let source = conn_manager.get_redis_connection(source_db_id).await
+            .ok_or_else(|| format!("Source Redis connection not found: {}", source_db_id))?;
+        let target_conn = conn_manager.get_sqlx_connection(target_db_id).await
+            .ok_or_else(|| format!("Target SQL database connection not found: {}", target_db_id))?;
+        let mut source_conn = source.get_connection().await?;
+        target_conn.test_connection().await?;
+
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+        let db_type = target_conn.db_type();
+        let (target, action) = match target_conn.get_table_schema(&self.table).await? {
+            Some(existing) => (existing, TableAction::Existing),
+            None => {
+                let (schema, warnings) = self.infer_target(&mut source_conn, &db_type).await?;
+                for warning in warnings {
+                    plan.warn(warning);
+                }
+                let ddl = DdlGenerator::new(db_type.clone(), db_type.clone()).generate_table(&schema, None);
+                plan.add_ddl_warnings(&ddl);
+                plan.add_statement(StatementKind::CreateTable, Some(&schema.name), ddl.create_table, Some(1));
+                (schema, TableAction::Create)
+            }
+        };
+        let flatten = FlattenPlan::new(&target, &self.key_column, &self.key_pattern, &self.columns)?;
+        if flatten.conflict_columns().is_empty() {
+            plan.warn(format!("目标表 {} 没有主键，重复执行会写入重复行", target.name));
+        }
+
+        // 每个键展开为一行，写入的列取决于键中实际存在的字段
+        let checkpoint = ctx.table_checkpoint(&scan_checkpoint_name(&self.key_pattern)).await?.unwrap_or_default();
+        let keys = remaining_keys(&mut source_conn, &self.key_pattern, self.batch_size, ctx).await?;
+        let columns: Vec<&ColumnSchema> = target.columns.iter().collect();
+        plan.add_statement(
+            StatementKind::Write,
+            Some(&target.name),
+            table_copy::write_template(&db_type, &target.name, &columns, flatten.conflict_columns()),
+            plan::batches(Some(keys), self.batch_size),
+        );
+        plan.add_table(TablePlan {
+            source: self.key_pattern.clone(),
+            target: target.name.clone(),
+            action,
+            columns: target.columns.iter().map(|c| c.name.clone()).collect(),
+            skipped_columns: Vec::new(),
+            dropped_columns: Vec::new(),
+            transformed_columns: Vec::new(),
+            filter: None,
+            estimated_rows: Some(keys),
+            resumed_rows: checkpoint.rows_copied,
+        });
+        Ok(plan)
+    }
 }
 
-// 自定义SQL迁移策略
+// SQL到Redis迁移策略 - 把查询结果的每一行按键模板写入Redis，用于预热缓存
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CustomSQLMigrationStrategy {
+pub struct SqlToRedisStrategy {
     name: String,
     source_sql: String,
-    target_sql_template: String,
+    // 键模板，如 order:{id}
+    key_template: String,
+    #[serde(default)]
+    value_format: RedisValueFormat,
+    // 有序集合的成员模板和分数列
+    #[serde(default)]
+    member_template: Option<String>,
+    #[serde(default)]
+    score_column: Option<String>,
+    // 过期表达式，如 3600 或 {expires_at}，渲染为秒数或绝对时间
+    #[serde(default)]
+    ttl: Option<String>,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
 }
 
-impl CustomSQLMigrationStrategy {
-    pub fn new(name: String, source_sql: String, target_sql_template: String) -> Self {
+impl SqlToRedisStrategy {
+    pub fn new(source_sql: String, key_template: String) -> Self {
         Self {
-            name,
+            name: "SQL to Redis Migration".to_string(),
             source_sql,
-            target_sql_template,
+            key_template,
+            value_format: RedisValueFormat::default(),
+            member_template: None,
+            score_column: None,
+            ttl: None,
+            batch_size: default_batch_size(),
         }
     }
+
+    pub fn with_value_format(mut self, value_format: RedisValueFormat) -> Self {
+        self.value_format = value_format;
+        self
+    }
+
+    // 写入有序集合时的成员模板和分数列
+    pub fn with_sorted_set(mut self, member_template: String, score_column: String) -> Self {
+        self.member_template = Some(member_template);
+        self.score_column = Some(score_column);
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: String) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
 }
 
 #[async_trait::async_trait]
-impl MigrationStrategy for CustomSQLMigrationStrategy {
+impl MigrationStrategy for SqlToRedisStrategy {
     fn get_name(&self) -> &str {
         &self.name
     }
-    
+
     async fn execute(
         &self,
         source_db_id: &str,
         target_db_id: &str,
         conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
     ) -> Result<(), String> {
-        // 这里实现自定义SQL迁移的逻辑
-        // 1. 执行自定义SQL查询源数据库
-        // 2. 使用查询结果填充目标SQL模板
-        // 3. 执行生成的SQL写入目标数据库
-        
-        // 示例实现，实际需要根据不同数据库类型进行处理
-        let source_conn = conn_manager.get_connection(source_db_id).await
-            .ok_or_else(|| format!("Source database connection not found: {}", source_db_id))?;
-        let target_conn = conn_manager.get_connection(target_db_id).await
-            .ok_or_else(|| format!("Target database connection not found: {}", target_db_id))?;
-        
-        // 测试连接
+        let source_conn = conn_manager.get_sqlx_connection(source_db_id).await
+            .ok_or_else(|| format!("Source SQL database connection not found: {}", source_db_id))?;
+        let target = conn_manager.get_redis_connection(target_db_id).await
+            .ok_or_else(|| format!("Target Redis connection not found: {}", target_db_id))?;
         source_conn.test_connection().await?;
-        target_conn.test_connection().await?;
-        
-        // 实际迁移逻辑将在后续实现
+        let mut target_conn = target.get_connection().await?;
+
+        let plan = ProjectionPlan::new(
+            &self.key_template,
+            self.value_format,
+            self.member_template.as_deref(),
+            self.score_column.as_deref(),
+            self.ttl.as_deref(),
+        )?;
+        ctx.logger().info(format!("SQL到Redis迁移开始，键模板: {}，格式: {:?}", self.key_template, self.value_format)).await;
+
+        // 任务中断后跳过已写入的行，要求源查询的结果顺序稳定
+        let mut checkpoint = ctx.table_checkpoint(QUERY_CHECKPOINT).await?.unwrap_or_default();
+        let mut skip = checkpoint.rows_copied;
+        if skip > 0 {
+            ctx.logger().info(format!("从检查点继续，跳过已处理的 {} 行", skip)).await;
+        }
+
+        let mut batches = source_conn.stream_rows(self.source_sql.clone(), vec![], self.batch_size);
+        let mut validated = false;
+        let mut total_rows = skip;
+        let mut written = skip;
+        let mut expired = 0u64;
+        while let Some(batch) = batches.recv().await {
+            let mut batch = batch?;
+            ctx.wait_if_paused().await?;
+            if !validated {
+                plan.validate(&batch)?;
+                validated = true;
+            }
+            if skip_rows(&mut batch, &mut skip) {
+                continue;
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            let mut writes = Vec::with_capacity(batch.rows.len());
+            for row in &batch.rows {
+                total_rows += 1;
+                match plan.write(&batch, row, now).map_err(|e| format!("Row {}: {}", total_rows, e))? {
+                    Some(write) => writes.push(write),
+                    None => expired += 1,
+                }
+            }
+            redis_manager::write_pipeline(&mut target_conn, &writes).await?;
+            let bytes = writes.iter().map(|write| write.byte_size()).sum();
+            ctx.record_rows(batch.rows.len() as u64, writes.len() as u64, bytes);
+            written += writes.len() as u64;
+            checkpoint.batch_number += 1;
+            checkpoint.rows_copied = total_rows;
+            ctx.save_table_checkpoint(QUERY_CHECKPOINT, &checkpoint).await?;
+            ctx.update_progress(written, format!("Wrote {} of {} rows to Redis", written, total_rows)).await;
+        }
+
+        ctx.logger().info(format!(
+            "SQL到Redis迁移完成，共 {} 行，写入 {} 行，跳过 {} 行已过期的数据",
+            total_rows, written, expired
+        )).await;
         Ok(())
     }
+
+    async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        let source_conn = conn_manager.get_sqlx_connection(source_db_id).await
+            .ok_or_else(|| format!("Source SQL database connection not found: {}", source_db_id))?;
+        let target = conn_manager.get_redis_connection(target_db_id).await
+            .ok_or_else(|| format!("Target Redis connection not found: {}", target_db_id))?;
+        source_conn.test_connection().await?;
+        target.test_connection().await?;
+
+        let projection = ProjectionPlan::new(
+            &self.key_template,
+            self.value_format,
+            self.member_template.as_deref(),
+            self.score_column.as_deref(),
+            self.ttl.as_deref(),
+        )?;
+        let mut plan = MigrationPlan::new(ctx.task_id(), &self.name, source_db_id, target_db_id);
+
+        // 以第一行展示将要执行的命令
+        let mut command = format!("{:?} {}", self.value_format, self.key_template);
+        if let Some(sample) = sample_query_row(&source_conn, &self.source_sql, &mut plan).await {
+            projection.validate(&sample)?;
+            match projection.write(&sample, &sample.rows[0], chrono::Utc::now().timestamp()) {
+                Ok(Some(write)) => command = write.describe(),
+                Ok(None) => {}
+                Err(e) => plan.warn(format!("第一行无法写入Redis: {}", e)),
+            }
+        }
+
+        let skip = ctx.table_checkpoint(QUERY_CHECKPOINT).await?.unwrap_or_default().rows_copied;
+        let rows = count_query_rows(&source_conn, &self.source_sql, &mut plan)
+            .await
+            .map(|rows| rows.saturating_sub(skip));
+        plan.add_rows(rows);
+        plan.add_statement(StatementKind::Write, None, command, plan::batches(rows, self.batch_size));
+        Ok(plan)
+    }
 }
 
 // 迁移策略枚举 - 用于替代dyn trait
@@ -169,6 +2011,11 @@ pub enum MigrationStrategyEnum {
     Full(FullMigrationStrategy),
     Incremental(IncrementalMigrationStrategy),
     CustomSQL(CustomSQLMigrationStrategy),
+    MySqlBinlogCdc(MySqlBinlogCdcStrategy),
+    PostgresLogicalCdc(PostgresLogicalCdcStrategy),
+    Redis(RedisMigrationStrategy),
+    RedisToSql(RedisToSqlStrategy),
+    SqlToRedis(SqlToRedisStrategy),
 }
 
 impl MigrationStrategyEnum {
@@ -178,6 +2025,11 @@ impl MigrationStrategyEnum {
             MigrationStrategyEnum::Full(strategy) => strategy.get_name(),
             MigrationStrategyEnum::Incremental(strategy) => strategy.get_name(),
             MigrationStrategyEnum::CustomSQL(strategy) => strategy.get_name(),
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => strategy.get_name(),
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => strategy.get_name(),
+            MigrationStrategyEnum::Redis(strategy) => strategy.get_name(),
+            MigrationStrategyEnum::RedisToSql(strategy) => strategy.get_name(),
+            MigrationStrategyEnum::SqlToRedis(strategy) => strategy.get_name(),
         }
     }
     
@@ -187,16 +2039,159 @@ impl MigrationStrategyEnum {
         source_db_id: &str,
         target_db_id: &str,
         conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<(), String> {
+        match self {
+            MigrationStrategyEnum::Full(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::Incremental(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::CustomSQL(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::Redis(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::RedisToSql(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::SqlToRedis(strategy) => {
+                strategy.execute(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+        }
+    }
+    
+    // 迁移完成后需要校验的源表，不支持校验的策略返回None
+    pub fn verification_tables(&self) -> Option<Vec<String>> {
+        match self {
+            MigrationStrategyEnum::Full(strategy) => strategy.verification_tables(),
+            MigrationStrategyEnum::Incremental(strategy) => strategy.verification_tables(),
+            MigrationStrategyEnum::CustomSQL(strategy) => strategy.verification_tables(),
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => strategy.verification_tables(),
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => strategy.verification_tables(),
+            MigrationStrategyEnum::Redis(strategy) => strategy.verification_tables(),
+            MigrationStrategyEnum::RedisToSql(strategy) => strategy.verification_tables(),
+            MigrationStrategyEnum::SqlToRedis(strategy) => strategy.verification_tables(),
+        }
+    }
+    
+    // 表映射规则，没有表映射的策略返回空
+    pub fn mappings(&self) -> &[TableMapping] {
+        match self {
+            MigrationStrategyEnum::Full(strategy) => strategy.mappings(),
+            MigrationStrategyEnum::Incremental(strategy) => strategy.mappings(),
+            MigrationStrategyEnum::CustomSQL(strategy) => strategy.mappings(),
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => strategy.mappings(),
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => strategy.mappings(),
+            MigrationStrategyEnum::Redis(strategy) => strategy.mappings(),
+            MigrationStrategyEnum::RedisToSql(strategy) => strategy.mappings(),
+            MigrationStrategyEnum::SqlToRedis(strategy) => strategy.mappings(),
+        }
+    }
+    
+    // 行转换器，不支持转换的策略返回空
+    pub fn transformers(&self) -> &[Transformer] {
+        match self {
+            MigrationStrategyEnum::Full(strategy) => strategy.transformers(),
+            MigrationStrategyEnum::Incremental(strategy) => strategy.transformers(),
+            MigrationStrategyEnum::CustomSQL(strategy) => strategy.transformers(),
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => strategy.transformers(),
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => strategy.transformers(),
+            MigrationStrategyEnum::Redis(strategy) => strategy.transformers(),
+            MigrationStrategyEnum::RedisToSql(strategy) => strategy.transformers(),
+            MigrationStrategyEnum::SqlToRedis(strategy) => strategy.transformers(),
+        }
+    }
+    
+    // 按源表的过滤条件，不支持过滤的策略返回空
+    pub fn filters(&self) -> &BTreeMap<String, String> {
+        match self {
+            MigrationStrategyEnum::Full(strategy) => strategy.filters(),
+            MigrationStrategyEnum::Incremental(strategy) => strategy.filters(),
+            MigrationStrategyEnum::CustomSQL(strategy) => strategy.filters(),
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => strategy.filters(),
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => strategy.filters(),
+            MigrationStrategyEnum::Redis(strategy) => strategy.filters(),
+            MigrationStrategyEnum::RedisToSql(strategy) => strategy.filters(),
+            MigrationStrategyEnum::SqlToRedis(strategy) => strategy.filters(),
+        }
+    }
+    
+    // 创建任务时按两端的表结构校验过滤条件、映射规则和转换器
+    pub async fn validate(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
     ) -> Result<(), String> {
         match self {
             MigrationStrategyEnum::Full(strategy) => {
-                strategy.execute(source_db_id, target_db_id, conn_manager).await
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+            MigrationStrategyEnum::Incremental(strategy) => {
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+            MigrationStrategyEnum::CustomSQL(strategy) => {
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => {
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => {
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+            MigrationStrategyEnum::Redis(strategy) => {
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+            MigrationStrategyEnum::RedisToSql(strategy) => {
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+            MigrationStrategyEnum::SqlToRedis(strategy) => {
+                strategy.validate(source_db_id, target_db_id, conn_manager).await
+            },
+        }
+    }
+    
+    // 演练迁移，返回执行计划，不写入目标库
+    pub async fn plan(
+        &self,
+        source_db_id: &str,
+        target_db_id: &str,
+        conn_manager: Arc<UnifiedConnectionManager>,
+        ctx: &TaskContext,
+    ) -> Result<MigrationPlan, String> {
+        match self {
+            MigrationStrategyEnum::Full(strategy) => {
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
             },
             MigrationStrategyEnum::Incremental(strategy) => {
-                strategy.execute(source_db_id, target_db_id, conn_manager).await
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
             },
             MigrationStrategyEnum::CustomSQL(strategy) => {
-                strategy.execute(source_db_id, target_db_id, conn_manager).await
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::MySqlBinlogCdc(strategy) => {
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::PostgresLogicalCdc(strategy) => {
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::Redis(strategy) => {
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::RedisToSql(strategy) => {
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
+            },
+            MigrationStrategyEnum::SqlToRedis(strategy) => {
+                strategy.plan(source_db_id, target_db_id, conn_manager, ctx).await
             },
         }
     }
@@ -0,0 +1,722 @@
+// 表数据复制模块 - 在不同SQL数据库之间按批次读写行
+
+use std::collections::BTreeMap;
+use crate::database::{ColumnKind, ColumnSchema, DatabaseType, SqlValue, SqlxDatabaseConnection, TableDdl, TableSchema};
+use crate::migration::checkpoint::TableCheckpoint;
+use crate::migration::filter;
+use crate::migration::mapping::{ColumnRules, TableMapping};
+use crate::migration::task::TaskContext;
+use crate::migration::task_log::TaskLogger;
+use crate::migration::transform::{MaskedColumn, RowTransform, Transformer};
+
+// 默认批次大小
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+// 源端读取列的表达式，把各方言的值统一成Any驱动可以解码的类型
+pub fn select_expression(db_type: &DatabaseType, column: &ColumnSchema) -> String {
+    let name = db_type.quote_identifier(&column.name);
+    let expr = match db_type {
+        DatabaseType::MySql => match column.kind {
+            ColumnKind::Bool | ColumnKind::Integer => format!("CAST({} AS SIGNED)", name),
+            ColumnKind::Float => name.clone(),
+            ColumnKind::Bytes => format!("HEX({})", name),
+            _ => format!("CAST({} AS CHAR)", name),
+        },
+        DatabaseType::PostgreSql => match column.kind {
+            ColumnKind::Bool => format!("CAST({} AS INTEGER)", name),
+            ColumnKind::Integer => format!("CAST({} AS BIGINT)", name),
+            ColumnKind::Float => format!("CAST({} AS DOUBLE PRECISION)", name),
+            ColumnKind::Bytes => name.clone(),
+            _ => format!("CAST({} AS TEXT)", name),
+        },
+        _ => name.clone(),
+    };
+    if expr == name {
+        expr
+    } else {
+        format!("{} AS {}", expr, name)
+    }
+}
+
+// 构建读取指定列的SELECT语句（不含WHERE/ORDER/LIMIT）
+pub fn select_columns_sql(db_type: &DatabaseType, table: &str, columns: &[&ColumnSchema]) -> String {
+    let exprs: Vec<String> = columns.iter().map(|c| select_expression(db_type, c)).collect();
+    format!("SELECT {} FROM {}", exprs.join(", "), db_type.quote_identifier(table))
+}
+
+// 目标端参数占位符，PostgreSQL需要显式转换成列类型
+pub fn insert_placeholder(db_type: &DatabaseType, column: &ColumnSchema, index: usize) -> String {
+    match db_type {
+        DatabaseType::PostgreSql => format!("CAST({} AS {})", db_type.placeholder(index), column.data_type),
+        _ => db_type.placeholder(index),
+    }
+}
+
+// 把源端读出的值还原成列的中立类型
+pub fn decode_value(db_type: &DatabaseType, column: &ColumnSchema, value: SqlValue) -> SqlValue {
+    match (db_type, column.kind, value) {
+        // MySQL二进制列按十六进制读出
+        (DatabaseType::MySql, ColumnKind::Bytes, SqlValue::Text(hex)) => match decode_hex(&hex) {
+            Some(bytes) => SqlValue::Bytes(bytes),
+            None => SqlValue::Text(hex),
+        },
+        (_, _, value) => value,
+    }
+}
+
+// 按列顺序构造“位于给定位置之后”的条件，如 (a > ?) OR (a = ? AND b > ?)
+pub fn keyset_predicate(
+    db_type: &DatabaseType,
+    columns: &[&ColumnSchema],
+    values: &[SqlValue],
+    first_param: usize,
+) -> (String, Vec<SqlValue>) {
+    let mut params = Vec::new();
+    let mut terms = Vec::with_capacity(columns.len());
+    for i in 0..columns.len().min(values.len()) {
+        let mut parts = Vec::with_capacity(i + 1);
+        for j in 0..=i {
+            let op = if j == i { ">" } else { "=" };
+            params.push(coerce_value(values[j].clone(), columns[j].kind));
+            parts.push(format!(
+                "{} {} {}",
+                db_type.quote_identifier(&columns[j].name),
+                op,
+                insert_placeholder(db_type, columns[j], first_param + params.len() - 1)
+            ));
+        }
+        terms.push(format!("({})", parts.join(" AND ")));
+    }
+    (terms.join(" OR "), params)
+}
+
+// 把值转换为目标列可以接受的类型
+pub fn coerce_value(value: SqlValue, kind: ColumnKind) -> SqlValue {
+    if value.is_null() {
+        return SqlValue::Null;
+    }
+    match kind {
+        ColumnKind::Bool => match value.as_bool() {
+            Some(b) => SqlValue::Bool(b),
+            None => value,
+        },
+        ColumnKind::Integer => match value {
+            SqlValue::Int(_) => value,
+            SqlValue::Bool(b) => SqlValue::Int(b as i64),
+            SqlValue::Float(f) if f.fract() == 0.0 => SqlValue::Int(f as i64),
+            _ => value.as_i64().map(SqlValue::Int).unwrap_or(value),
+        },
+        ColumnKind::Float => match value {
+            SqlValue::Float(_) => value,
+            SqlValue::Int(i) => SqlValue::Float(i as f64),
+            _ => value
+                .as_string()
+                .and_then(|s| s.trim().parse::<f64>().ok())
+                .map(SqlValue::Float)
+                .unwrap_or(value),
+        },
+        ColumnKind::Bytes => match value {
+            SqlValue::Bytes(_) => value,
+            other => SqlValue::Bytes(other.as_string().unwrap_or_default().into_bytes()),
+        },
+        _ => match value {
+            SqlValue::Text(_) => value,
+            other => other.as_string().map(SqlValue::Text).unwrap_or(SqlValue::Null),
+        },
+    }
+}
+
+// 十六进制字符串解码
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// 确保目标表存在，返回目标表结构以及是否为本次新建
+pub async fn ensure_target_table(
+    ddl: &TableDdl,
+    target_conn: &SqlxDatabaseConnection,
+    logger: &TaskLogger,
+) -> Result<(TableSchema, bool), String> {
+    if let Some(existing) = target_conn.get_table_schema(&ddl.table).await? {
+        return Ok((existing, false));
+    }
+
+    logger.info(format!("在目标库创建表 {}: {}", ddl.table, ddl.create_table)).await;
+    target_conn.execute_raw(&ddl.create_table).await?;
+
+    let created = target_conn
+        .get_table_schema(&ddl.table)
+        .await?
+        .ok_or_else(|| format!("Table {} was not created on target", ddl.table))?;
+    Ok((created, true))
+}
+
+// 数据导入完成后为新建的表补充序列、索引和外键
+pub async fn finalize_target_table(
+    ddl: &TableDdl,
+    target_conn: &SqlxDatabaseConnection,
+) -> Result<(), String> {
+    for sql in ddl.post_load.iter().chain(ddl.indexes.iter()) {
+        target_conn.execute_raw(sql).await?;
+    }
+    Ok(())
+}
+
+// 为新建的表添加外键约束，需在所有表数据导入完成后执行
+pub async fn add_foreign_keys(
+    ddl: &TableDdl,
+    target_conn: &SqlxDatabaseConnection,
+) -> Result<(), String> {
+    for sql in &ddl.foreign_keys {
+        target_conn.execute_raw(sql).await?;
+    }
+    Ok(())
+}
+
+// 统计表的行数
+pub async fn count_rows(conn: &SqlxDatabaseConnection, table: &str, filter: Option<&str>) -> Result<u64, String> {
+    let sql = format!("SELECT COUNT(*) FROM {}{}", conn.db_type().quote_identifier(table), filter::where_clause(filter, ""));
+    let result = conn.fetch_rows(&sql, vec![]).await?;
+    Ok(result
+        .rows
+        .first()
+        .and_then(|row| row.first())
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0) as u64)
+}
+
+// 一批行的数据量（字节），用于执行统计
+pub fn rows_bytes(rows: &[Vec<SqlValue>]) -> u64 {
+    rows.iter().flat_map(|row| row.iter()).map(|v| v.byte_size()).sum()
+}
+
+// 把一批行写入目标表，按参数上限拆分成多条INSERT并在一个事务中执行
+pub async fn insert_rows(
+    target_conn: &SqlxDatabaseConnection,
+    table: &str,
+    columns: &[&ColumnSchema],
+    rows: Vec<Vec<SqlValue>>,
+) -> Result<u64, String> {
+    write_rows(target_conn, table, columns, &[], rows).await
+}
+
+// 按键列写入一批行，已存在的行更新为新值
+pub async fn upsert_rows(
+    target_conn: &SqlxDatabaseConnection,
+    table: &str,
+    columns: &[&ColumnSchema],
+    key_columns: &[String],
+    rows: Vec<Vec<SqlValue>>,
+) -> Result<u64, String> {
+    write_rows(target_conn, table, columns, key_columns, rows).await
+}
+
+// 冲突时的更新子句，键列为空时不处理冲突
+fn upsert_clause(db_type: &DatabaseType, columns: &[&ColumnSchema], key_columns: &[String]) -> String {
+    if key_columns.is_empty() {
+        return String::new();
+    }
+    let is_key = |c: &&&ColumnSchema| key_columns.iter().any(|k| k.eq_ignore_ascii_case(&c.name));
+    let updates: Vec<String> = columns
+        .iter()
+        .filter(|c| !is_key(c))
+        .map(|c| {
+            let name = db_type.quote_identifier(&c.name);
+            match db_type {
+                DatabaseType::MySql => format!("{} = VALUES({})", name, name),
+                _ => format!("{} = excluded.{}", name, name),
+            }
+        })
+        .collect();
+
+    match db_type {
+        DatabaseType::MySql => {
+            if updates.is_empty() {
+                // 只有键列时用无副作用的赋值忽略重复行
+                let key = db_type.quote_identifier(&key_columns[0]);
+                format!(" ON DUPLICATE KEY UPDATE {} = {}", key, key)
+            } else {
+                format!(" ON DUPLICATE KEY UPDATE {}", updates.join(", "))
+            }
+        }
+        _ => {
+            let keys: Vec<String> = key_columns.iter().map(|k| db_type.quote_identifier(k)).collect();
+            if updates.is_empty() {
+                format!(" ON CONFLICT ({}) DO NOTHING", keys.join(", "))
+            } else {
+                format!(" ON CONFLICT ({}) DO UPDATE SET {}", keys.join(", "), updates.join(", "))
+            }
+        }
+    }
+}
+
+async fn write_rows(
+    target_conn: &SqlxDatabaseConnection,
+    table: &str,
+    columns: &[&ColumnSchema],
+    key_columns: &[String],
+    rows: Vec<Vec<SqlValue>>,
+) -> Result<u64, String> {
+    let statements = insert_statements(&target_conn.db_type(), table, columns, key_columns, &rows);
+    if statements.is_empty() {
+        return Ok(0);
+    }
+    target_conn.execute_batch(statements).await
+}
+
+// 构建多行INSERT语句，键列非空时冲突的行改为更新
+pub fn insert_statements(
+    db_type: &DatabaseType,
+    table: &str,
+    columns: &[&ColumnSchema],
+    key_columns: &[String],
+    rows: &[Vec<SqlValue>],
+) -> Vec<(String, Vec<SqlValue>)> {
+    if rows.is_empty() || columns.is_empty() {
+        return Vec::new();
+    }
+
+    let rows_per_statement = (db_type.max_bind_params() / columns.len()).max(1);
+    let column_list: Vec<String> = columns.iter().map(|c| db_type.quote_identifier(&c.name)).collect();
+    let on_conflict = upsert_clause(db_type, columns, key_columns);
+
+    let mut statements = Vec::new();
+    for chunk in rows.chunks(rows_per_statement) {
+        let mut params = Vec::with_capacity(chunk.len() * columns.len());
+        let mut tuples = Vec::with_capacity(chunk.len());
+        for row in chunk {
+            let mut placeholders = Vec::with_capacity(columns.len());
+            for (column, value) in columns.iter().zip(row.iter()) {
+                // NULL直接写入SQL：Any驱动按整数类型绑定NULL，PostgreSQL中无法转换为bytea等类型，
+                // 且同一条SQL以NULL预编译后再绑定其他类型的值会被拒绝
+                let value = coerce_value(value.clone(), column.kind);
+                if value.is_null() {
+                    placeholders.push("NULL".to_string());
+                    continue;
+                }
+                params.push(value);
+                placeholders.push(insert_placeholder(db_type, column, params.len()));
+            }
+            tuples.push(format!("({})", placeholders.join(", ")));
+        }
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES {}{}",
+            db_type.quote_identifier(table),
+            column_list.join(", "),
+            tuples.join(", "),
+            on_conflict
+        );
+        statements.push((sql, params));
+    }
+    statements
+}
+
+// 单行形式的写入语句，用于演练计划展示，实际写入时按批次合并为多行INSERT
+pub fn write_template(
+    db_type: &DatabaseType,
+    table: &str,
+    columns: &[&ColumnSchema],
+    key_columns: &[String],
+) -> String {
+    let column_list: Vec<String> = columns.iter().map(|c| db_type.quote_identifier(&c.name)).collect();
+    let placeholders: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| insert_placeholder(db_type, c, i + 1))
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({}){}",
+        db_type.quote_identifier(table),
+        column_list.join(", "),
+        placeholders.join(", "),
+        upsert_clause(db_type, columns, key_columns)
+    )
+}
+
+// 任务对源表各行的处理：表和列的映射规则、转换器和按源表配置的过滤条件
+#[derive(Clone, Copy)]
+pub struct RowRules<'a> {
+    pub mappings: &'a [TableMapping],
+    pub transformers: &'a [Transformer],
+    pub filters: &'a BTreeMap<String, String>,
+}
+
+impl<'a> RowRules<'a> {
+    pub fn new(mappings: &'a [TableMapping], transformers: &'a [Transformer], filters: &'a BTreeMap<String, String>) -> Self {
+        Self { mappings, transformers, filters }
+    }
+
+    // 源表配置的过滤条件
+    pub fn filter(&self, table: &str) -> Option<&'a str> {
+        filter::filter_for(self.filters, table)
+    }
+}
+
+// 源表的一行到目标表的一行：执行转换链后按列映射规则挑出写入的列，并在最后追加常量列
+pub struct RowMapper<'a> {
+    select_columns: Vec<&'a ColumnSchema>,
+    target_columns: Vec<&'a ColumnSchema>,
+    transform: RowTransform,
+    // 写入的每一列在转换后的行中的位置
+    outputs: Vec<usize>,
+    constants: Vec<SqlValue>,
+    // 参与转换的读取列数，之后是额外读取的键列
+    read: usize,
+    // 读取源表时的过滤条件
+    filter: Option<&'a str>,
+}
+
+impl<'a> RowMapper<'a> {
+    pub fn new(
+        source_table: &'a TableSchema,
+        target_table: &'a TableSchema,
+        rules: &ColumnRules,
+        transformers: &[Transformer],
+    ) -> Result<Self, String> {
+        let names: Vec<String> = source_table.columns.iter().map(|c| c.name.clone()).collect();
+        let mut transform = RowTransform::new(transformers, Some(&source_table.name), &names)?;
+        // 没有转换器时只读取要写入的列，否则读取全部列供转换器使用
+        let select_columns: Vec<&ColumnSchema> = if transform.is_empty() {
+            let selected: Vec<&ColumnSchema> = rules.column_pairs(source_table, target_table).into_iter().map(|(s, _)| s).collect();
+            let names: Vec<String> = selected.iter().map(|c| c.name.clone()).collect();
+            transform = RowTransform::new(&[], Some(&source_table.name), &names)?;
+            selected
+        } else {
+            source_table.columns.iter().collect()
+        };
+
+        let mut target_columns: Vec<&ColumnSchema> = Vec::new();
+        let mut outputs = Vec::new();
+        for (index, name) in transform.columns().iter().enumerate() {
+            if let Some(column) = rules.target_column(name).and_then(|name| target_table.column(name)) {
+                if !target_columns.iter().any(|c| c.name == column.name) {
+                    target_columns.push(column);
+                    outputs.push(index);
+                }
+            }
+        }
+        if target_columns.is_empty() {
+            return Err(format!("Table {} has no columns in common with target", source_table.name));
+        }
+        let mut constants = Vec::new();
+        for (column, value) in rules.constant_columns(target_table) {
+            if !target_columns.iter().any(|c| c.name == column.name) {
+                target_columns.push(column);
+                constants.push(value);
+            }
+        }
+
+        Ok(Self {
+            read: select_columns.len(),
+            select_columns,
+            target_columns,
+            transform,
+            outputs,
+            constants,
+            filter: None,
+        })
+    }
+
+    // 设置读取源表时的过滤条件
+    pub fn with_filter(mut self, filter: Option<&'a str>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    // 读取源表时的过滤条件
+    pub fn filter(&self) -> Option<&'a str> {
+        self.filter
+    }
+
+    // 从源表读取的列
+    pub fn select_columns(&self) -> &[&'a ColumnSchema] {
+        &self.select_columns
+    }
+
+    // 写入目标表的列，常量列排在最后
+    pub fn target_columns(&self) -> &[&'a ColumnSchema] {
+        &self.target_columns
+    }
+
+    // 由mask转换器脱敏的源列
+    pub fn masked_columns(&self, table: &str) -> Vec<MaskedColumn> {
+        self.transform.masked_columns(table)
+    }
+
+    // 由转换器改写或新增的目标列
+    pub fn transformed_columns(&self) -> Vec<String> {
+        self.outputs
+            .iter()
+            .zip(self.target_columns.iter())
+            .filter(|(&index, _)| self.transform.is_modified(index))
+            .map(|(_, column)| column.name.clone())
+            .collect()
+    }
+
+    // 读取列中的位置，不在读取列中的列（如分页用的主键）追加到末尾
+    pub fn select_index(&mut self, column: &'a ColumnSchema) -> usize {
+        match self.select_columns.iter().position(|c| c.name == column.name) {
+            Some(index) => index,
+            None => {
+                self.select_columns.push(column);
+                self.select_columns.len() - 1
+            }
+        }
+    }
+
+    // 把按select_columns读取的一行转换为按target_columns写入的一行
+    pub fn map_row(&self, mut row: Vec<SqlValue>) -> Result<Vec<SqlValue>, String> {
+        row.truncate(self.read);
+        let mut row = self.transform.apply(row)?;
+        Ok(self
+            .outputs
+            .iter()
+            .map(|&index| std::mem::replace(&mut row[index], SqlValue::Null))
+            .chain(self.constants.iter().cloned())
+            .collect())
+    }
+}
+
+// 把源表的全部数据（有过滤条件时为满足条件的行）按行映射分批复制到目标表，返回复制的行数
+pub async fn copy_table(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    source_table: &TableSchema,
+    target_table: &TableSchema,
+    mapper: RowMapper<'_>,
+    batch_size: usize,
+    ctx: &TaskContext,
+) -> Result<u64, String> {
+    copy_rows(source_conn, target_conn, source_table, target_table, mapper, batch_size, ctx).await
+}
+
+// 把源表的全部数据按目标表主键写入，目标表中已有的行更新为源表的值
+pub async fn sync_table(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    source_table: &TableSchema,
+    target_table: &TableSchema,
+    mapper: RowMapper<'_>,
+    batch_size: usize,
+    ctx: &TaskContext,
+) -> Result<u64, String> {
+    if target_table.primary_key.is_empty() {
+        return Err(format!("Target table {} has no primary key to sync on", target_table.name));
+    }
+    copy_rows(source_conn, target_conn, source_table, target_table, mapper, batch_size, ctx).await
+}
+
+// 每批写入后保存检查点，任务中断后从最后一批之后继续，返回累计复制的行数
+async fn copy_rows(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    source_table: &TableSchema,
+    target_table: &TableSchema,
+    mut mapper: RowMapper<'_>,
+    batch_size: usize,
+    ctx: &TaskContext,
+) -> Result<u64, String> {
+    let source_type = source_conn.db_type();
+    let filter = mapper.filter();
+
+    // 有主键时按主键做键集分页；否则按全部可排序列排序后按偏移量分页
+    let key_columns = source_table.primary_key_columns();
+    let keyset = !key_columns.is_empty();
+    let order_columns: Vec<&ColumnSchema> = if keyset {
+        key_columns
+    } else {
+        mapper.select_columns().iter().filter(|c| c.kind != ColumnKind::Json).copied().collect()
+    };
+
+    // 主键不在读取列中时额外读取
+    let key_indexes: Vec<usize> = if keyset {
+        order_columns.iter().map(|column| mapper.select_index(column)).collect()
+    } else {
+        Vec::new()
+    };
+    let select_columns = mapper.select_columns().to_vec();
+    let target_columns = mapper.target_columns().to_vec();
+
+    // 目标表按主键写入，重复写入中断前的批次不会产生重复行
+    let upsert_keys: Vec<String> = target_table
+        .primary_key_columns()
+        .iter()
+        .filter(|k| target_columns.iter().any(|c| c.name == k.name))
+        .map(|k| k.name.clone())
+        .collect();
+
+    // 排序键变化后旧检查点失效，从头开始
+    let key_names: Vec<String> = if keyset {
+        order_columns.iter().map(|c| c.name.clone()).collect()
+    } else {
+        Vec::new()
+    };
+    let mut checkpoint = match ctx.table_checkpoint(&source_table.name).await? {
+        Some(checkpoint) if checkpoint.batch_number == 0 || checkpoint.key_columns == key_names => checkpoint,
+        Some(checkpoint) => {
+            ctx.logger().warning(format!("表 {} 的主键已变化，从头开始复制", source_table.name)).await;
+            TableCheckpoint {
+                created_target: checkpoint.created_target,
+                ..Default::default()
+            }
+        }
+        None => TableCheckpoint::default(),
+    };
+    checkpoint.key_columns = key_names;
+    if checkpoint.batch_number > 0 {
+        ctx.logger().info(format!(
+            "表 {} 从第 {} 批之后继续复制，已复制 {} 行",
+            source_table.name, checkpoint.batch_number, checkpoint.rows_copied
+        )).await;
+    }
+
+    let base_sql = select_columns_sql(&source_type, &source_table.name, &select_columns);
+    let order_by: Vec<String> = order_columns
+        .iter()
+        .map(|c| source_type.quote_identifier(&c.name))
+        .collect();
+    let order_by = if order_by.is_empty() {
+        String::new()
+    } else {
+        format!(" ORDER BY {}", order_by.join(", "))
+    };
+
+    let batch_size = batch_size.max(1);
+    loop {
+        ctx.wait_if_paused().await?;
+        let (sql, params) = if keyset {
+            let (condition, params) = if checkpoint.last_key.is_empty() {
+                (String::new(), Vec::new())
+            } else {
+                keyset_predicate(&source_type, &order_columns, &checkpoint.last_key, 1)
+            };
+            let where_sql = filter::where_clause(filter, &condition);
+            (format!("{}{}{} LIMIT {}", base_sql, where_sql, order_by, batch_size), params)
+        } else {
+            let where_sql = filter::where_clause(filter, "");
+            (
+                format!("{}{}{} LIMIT {} OFFSET {}", base_sql, where_sql, order_by, batch_size, checkpoint.rows_copied),
+                Vec::new(),
+            )
+        };
+        let batch = source_conn.fetch_rows(&sql, params).await?;
+        let fetched = batch.rows.len();
+        if fetched == 0 {
+            break;
+        }
+
+        let rows: Vec<Vec<SqlValue>> = batch
+            .rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(select_columns.iter())
+                    .map(|(value, column)| decode_value(&source_type, column, value))
+                    .collect()
+            })
+            .collect();
+        if keyset {
+            let last = &rows[fetched - 1];
+            checkpoint.last_key = key_indexes.iter().map(|&i| last[i].clone()).collect();
+        }
+        let rows = rows
+            .into_iter()
+            .map(|row| mapper.map_row(row))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let bytes = rows_bytes(&rows);
+        upsert_rows(target_conn, &target_table.name, &target_columns, &upsert_keys, rows).await?;
+        ctx.record_rows(fetched as u64, fetched as u64, bytes);
+        checkpoint.batch_number += 1;
+        checkpoint.rows_copied += fetched as u64;
+        ctx.save_table_checkpoint(&source_table.name, &checkpoint).await?;
+        ctx.progress()
+            .advance(fetched as u64, format!("Copied {} rows of {}", checkpoint.rows_copied, source_table.name))
+            .await;
+
+        if fetched < batch_size {
+            break;
+        }
+    }
+
+    Ok(checkpoint.rows_copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> SqlValue {
+        SqlValue::Text(value.to_string())
+    }
+
+    #[test]
+    fn coerces_values_to_column_kinds() {
+        assert_eq!(coerce_value(SqlValue::Null, ColumnKind::Integer), SqlValue::Null);
+        assert_eq!(coerce_value(text("yes"), ColumnKind::Bool), SqlValue::Bool(true));
+        assert_eq!(coerce_value(SqlValue::Int(0), ColumnKind::Bool), SqlValue::Bool(false));
+        assert_eq!(coerce_value(text("maybe"), ColumnKind::Bool), text("maybe"));
+        assert_eq!(coerce_value(SqlValue::Bool(true), ColumnKind::Integer), SqlValue::Int(1));
+        assert_eq!(coerce_value(SqlValue::Float(3.0), ColumnKind::Integer), SqlValue::Int(3));
+        assert_eq!(coerce_value(SqlValue::Float(3.5), ColumnKind::Integer), SqlValue::Int(3));
+        assert_eq!(coerce_value(text(" 12 "), ColumnKind::Integer), SqlValue::Int(12));
+        assert_eq!(coerce_value(text("abc"), ColumnKind::Integer), text("abc"));
+        assert_eq!(coerce_value(SqlValue::Int(2), ColumnKind::Float), SqlValue::Float(2.0));
+        assert_eq!(coerce_value(text("2.5"), ColumnKind::Float), SqlValue::Float(2.5));
+        assert_eq!(coerce_value(text("ab"), ColumnKind::Bytes), SqlValue::Bytes(b"ab".to_vec()));
+        assert_eq!(coerce_value(SqlValue::Int(7), ColumnKind::Text), text("7"));
+        assert_eq!(coerce_value(SqlValue::Bytes(b"hi".to_vec()), ColumnKind::Json), text("hi"));
+    }
+
+    #[test]
+    fn decodes_hex() {
+        assert_eq!(decode_hex("00ff7A"), Some(vec![0, 255, 122]));
+        assert_eq!(decode_hex(""), Some(Vec::new()));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+
+    #[test]
+    fn decodes_mysql_binary_columns() {
+        let column = ColumnSchema::new(&DatabaseType::MySql, "b".to_string(), "varbinary(8)".to_string(), true);
+        assert_eq!(decode_value(&DatabaseType::MySql, &column, text("0A0B")), SqlValue::Bytes(vec![10, 11]));
+        assert_eq!(decode_value(&DatabaseType::MySql, &column, text("not hex")), text("not hex"));
+        assert_eq!(decode_value(&DatabaseType::PostgreSql, &column, text("0A0B")), text("0A0B"));
+    }
+
+    #[test]
+    fn builds_keyset_predicates() {
+        let db_type = DatabaseType::PostgreSql;
+        let a = ColumnSchema::new(&db_type, "a".to_string(), "integer".to_string(), false);
+        let b = ColumnSchema::new(&db_type, "b".to_string(), "text".to_string(), false);
+        let (sql, params) = keyset_predicate(&db_type, &[&a, &b], &[text("5"), text("k")], 3);
+        assert_eq!(
+            sql,
+            "(\"a\" > CAST($3 AS integer)) OR (\"a\" = CAST($4 AS integer) AND \"b\" > CAST($5 AS text))"
+        );
+        assert_eq!(params, vec![SqlValue::Int(5), SqlValue::Int(5), text("k")]);
+
+        let (sql, params) = keyset_predicate(&DatabaseType::MySql, &[&a], &[SqlValue::Int(1)], 1);
+        assert_eq!(sql, "(`a` > ?)");
+        assert_eq!(params, vec![SqlValue::Int(1)]);
+    }
+
+    #[test]
+    fn selects_columns_in_a_neutral_form() {
+        let db_type = DatabaseType::MySql;
+        let id = ColumnSchema::new(&db_type, "id".to_string(), "int".to_string(), false);
+        let data = ColumnSchema::new(&db_type, "data".to_string(), "blob".to_string(), true);
+        assert_eq!(
+            select_columns_sql(&db_type, "t", &[&id, &data]),
+            "SELECT CAST(`id` AS SIGNED) AS `id`, HEX(`data`) AS `data` FROM `t`"
+        );
+        let sqlite = ColumnSchema::new(&DatabaseType::Sqlite, "id".to_string(), "INTEGER".to_string(), false);
+        assert_eq!(select_expression(&DatabaseType::Sqlite, &sqlite), "\"id\"");
+    }
+}
@@ -0,0 +1,596 @@
+// 表差异比较模块 - 按主键范围分段比较源表与目标表的校验和，逐级缩小到不一致的行，并可按源表修复目标表
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use crate::database::{ColumnKind, ColumnSchema, DatabaseType, SqlValue, SqlxDatabaseConnection, TableSchema};
+use crate::migration::filter;
+use crate::migration::mapping;
+use crate::migration::table_copy::{self, RowMapper, RowRules};
+use crate::migration::task_log::TaskLogger;
+use crate::migration::transform;
+use crate::migration::verify::{self, Checksum};
+
+fn default_chunk_size() -> usize {
+    10000
+}
+
+fn default_leaf_size() -> usize {
+    200
+}
+
+fn default_fanout() -> usize {
+    8
+}
+
+fn default_max_reported_rows() -> usize {
+    1000
+}
+
+// 差异比较的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffOptions {
+    // 需要比较的源表，为空时按任务策略确定
+    #[serde(default)]
+    pub tables: Vec<String>,
+    // 第一级范围的行数
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    // 范围内的行数不超过该值时逐行比较
+    #[serde(default = "default_leaf_size")]
+    pub leaf_size: usize,
+    // 不一致的范围每次拆分成的子范围数
+    #[serde(default = "default_fanout")]
+    pub fanout: usize,
+    // 报告中最多列出的差异行数，超出的只计数
+    #[serde(default = "default_max_reported_rows")]
+    pub max_reported_rows: usize,
+    // 是否按源表修复目标表中不一致的行
+    #[serde(default)]
+    pub repair: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            tables: Vec::new(),
+            chunk_size: default_chunk_size(),
+            leaf_size: default_leaf_size(),
+            fanout: default_fanout(),
+            max_reported_rows: default_max_reported_rows(),
+            repair: false,
+        }
+    }
+}
+
+// 差异行的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowDiffKind {
+    // 源表中有，目标表中没有
+    Missing,
+    // 目标表中有，源表中没有
+    Extra,
+    // 两端都有但值不同
+    Changed,
+}
+
+// 一行差异，按主键标识
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowDiff {
+    pub kind: RowDiffKind,
+    pub key: Vec<SqlValue>,
+}
+
+// 单张表的差异比较结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub table: String,
+    pub key_columns: Vec<String>,
+    pub source_rows: u64,
+    pub target_rows: u64,
+    // 比较过的范围数和其中不一致的范围数
+    pub ranges_compared: u64,
+    pub ranges_mismatched: u64,
+    pub missing_rows: u64,
+    pub extra_rows: u64,
+    pub changed_rows: u64,
+    // 差异行的主键，最多max_reported_rows行
+    pub rows: Vec<RowDiff>,
+    pub repaired: bool,
+    // 修复时写入和删除的目标行数
+    pub rows_written: u64,
+    pub rows_deleted: u64,
+    pub duration_ms: u64,
+}
+
+impl TableDiff {
+    // 是否有差异
+    pub fn has_differences(&self) -> bool {
+        self.missing_rows + self.extra_rows + self.changed_rows > 0
+    }
+
+    fn record(&mut self, kind: RowDiffKind, key: &[SqlValue], max_reported_rows: usize) {
+        match kind {
+            RowDiffKind::Missing => self.missing_rows += 1,
+            RowDiffKind::Extra => self.extra_rows += 1,
+            RowDiffKind::Changed => self.changed_rows += 1,
+        }
+        if self.rows.len() < max_reported_rows {
+            self.rows.push(RowDiff { kind, key: key.to_vec() });
+        }
+    }
+}
+
+// 主键范围 (lower, upper]，边界为空表示不限
+#[derive(Debug, Clone, Default)]
+struct KeyRange {
+    lower: Option<Vec<SqlValue>>,
+    upper: Option<Vec<SqlValue>>,
+}
+
+// 表的一端：连接、读取的列和主键列
+struct TableSide<'a> {
+    conn: &'a SqlxDatabaseConnection,
+    db_type: DatabaseType,
+    table: &'a str,
+    // 与另一端按位置对应的共有列
+    columns: Vec<&'a ColumnSchema>,
+    key_columns: Vec<&'a ColumnSchema>,
+    // 源表的过滤条件，只比较满足条件的行
+    filter: Option<&'a str>,
+}
+
+impl TableSide<'_> {
+    // 范围条件（含过滤条件），参数从first_param开始编号；没有边界和过滤条件时为空
+    fn range_condition(&self, range: &KeyRange, first_param: usize) -> (String, Vec<SqlValue>) {
+        let mut terms: Vec<String> = self.filter.map(|f| format!("({})", f)).into_iter().collect();
+        let mut params = Vec::new();
+        if let Some(lower) = &range.lower {
+            // 复合主键的OR条件无法利用索引，额外加上首列的范围
+            if self.key_columns.len() > 1 {
+                let (condition, value) = self.first_key_bound(">=", lower, first_param + params.len());
+                terms.push(condition);
+                params.push(value);
+            }
+            let (condition, values) =
+                table_copy::keyset_predicate(&self.db_type, &self.key_columns, lower, first_param + params.len());
+            terms.push(format!("({})", condition));
+            params.extend(values);
+        }
+        if let Some(upper) = &range.upper {
+            if self.key_columns.len() > 1 {
+                let (condition, value) = self.first_key_bound("<=", upper, first_param + params.len());
+                terms.push(condition);
+                params.push(value);
+            }
+            let (condition, values) =
+                table_copy::keyset_predicate(&self.db_type, &self.key_columns, upper, first_param + params.len());
+            terms.push(format!("NOT ({})", condition));
+            params.extend(values);
+        }
+        if terms.is_empty() {
+            (String::new(), params)
+        } else {
+            (format!(" WHERE {}", terms.join(" AND ")), params)
+        }
+    }
+
+    // 主键首列与边界值比较的条件
+    fn first_key_bound(&self, op: &str, bound: &[SqlValue], param: usize) -> (String, SqlValue) {
+        let column = self.key_columns[0];
+        let condition = format!(
+            "{} {} {}",
+            self.db_type.quote_identifier(&column.name),
+            op,
+            table_copy::insert_placeholder(&self.db_type, column, param)
+        );
+        (condition, table_copy::coerce_value(bound[0].clone(), column.kind))
+    }
+
+    // 读取范围内全部共有列的SQL
+    fn select_range(&self, range: &KeyRange) -> (String, Vec<SqlValue>) {
+        let (condition, params) = self.range_condition(range, 1);
+        (format!("{}{}", table_copy::select_columns_sql(&self.db_type, self.table, &self.columns), condition), params)
+    }
+
+    async fn checksum(&self, range: &KeyRange, kinds: &[ColumnKind]) -> Result<Checksum, String> {
+        let (sql, params) = self.select_range(range);
+        verify::query_checksum(self.conn, sql, params, &self.columns, kinds).await
+    }
+
+    // 范围内按主键排序后第offset行（从0开始）的主键，不存在时返回None
+    async fn key_at(&self, range: &KeyRange, offset: u64) -> Result<Option<Vec<SqlValue>>, String> {
+        let (condition, params) = self.range_condition(range, 1);
+        let order_by: Vec<String> = self.key_columns.iter().map(|c| self.db_type.quote_identifier(&c.name)).collect();
+        let sql = format!(
+            "{}{} ORDER BY {} LIMIT 1 OFFSET {}",
+            table_copy::select_columns_sql(&self.db_type, self.table, &self.key_columns),
+            condition,
+            order_by.join(", "),
+            offset
+        );
+        let result = self.conn.fetch_rows(&sql, params).await?;
+        Ok(result.rows.into_iter().next().map(|row| {
+            row.into_iter()
+                .zip(&self.key_columns)
+                .map(|(value, column)| table_copy::decode_value(&self.db_type, column, value))
+                .collect()
+        }))
+    }
+
+    // 按主键取值的条件
+    fn key_condition(&self, key: &[SqlValue], first_param: usize) -> (String, Vec<SqlValue>) {
+        let mut terms = Vec::with_capacity(key.len());
+        let mut params = Vec::with_capacity(key.len());
+        for (column, value) in self.key_columns.iter().zip(key) {
+            params.push(table_copy::coerce_value(value.clone(), column.kind));
+            terms.push(format!(
+                "{} = {}",
+                self.db_type.quote_identifier(&column.name),
+                table_copy::insert_placeholder(&self.db_type, column, first_param + params.len() - 1)
+            ));
+        }
+        (terms.join(" AND "), params)
+    }
+
+    // 主键对应的行是否存在，有过滤条件时还须满足条件
+    async fn contains(&self, key: &[SqlValue]) -> Result<bool, String> {
+        let (condition, params) = self.key_condition(key, 1);
+        let sql = format!(
+            "SELECT 1 FROM {}{}",
+            self.db_type.quote_identifier(self.table),
+            filter::where_clause(self.filter, &condition)
+        );
+        Ok(!self.conn.fetch_rows(&sql, params).await?.rows.is_empty())
+    }
+}
+
+// 一行的主键和规范化后的校验值
+struct LoadedRow {
+    key: Vec<SqlValue>,
+    hash: u64,
+}
+
+// 一张表的比较过程
+struct TableDiffer<'a> {
+    source: TableSide<'a>,
+    target: TableSide<'a>,
+    // 两端的值都按源列的类型规范化
+    kinds: Vec<ColumnKind>,
+    // 主键列在共有列中的位置
+    key_indexes: Vec<usize>,
+    // 修复时按任务的转换和列映射从源行生成目标行，不修复时为空
+    mapper: Option<RowMapper<'a>>,
+    // 修复时按目标表主键写入
+    upsert_keys: Vec<String>,
+    options: &'a DiffOptions,
+}
+
+// 逐表比较源库与映射后的目标表（表列表为空时比较源库全部表），源表只比较满足过滤条件的行
+pub async fn diff_tables(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    tables: &[String],
+    row_rules: RowRules<'_>,
+    options: &DiffOptions,
+    logger: &TaskLogger,
+) -> Result<Vec<TableDiff>, String> {
+    let schema = source_conn.introspect().await?;
+    for table in tables {
+        if schema.table(table).is_none() {
+            return Err(format!("Source table not found: {}", table));
+        }
+    }
+    let selected: Vec<_> = schema
+        .tables_in_dependency_order()
+        .into_iter()
+        .filter(|t| tables.is_empty() || tables.iter().any(|name| name.eq_ignore_ascii_case(&t.name)))
+        .collect();
+    logger.info(format!("开始比较数据差异，共 {} 张表", selected.len())).await;
+
+    let mut results = Vec::with_capacity(selected.len());
+    for source_table in selected {
+        let mapping = mapping::mapping_for(row_rules.mappings, &source_table.name);
+        let target_table = target_conn
+            .get_table_schema(mapping.target_table())
+            .await?
+            .ok_or_else(|| format!("Target table not found: {}", mapping.target_table()))?;
+        let diff = diff_table(source_conn, target_conn, source_table, &target_table, row_rules, options)
+            .await
+            .map_err(|e| format!("Failed to diff table {}: {}", source_table.name, e))?;
+        if !diff.has_differences() {
+            logger.info(format!("表 {} 没有差异，共比较 {} 个范围", diff.table, diff.ranges_compared)).await;
+        } else if diff.repaired {
+            logger.info(format!(
+                "表 {} 已修复: 缺少 {} 行，多出 {} 行，不一致 {} 行；写入 {} 行，删除 {} 行",
+                diff.table, diff.missing_rows, diff.extra_rows, diff.changed_rows, diff.rows_written, diff.rows_deleted
+            )).await;
+        } else {
+            logger.warning(format!(
+                "表 {} 存在差异: 缺少 {} 行，多出 {} 行，不一致 {} 行",
+                diff.table, diff.missing_rows, diff.extra_rows, diff.changed_rows
+            )).await;
+        }
+        results.push(diff);
+    }
+    Ok(results)
+}
+
+// 比较一张表：先按源表主键切分第一级范围，校验和不一致的范围继续拆分，直到可以逐行比较
+pub async fn diff_table(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    source_table: &TableSchema,
+    target_table: &TableSchema,
+    row_rules: RowRules<'_>,
+    options: &DiffOptions,
+) -> Result<TableDiff, String> {
+    let started = Instant::now();
+    let rules = &mapping::mapping_for(row_rules.mappings, &source_table.name).columns;
+    let transformers = row_rules.transformers;
+    let source_keys = source_table.primary_key_columns();
+    if source_keys.is_empty() {
+        return Err(format!("Table {} has no primary key to diff on", source_table.name));
+    }
+    // 转换器改写的列两端的值不同，不参与比较；修复时整行按转换和列映射重新生成
+    let transformed = transform::transformed_columns(transformers, source_table)?;
+    let pairs: Vec<_> = rules
+        .column_pairs(source_table, target_table)
+        .into_iter()
+        .filter(|(s, _)| !transformed.contains(&s.name))
+        .collect();
+    let mut key_indexes = Vec::with_capacity(source_keys.len());
+    for key in &source_keys {
+        if transformed.contains(&key.name) {
+            return Err(format!("Primary key column {} is changed by transformers and cannot be compared", key.name));
+        }
+        let index = pairs
+            .iter()
+            .position(|(s, _)| s.name == key.name)
+            .ok_or_else(|| format!("Primary key column {} is missing in target table", key.name))?;
+        key_indexes.push(index);
+    }
+    // 修复时与复制一样写入转换后的值，按目标表主键写入
+    let mapper = if options.repair {
+        Some(RowMapper::new(source_table, target_table, rules, transformers)?)
+    } else {
+        None
+    };
+    let upsert_keys: Vec<String> = match &mapper {
+        Some(mapper) => target_table
+            .primary_key_columns()
+            .iter()
+            .filter(|k| mapper.target_columns().iter().any(|c| c.name == k.name))
+            .map(|k| k.name.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+    if options.repair && upsert_keys.is_empty() {
+        return Err(format!("Target table {} has no primary key to repair on", target_table.name));
+    }
+
+    let differ = TableDiffer {
+        source: TableSide {
+            conn: source_conn,
+            db_type: source_conn.db_type(),
+            table: &source_table.name,
+            columns: pairs.iter().map(|(s, _)| *s).collect(),
+            key_columns: key_indexes.iter().map(|&i| pairs[i].0).collect(),
+            filter: row_rules.filter(&source_table.name),
+        },
+        target: TableSide {
+            conn: target_conn,
+            db_type: target_conn.db_type(),
+            table: &target_table.name,
+            columns: pairs.iter().map(|(_, t)| *t).collect(),
+            key_columns: key_indexes.iter().map(|&i| pairs[i].1).collect(),
+            filter: None,
+        },
+        kinds: pairs.iter().map(|(s, _)| s.kind).collect(),
+        key_indexes,
+        mapper,
+        upsert_keys,
+        options,
+    };
+    let mut diff = TableDiff {
+        table: source_table.name.clone(),
+        key_columns: differ.source.key_columns.iter().map(|c| c.name.clone()).collect(),
+        repaired: options.repair,
+        ..Default::default()
+    };
+
+    let chunk_size = options.chunk_size.max(1) as u64;
+    let mut lower: Option<Vec<SqlValue>> = None;
+    loop {
+        // 第一级范围以源表每chunk_size行的主键为上界，最后一个范围不设上界，覆盖目标表中多出的行
+        let upper = differ.source.key_at(&KeyRange { lower: lower.clone(), upper: None }, chunk_size - 1).await?;
+        let chunk = KeyRange { lower: lower.clone(), upper: upper.clone() };
+
+        differ.diff_range(chunk, &mut diff).await?;
+
+        match upper {
+            Some(upper) => lower = Some(upper),
+            None => break,
+        }
+    }
+
+    diff.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(diff)
+}
+
+impl TableDiffer<'_> {
+    // 比较一个第一级范围，校验和不一致时拆分成子范围，行数足够少时逐行比较
+    async fn diff_range(&self, chunk: KeyRange, diff: &mut TableDiff) -> Result<(), String> {
+        let leaf_size = self.options.leaf_size.max(1) as u64;
+        let fanout = self.options.fanout.max(2) as u64;
+        let mut pending = vec![chunk];
+        let mut first = true;
+        while let Some(range) = pending.pop() {
+            let (source_sum, target_sum) = tokio::join!(
+                self.source.checksum(&range, &self.kinds),
+                self.target.checksum(&range, &self.kinds),
+            );
+            let (source_sum, target_sum) = (source_sum?, target_sum?);
+            if first {
+                diff.source_rows += source_sum.rows;
+                diff.target_rows += target_sum.rows;
+                first = false;
+            }
+            diff.ranges_compared += 1;
+            if source_sum == target_sum {
+                continue;
+            }
+            diff.ranges_mismatched += 1;
+
+            // 按行数较多的一端拆分，每个子范围的行数约为原来的1/fanout
+            let rows = source_sum.rows.max(target_sum.rows);
+            let splitter = if source_sum.rows >= target_sum.rows { &self.source } else { &self.target };
+            let mut bounds = Vec::new();
+            if rows > leaf_size {
+                let step = rows.div_ceil(fanout);
+                let mut offset = step;
+                while offset < rows {
+                    if let Some(key) = splitter.key_at(&range, offset - 1).await? {
+                        bounds.push(key);
+                    }
+                    offset += step;
+                }
+            }
+            if bounds.is_empty() {
+                self.compare_rows(&range, diff).await?;
+                continue;
+            }
+            let mut sub_lower = range.lower.clone();
+            for bound in bounds {
+                pending.push(KeyRange { lower: sub_lower, upper: Some(bound.clone()) });
+                sub_lower = Some(bound);
+            }
+            pending.push(KeyRange { lower: sub_lower, upper: range.upper.clone() });
+        }
+        Ok(())
+    }
+
+    // 读取范围内两端的全部行，按主键逐行比较，需要时修复目标表
+    async fn compare_rows(&self, range: &KeyRange, diff: &mut TableDiff) -> Result<(), String> {
+        let max_reported_rows = self.options.max_reported_rows;
+        let (source_rows, target_rows) = tokio::join!(
+            self.load_rows(&self.source, range),
+            self.load_rows(&self.target, range),
+        );
+        let (mut source_rows, target_rows) = (source_rows?, target_rows?);
+
+        let mut writes = BTreeSet::new();
+        let mut extra = Vec::new();
+        for (key, target_row) in target_rows {
+            match source_rows.remove(&key) {
+                Some(source_row) if source_row.hash == target_row.hash => {}
+                Some(source_row) => {
+                    diff.record(RowDiffKind::Changed, &source_row.key, max_reported_rows);
+                    writes.insert(key);
+                }
+                None => extra.push(target_row.key),
+            }
+        }
+        for (key, source_row) in source_rows {
+            diff.record(RowDiffKind::Missing, &source_row.key, max_reported_rows);
+            writes.insert(key);
+        }
+        // 两端排序规则不同时，同一行可能落在不同的范围，确认源表中确实没有后才视为多出的行
+        let mut deletes = Vec::new();
+        for key in extra {
+            if !self.source.contains(&key).await? {
+                diff.record(RowDiffKind::Extra, &key, max_reported_rows);
+                deletes.push(key);
+            }
+        }
+
+        let mapper = match &self.mapper {
+            Some(mapper) => mapper,
+            None => return Ok(()),
+        };
+        let target = &self.target;
+        if !writes.is_empty() {
+            let rows = self.mapped_rows(mapper, range, &writes).await?;
+            // 按写入的行数计数，MySQL更新已有行时影响行数为2
+            diff.rows_written += rows.len() as u64;
+            table_copy::upsert_rows(target.conn, target.table, mapper.target_columns(), &self.upsert_keys, rows).await?;
+        }
+        if !deletes.is_empty() {
+            let statements = deletes
+                .iter()
+                .map(|key| {
+                    let (condition, params) = target.key_condition(key, 1);
+                    (format!("DELETE FROM {} WHERE {}", target.db_type.quote_identifier(target.table), condition), params)
+                })
+                .collect();
+            diff.rows_deleted += target.conn.execute_batch(statements).await?;
+        }
+        Ok(())
+    }
+
+    // 重新读取源表范围内需要写入的行的全部读取列，按任务的转换和列映射生成目标行
+    async fn mapped_rows(
+        &self,
+        mapper: &RowMapper<'_>,
+        range: &KeyRange,
+        keys: &BTreeSet<Vec<Option<String>>>,
+    ) -> Result<Vec<Vec<SqlValue>>, String> {
+        let source = &self.source;
+        let columns = mapper.select_columns();
+        let key_indexes = source
+            .key_columns
+            .iter()
+            .map(|key| {
+                columns
+                    .iter()
+                    .position(|c| c.name == key.name)
+                    .ok_or_else(|| format!("Primary key column {} is not read from source table", key.name))
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        let (condition, params) = source.range_condition(range, 1);
+        let sql = format!("{}{}", table_copy::select_columns_sql(&source.db_type, source.table, columns), condition);
+        let result = source.conn.fetch_rows(&sql, params).await?;
+        let mut rows = Vec::with_capacity(keys.len());
+        for row in result.rows {
+            let values: Vec<SqlValue> = row
+                .into_iter()
+                .zip(columns)
+                .map(|(value, column)| table_copy::decode_value(&source.db_type, column, value))
+                .collect();
+            let key_text: Vec<Option<String>> = key_indexes
+                .iter()
+                .map(|&i| verify::normalize_value(values[i].clone(), columns[i].kind))
+                .collect();
+            if keys.contains(&key_text) {
+                rows.push(mapper.map_row(values)?);
+            }
+        }
+        Ok(rows)
+    }
+
+    // 读取一端范围内的行，按规范化后的主键索引
+    async fn load_rows(&self, side: &TableSide<'_>, range: &KeyRange) -> Result<BTreeMap<Vec<Option<String>>, LoadedRow>, String> {
+        let (sql, params) = side.select_range(range);
+        let result = side.conn.fetch_rows(&sql, params).await?;
+        let mut rows = BTreeMap::new();
+        for row in result.rows {
+            let values: Vec<SqlValue> = row
+                .into_iter()
+                .zip(&side.columns)
+                .map(|(value, column)| table_copy::decode_value(&side.db_type, column, value))
+                .collect();
+            let normalized: Vec<Option<String>> = values
+                .iter()
+                .zip(&self.kinds)
+                .map(|(value, kind)| verify::normalize_value(value.clone(), *kind))
+                .collect();
+            let key_text = self.key_indexes.iter().map(|&i| normalized[i].clone()).collect();
+            rows.insert(key_text, LoadedRow {
+                key: self.key_indexes.iter().map(|&i| values[i].clone()).collect(),
+                hash: verify::row_hash(&normalized),
+            });
+        }
+        Ok(rows)
+    }
+}
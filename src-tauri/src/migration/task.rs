@@ -1,13 +1,20 @@
 // 迁移任务模块
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tokio::time::Instant;
 use uuid::Uuid;
 use serde_with::{serde_as, TimestampMilliSeconds};
-use crate::database::UnifiedConnectionManager;
 use crate::migration::{MigrationStrategyEnum};
+use crate::migration::cdc::CdcCheckpoint;
+use crate::migration::checkpoint::{CheckpointStore, TableCheckpoint};
+use crate::migration::progress::{ProgressReporter, ProgressSink};
+use crate::migration::task_log::TaskLogger;
+use crate::migration::task_store::TaskStore;
+use crate::migration::transform::MaskedColumn;
+use crate::migration::verify::VerificationReport;
 
 // 任务状态枚举
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -20,16 +27,86 @@ pub enum TaskStatus {
     Canceled,
 }
 
+// 运行中任务的控制信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskSignal {
+    Run,
+    Pause,
+    Cancel,
+}
+
+// 任务被取消时策略返回的错误
+pub const TASK_CANCELED: &str = "Migration task canceled";
+
+// 瞬时速度的采样间隔，间隔内的多次更新合并计算
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+// 单张表的进度
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TableProgress {
+    pub table: String,
+    pub completed_items: u64,
+    pub total_items: Option<u64>,
+    pub bytes: u64,
+    // 从开始处理该表到最近一次更新的毫秒数
+    pub elapsed_ms: u64,
+    pub average_rows_per_second: f64,
+    // 上次执行中已完成、本次跳过的行数，不计入速度
+    #[serde(skip)]
+    skipped_items: u64,
+    #[serde(skip)]
+    started_at: Option<Instant>,
+}
+
+impl TableProgress {
+    fn new(table: String, total_items: Option<u64>) -> Self {
+        Self {
+            table,
+            total_items,
+            ..Default::default()
+        }
+    }
+
+    // 重新计算耗时和平均速度
+    fn refresh(&mut self) {
+        if let Some(started_at) = self.started_at {
+            let elapsed = Instant::now().saturating_duration_since(started_at);
+            self.elapsed_ms = elapsed.as_millis() as u64;
+            self.average_rows_per_second = rate(self.completed_items.saturating_sub(self.skipped_items), elapsed);
+        }
+    }
+}
+
 // 任务进度信息
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TaskProgress {
     pub completed_items: u64,
     pub total_items: Option<u64>,
     pub current_operation: String,
+    // 正在处理的表，不按表处理的策略为空
+    pub current_table: Option<String>,
+    // 本次执行写入目标端的数据量
+    pub bytes_transferred: u64,
+    // 最近一个采样间隔内的速度
+    pub rows_per_second: f64,
+    // 从开始执行到现在的平均速度
+    pub average_rows_per_second: f64,
+    pub elapsed_ms: u64,
+    // 按当前速度估算的剩余时间，总量未知或速度为零时为空
+    pub eta_seconds: Option<u64>,
+    // 按处理顺序排列的各表进度
+    pub tables: Vec<TableProgress>,
     #[serde(skip)]
     pub start_time: Instant,
     #[serde(skip)]
     pub last_update_time: Instant,
+    // 从检查点跳过的项目数，不计入速度
+    #[serde(skip)]
+    skipped_items: u64,
+    #[serde(skip)]
+    sample_time: Instant,
+    #[serde(skip)]
+    sample_items: u64,
 }
 
 // 为TaskProgress实现自定义的反序列化
@@ -44,20 +121,32 @@ impl<'de> serde::Deserialize<'de> for TaskProgress {
             completed_items: u64,
             total_items: Option<u64>,
             current_operation: String,
+            #[serde(default)]
+            current_table: Option<String>,
+            #[serde(default)]
+            bytes_transferred: u64,
+            #[serde(default)]
+            average_rows_per_second: f64,
+            #[serde(default)]
+            elapsed_ms: u64,
+            #[serde(default)]
+            tables: Vec<TableProgress>,
         }
         
         let temp = TaskProgressTemp::deserialize(deserializer)?;
         
-        // 创建一个新的Instant用于start_time和last_update_time
-        let now = Instant::now();
-        
-        Ok(Self {
-            completed_items: temp.completed_items,
-            total_items: temp.total_items,
-            current_operation: temp.current_operation,
-            start_time: now,
-            last_update_time: now,
-        })
+        // 创建一个新的Instant用于start_time和last_update_time，瞬时速度和剩余时间不再有效
+        let mut progress = Self::new();
+        progress.completed_items = temp.completed_items;
+        progress.total_items = temp.total_items;
+        progress.current_operation = temp.current_operation;
+        progress.current_table = temp.current_table;
+        progress.bytes_transferred = temp.bytes_transferred;
+        progress.average_rows_per_second = temp.average_rows_per_second;
+        progress.elapsed_ms = temp.elapsed_ms;
+        progress.tables = temp.tables;
+        progress.sample_items = temp.completed_items;
+        Ok(progress)
     }
 }
 
@@ -69,16 +158,98 @@ impl TaskProgress {
             completed_items: 0,
             total_items: None,
             current_operation: "Initializing".to_string(),
+            current_table: None,
+            bytes_transferred: 0,
+            rows_per_second: 0.0,
+            average_rows_per_second: 0.0,
+            elapsed_ms: 0,
+            eta_seconds: None,
+            tables: Vec::new(),
             start_time: now,
             last_update_time: now,
+            skipped_items: 0,
+            sample_time: now,
+            sample_items: 0,
         }
     }
     
+    // 重新开始计量，用于任务的一次新执行
+    pub fn restart(&mut self, current_operation: String) {
+        *self = Self::new();
+        self.current_operation = current_operation;
+    }
+    
+    // 开始新的阶段（如CDC从初始复制转入持续同步），重新计算完成数和速度，保留各表的进度
+    pub fn begin_stage(&mut self, current_operation: String) {
+        let now = Instant::now();
+        self.completed_items = 0;
+        self.total_items = None;
+        self.current_table = None;
+        self.rows_per_second = 0.0;
+        self.average_rows_per_second = 0.0;
+        self.eta_seconds = None;
+        self.start_time = now;
+        self.skipped_items = 0;
+        self.sample_time = now;
+        self.sample_items = 0;
+        self.update(0, current_operation);
+    }
+    
     // 更新进度
     pub fn update(&mut self, completed_items: u64, current_operation: String) {
         self.completed_items = completed_items;
         self.current_operation = current_operation;
         self.last_update_time = Instant::now();
+        self.refresh_rates();
+    }
+    
+    // 增加已完成的项目数，同时计入当前表
+    pub fn advance(&mut self, items: u64, current_operation: String) {
+        if let Some(table) = self.current_table_mut() {
+            table.completed_items += items;
+            table.refresh();
+        }
+        self.update(self.completed_items + items, current_operation);
+    }
+    
+    // 计入从检查点跳过的项目，它们不影响速度
+    pub fn skip(&mut self, items: u64, current_operation: String) {
+        if let Some(table) = self.current_table_mut() {
+            table.completed_items += items;
+            table.skipped_items += items;
+        }
+        self.skipped_items += items;
+        self.sample_items += items;
+        self.update(self.completed_items + items, current_operation);
+    }
+    
+    // 增加写入的数据量，同时计入当前表
+    pub fn add_bytes(&mut self, bytes: u64) {
+        self.bytes_transferred += bytes;
+        if let Some(table) = self.current_table_mut() {
+            table.bytes += bytes;
+        }
+    }
+    
+    // 登记待处理的表及其预估行数，已登记时更新行数
+    pub fn add_table(&mut self, table: &str, total_items: Option<u64>) {
+        match self.tables.iter_mut().find(|t| t.table == table) {
+            Some(existing) => existing.total_items = total_items,
+            None => self.tables.push(TableProgress::new(table.to_string(), total_items)),
+        }
+    }
+    
+    // 切换正在处理的表，未登记的表自动登记
+    pub fn set_current_table(&mut self, table: Option<String>) {
+        if let Some(name) = &table {
+            if !self.tables.iter().any(|t| &t.table == name) {
+                self.add_table(name, None);
+            }
+        }
+        self.current_table = table;
+        if let Some(table) = self.current_table_mut() {
+            table.started_at.get_or_insert_with(Instant::now);
+        }
     }
     
     // 设置总项目数
@@ -100,6 +271,41 @@ impl TaskProgress {
     pub fn get_duration(&self) -> Duration {
         self.last_update_time.saturating_duration_since(self.start_time)
     }
+    
+    fn current_table_mut(&mut self) -> Option<&mut TableProgress> {
+        let name = self.current_table.as_ref()?;
+        self.tables.iter_mut().find(|t| &t.table == name)
+    }
+    
+    // 重新计算速度和剩余时间，瞬时速度每个采样间隔更新一次
+    fn refresh_rates(&mut self) {
+        let elapsed = self.get_duration();
+        self.elapsed_ms = elapsed.as_millis() as u64;
+        self.average_rows_per_second = rate(self.completed_items.saturating_sub(self.skipped_items), elapsed);
+        
+        let window = self.last_update_time.saturating_duration_since(self.sample_time);
+        if window >= RATE_SAMPLE_INTERVAL {
+            self.rows_per_second = rate(self.completed_items.saturating_sub(self.sample_items), window);
+            self.sample_time = self.last_update_time;
+            self.sample_items = self.completed_items;
+        }
+        
+        let speed = if self.rows_per_second > 0.0 { self.rows_per_second } else { self.average_rows_per_second };
+        self.eta_seconds = match self.total_items {
+            Some(total) if speed > 0.0 => Some((total.saturating_sub(self.completed_items) as f64 / speed).ceil() as u64),
+            _ => None,
+        };
+    }
+}
+
+// 每秒处理的项目数
+fn rate(items: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        items as f64 / seconds
+    } else {
+        0.0
+    }
 }
 
 // 迁移任务
@@ -123,6 +329,15 @@ pub struct MigrationTask {
     pub started_at: Option<SystemTime>,
     #[serde_as(as = "Option<TimestampMilliSeconds>")]
     pub completed_at: Option<SystemTime>,
+    // CDC任务的同步位置
+    #[serde(default)]
+    pub cdc_checkpoint: Option<CdcCheckpoint>,
+    // 完成后是否自动校验源库和目标库的数据
+    #[serde(default)]
+    pub verify_on_complete: bool,
+    // 最近一次校验的结果
+    #[serde(default)]
+    pub verification: Option<VerificationReport>,
 }
 
 impl MigrationTask {
@@ -149,11 +364,20 @@ impl MigrationTask {
             updated_at: now,
             started_at: None,
             completed_at: None,
+            cdc_checkpoint: None,
+            verify_on_complete: false,
+            verification: None,
         }
     }
     
+    // 设置完成后自动校验
+    pub fn with_verify_on_complete(mut self, verify_on_complete: bool) -> Self {
+        self.verify_on_complete = verify_on_complete;
+        self
+    }
+    
     // 开始任务
-    pub async fn start(&mut self, _: Arc<UnifiedConnectionManager>) -> Result<(), String> {
+    pub fn start(&mut self) -> Result<(), String> {
         if self.status != TaskStatus::Created && self.status != TaskStatus::Paused && self.status != TaskStatus::Failed {
             return Err(format!("Cannot start task in {} status", self.status_to_string()));
         }
@@ -161,10 +385,7 @@ impl MigrationTask {
         self.status = TaskStatus::Running;
         self.started_at.get_or_insert(SystemTime::now());
         self.updated_at = SystemTime::now();
-        self.progress.update(0, "Starting migration".to_string());
-        
-        // 在实际应用中，这里会启动一个异步任务来执行迁移
-        // 为了简化示例，我们仅返回成功
+        self.progress.restart("Starting migration".to_string());
         Ok(())
     }
     
@@ -181,6 +402,19 @@ impl MigrationTask {
         Ok(())
     }
     
+    // 恢复暂停的任务
+    pub fn resume(&mut self) -> Result<(), String> {
+        if self.status != TaskStatus::Paused {
+            return Err(format!("Cannot resume task in {} status", self.status_to_string()));
+        }
+        
+        self.status = TaskStatus::Running;
+        self.updated_at = SystemTime::now();
+        self.progress.update(self.progress.completed_items, "Migration resumed".to_string());
+        
+        Ok(())
+    }
+    
     // 取消任务
     pub fn cancel(&mut self) -> Result<(), String> {
         if self.status == TaskStatus::Completed || self.status == TaskStatus::Canceled {
@@ -194,15 +428,17 @@ impl MigrationTask {
         Ok(())
     }
     
-    // 完成任务
+    // 完成任务，暂停信号到达前已执行完的任务同样视为完成
     pub fn complete(&mut self) -> Result<(), String> {
-        if self.status != TaskStatus::Running {
+        if self.status != TaskStatus::Running && self.status != TaskStatus::Paused {
             return Err(format!("Cannot complete task in {} status", self.status_to_string()));
         }
         
         self.status = TaskStatus::Completed;
         self.updated_at = SystemTime::now();
         self.completed_at = Some(SystemTime::now());
+        // 数据已变化，之前的校验结果不再有效
+        self.verification = None;
         self.progress.update(self.progress.completed_items, "Migration completed".to_string());
         
         Ok(())
@@ -222,6 +458,12 @@ impl MigrationTask {
         Ok(())
     }
     
+    // 记录校验结果
+    pub fn set_verification(&mut self, report: VerificationReport) {
+        self.verification = Some(report);
+        self.updated_at = SystemTime::now();
+    }
+    
     // 获取状态的字符串表示
     pub fn status_to_string(&self) -> String {
         match self.status {
@@ -234,4 +476,260 @@ impl MigrationTask {
         }
         .to_string()
     }
-}
\ No newline at end of file
+}
+
+// 一次执行的读写统计
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RunStats {
+    pub rows_read: u64,
+    pub rows_written: u64,
+    pub rows_failed: u64,
+    // 写入目标端的数据量，按值的大小估算
+    pub bytes: u64,
+    // 写入前被脱敏的列
+    #[serde(default)]
+    pub masked_columns: Vec<MaskedColumn>,
+}
+
+// 任务的一次执行记录，重试不会覆盖之前的记录
+#[serde_as]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TaskRun {
+    pub id: String,
+    pub task_id: String,
+    // 任务内从1开始的执行序号
+    pub run_number: u64,
+    pub status: TaskStatus,
+    pub stats: RunStats,
+    pub error: Option<String>,
+    #[serde_as(as = "TimestampMilliSeconds")]
+    pub started_at: SystemTime,
+    #[serde_as(as = "Option<TimestampMilliSeconds>")]
+    pub ended_at: Option<SystemTime>,
+}
+
+impl TaskRun {
+    // 开始一次执行
+    pub fn new(task_id: &str, run_number: u64) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            run_number,
+            status: TaskStatus::Running,
+            stats: RunStats::default(),
+            error: None,
+            started_at: SystemTime::now(),
+            ended_at: None,
+        }
+    }
+
+    // 结束执行，记录最终状态和统计
+    pub fn finish(&mut self, status: TaskStatus, error: Option<String>, stats: RunStats) {
+        self.status = status;
+        self.error = error;
+        self.stats = stats;
+        self.ended_at = Some(SystemTime::now());
+    }
+}
+
+// 任务运行上下文，策略通过它读取和回写所属任务的状态，并在批次之间响应暂停和取消
+#[derive(Clone)]
+pub struct TaskContext {
+    task_id: String,
+    tasks: Arc<RwLock<HashMap<String, MigrationTask>>>,
+    signal: watch::Receiver<TaskSignal>,
+    // 保存表复制进度的检查点存储，未配置时不记录进度
+    checkpoints: Option<CheckpointStore>,
+    // 持久化任务状态的存储，CDC同步位置更新后立即写入
+    task_store: Option<TaskStore>,
+    // 本次执行的读写统计
+    stats: Arc<Mutex<RunStats>>,
+    // 进度上报句柄
+    progress: ProgressReporter,
+    // 任务日志句柄
+    logger: TaskLogger,
+}
+
+impl TaskContext {
+    pub fn new(
+        task_id: String,
+        tasks: Arc<RwLock<HashMap<String, MigrationTask>>>,
+        signal: watch::Receiver<TaskSignal>,
+    ) -> Self {
+        let stats = Arc::new(Mutex::new(RunStats::default()));
+        Self {
+            progress: ProgressReporter::new(task_id.clone(), tasks.clone(), stats.clone()),
+            logger: TaskLogger::new(task_id.clone()),
+            task_id,
+            tasks,
+            signal,
+            checkpoints: None,
+            task_store: None,
+            stats,
+        }
+    }
+
+    // 设置任务日志句柄
+    pub fn with_logger(mut self, logger: TaskLogger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    // 设置进度事件的接收方
+    pub fn with_progress_sink(mut self, sink: ProgressSink) -> Self {
+        self.progress = self.progress.with_sink(sink);
+        self
+    }
+
+    // 设置任务存储
+    pub fn with_task_store(mut self, task_store: TaskStore) -> Self {
+        self.task_store = Some(task_store);
+        self
+    }
+
+    // 设置检查点存储，中断的任务重新启动时从中恢复表复制进度
+    pub fn with_checkpoints(mut self, checkpoints: CheckpointStore) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    // 不属于任何流水线的上下文，用于直接执行策略
+    pub fn detached() -> Self {
+        let (_, signal) = watch::channel(TaskSignal::Run);
+        let tasks = Arc::new(RwLock::new(HashMap::new()));
+        let stats = Arc::new(Mutex::new(RunStats::default()));
+        Self {
+            task_id: String::new(),
+            progress: ProgressReporter::new(String::new(), tasks.clone(), stats.clone()),
+            logger: TaskLogger::new(String::new()),
+            tasks,
+            signal,
+            checkpoints: None,
+            task_store: None,
+            stats,
+        }
+    }
+
+    // 获取任务ID
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    // 是否收到了暂停信号
+    pub fn is_paused(&self) -> bool {
+        *self.signal.borrow() == TaskSignal::Pause
+    }
+
+    // 是否收到了取消信号
+    pub fn is_canceled(&self) -> bool {
+        *self.signal.borrow() == TaskSignal::Cancel
+    }
+
+    // 策略在批次之间调用：暂停时等待恢复，取消时返回错误
+    pub async fn wait_if_paused(&self) -> Result<(), String> {
+        let mut signal = self.signal.clone();
+        let mut announced = false;
+        loop {
+            let current = *signal.borrow_and_update();
+            match current {
+                TaskSignal::Run => return Ok(()),
+                TaskSignal::Cancel => return Err(TASK_CANCELED.to_string()),
+                TaskSignal::Pause => {
+                    if !announced {
+                        self.logger.info(format!("任务 {} 已暂停，等待恢复", self.task_id)).await;
+                        announced = true;
+                    }
+                    // 控制端已释放时按取消处理
+                    if signal.changed().await.is_err() {
+                        return Err(TASK_CANCELED.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    // 读取任务保存的CDC同步位置
+    pub async fn cdc_checkpoint(&self) -> Option<CdcCheckpoint> {
+        self.tasks
+            .read()
+            .await
+            .get(&self.task_id)
+            .and_then(|task| task.cdc_checkpoint.clone())
+    }
+
+    // 保存CDC同步位置，写入存储失败时只记录警告，同步继续进行
+    pub async fn save_cdc_checkpoint(&self, checkpoint: CdcCheckpoint) {
+        if let Some(task) = self.tasks.write().await.get_mut(&self.task_id) {
+            task.cdc_checkpoint = Some(checkpoint);
+            task.updated_at = SystemTime::now();
+        }
+        if let Some(store) = &self.task_store {
+            if let Err(e) = store.sync_task(&self.tasks, &self.task_id).await {
+                self.logger.warning(format!("保存任务 {} 的CDC同步位置失败: {}", self.task_id, e)).await;
+            }
+        }
+    }
+
+    // 读取表的复制进度
+    pub async fn table_checkpoint(&self, table: &str) -> Result<Option<TableCheckpoint>, String> {
+        match &self.checkpoints {
+            Some(store) => store.load_table_checkpoint(&self.task_id, table).await,
+            None => Ok(None),
+        }
+    }
+
+    // 保存表的复制进度
+    pub async fn save_table_checkpoint(&self, table: &str, checkpoint: &TableCheckpoint) -> Result<(), String> {
+        match &self.checkpoints {
+            Some(store) => store.save_table_checkpoint(&self.task_id, table, checkpoint).await,
+            None => Ok(()),
+        }
+    }
+
+    // 记录一批读取和写入的行数及写入的数据量
+    pub fn record_rows(&self, read: u64, written: u64, bytes: u64) {
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.rows_read += read;
+            stats.rows_written += written;
+            stats.bytes += bytes;
+        }
+    }
+
+    // 记录写入失败的行数
+    pub fn record_failed_rows(&self, failed: u64) {
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.rows_failed += failed;
+        }
+    }
+
+    // 记录被脱敏的列，同一表的同一列只记录一次
+    pub fn record_masked_columns(&self, columns: Vec<MaskedColumn>) {
+        if let Ok(mut stats) = self.stats.lock() {
+            for column in columns {
+                if !stats.masked_columns.iter().any(|c| c.table == column.table && c.column == column.column) {
+                    stats.masked_columns.push(column);
+                }
+            }
+        }
+    }
+
+    // 本次执行到目前为止的统计
+    pub fn run_stats(&self) -> RunStats {
+        self.stats.lock().map(|stats| stats.clone()).unwrap_or_default()
+    }
+
+    // 进度上报句柄
+    pub fn progress(&self) -> &ProgressReporter {
+        &self.progress
+    }
+
+    // 任务日志句柄
+    pub fn logger(&self) -> &TaskLogger {
+        &self.logger
+    }
+
+    // 更新任务进度
+    pub async fn update_progress(&self, completed_items: u64, current_operation: String) {
+        self.progress.update(completed_items, current_operation).await;
+    }
+}
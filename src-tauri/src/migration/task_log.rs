@@ -0,0 +1,304 @@
+// 迁移任务日志模块 - 策略和流水线按任务写入结构化日志，保存在应用配置库中并推送给界面
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::database::{SqlRowSet, SqlValue, SqlxDatabaseConnection, UnifiedConnectionManager};
+
+// 推送给界面的日志事件名
+pub const TASK_LOG_EVENT: &str = "migration://log";
+
+// 每写入多少条日志按保留策略清理一次，持续运行的CDC任务依赖它控制日志量
+const PRUNE_EVERY: u64 = 500;
+
+// 单页日志的最大条数
+const MAX_PAGE_SIZE: u64 = 1000;
+
+// 每个任务最多保留的日志条数
+const MAX_ENTRIES_PER_TASK: u64 = 10_000;
+
+// 日志的保留天数
+const RETENTION_DAYS: i64 = 30;
+
+// 日志级别，按严重程度递增
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    fn rank(self) -> i64 {
+        match self {
+            LogLevel::Info => 0,
+            LogLevel::Warning => 1,
+            LogLevel::Error => 2,
+        }
+    }
+
+    fn from_rank(rank: i64) -> Self {
+        match rank {
+            0 => LogLevel::Info,
+            1 => LogLevel::Warning,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
+// 一条任务日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskLogEntry {
+    pub id: String,
+    pub task_id: String,
+    // 产生日志的执行记录，流水线在执行之外的操作（如暂停）为空
+    pub run_id: Option<String>,
+    pub level: LogLevel,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+// 日志查询条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskLogQuery {
+    // 只返回不低于该级别的日志
+    #[serde(default)]
+    pub min_level: Option<LogLevel>,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub offset: u64,
+    #[serde(default = "default_page_size")]
+    pub limit: u64,
+}
+
+fn default_page_size() -> u64 {
+    200
+}
+
+impl Default for TaskLogQuery {
+    fn default() -> Self {
+        Self {
+            min_level: None,
+            run_id: None,
+            offset: 0,
+            limit: default_page_size(),
+        }
+    }
+}
+
+// 一页日志，最新的在前
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskLogPage {
+    pub entries: Vec<TaskLogEntry>,
+    // 符合条件的日志总数
+    pub total: u64,
+}
+
+// 日志事件的接收方，应用中转发为Tauri事件
+pub type LogSink = Arc<dyn Fn(&TaskLogEntry) + Send + Sync>;
+
+// 任务日志存储
+#[derive(Clone)]
+pub struct TaskLogStore {
+    conn: SqlxDatabaseConnection,
+}
+
+impl TaskLogStore {
+    // 打开配置库中的日志存储，必要时创建表
+    pub async fn open(conn_manager: &UnifiedConnectionManager, config_db_id: &str) -> Result<Self, String> {
+        let conn = conn_manager.get_sqlx_connection(config_db_id).await
+            .ok_or_else(|| format!("Task log database connection not found: {}", config_db_id))?;
+
+        let create_table_sql = r#"
+            CREATE TABLE IF NOT EXISTS migration_task_logs (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                id TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                run_id TEXT,
+                level INTEGER NOT NULL,
+                message TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )
+        "#;
+        conn.execute_raw(create_table_sql).await?;
+        conn.execute_raw("CREATE INDEX IF NOT EXISTS idx_migration_task_logs_task ON migration_task_logs (task_id, seq)").await?;
+
+        Ok(Self { conn })
+    }
+
+    // 写入一条日志
+    pub async fn append(&self, entry: &TaskLogEntry) -> Result<(), String> {
+        let sql = r#"
+            INSERT INTO migration_task_logs (id, task_id, run_id, level, message, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+        "#;
+        let params = vec![
+            SqlValue::Text(entry.id.clone()),
+            SqlValue::Text(entry.task_id.clone()),
+            entry.run_id.clone().map(SqlValue::Text).unwrap_or(SqlValue::Null),
+            SqlValue::Int(entry.level.rank()),
+            SqlValue::Text(entry.message.clone()),
+            SqlValue::Int(entry.timestamp.timestamp_millis()),
+        ];
+        self.conn.execute_values(sql, params).await?;
+        Ok(())
+    }
+
+    // 按条件分页查询任务日志
+    pub async fn query(&self, task_id: &str, query: &TaskLogQuery) -> Result<TaskLogPage, String> {
+        let mut filter = "task_id = ? AND level >= ?".to_string();
+        let mut params = vec![
+            SqlValue::Text(task_id.to_string()),
+            SqlValue::Int(query.min_level.unwrap_or(LogLevel::Info).rank()),
+        ];
+        if let Some(run_id) = &query.run_id {
+            filter.push_str(" AND run_id = ?");
+            params.push(SqlValue::Text(run_id.clone()));
+        }
+
+        let count_sql = format!("SELECT COUNT(*) FROM migration_task_logs WHERE {}", filter);
+        let result = self.conn.fetch_rows(&count_sql, params.clone()).await?;
+        let total = result.rows.first().and_then(|row| row.first()).and_then(|v| v.as_i64()).unwrap_or(0) as u64;
+
+        let sql = format!(
+            "SELECT id, task_id, run_id, level, message, created_at FROM migration_task_logs WHERE {} ORDER BY seq DESC LIMIT ? OFFSET ?",
+            filter
+        );
+        params.push(SqlValue::Int(query.limit.clamp(1, MAX_PAGE_SIZE) as i64));
+        params.push(SqlValue::Int(query.offset as i64));
+        let result = self.conn.fetch_rows(&sql, params).await?;
+        let entries = result.rows.iter().map(|row| decode_entry(&result, row)).collect::<Result<_, _>>()?;
+
+        Ok(TaskLogPage { entries, total })
+    }
+
+    // 清理超过保留天数和条数上限的日志，未指定任务时清理全部任务，返回删除的条数
+    pub async fn prune(&self, task_id: Option<&str>) -> Result<u64, String> {
+        let cutoff = Utc::now().timestamp_millis() - RETENTION_DAYS * 24 * 60 * 60 * 1000;
+        let mut deleted = self
+            .conn
+            .execute_values("DELETE FROM migration_task_logs WHERE created_at < ?", vec![SqlValue::Int(cutoff)])
+            .await?;
+
+        let task_ids = match task_id {
+            Some(task_id) => vec![task_id.to_string()],
+            None => {
+                let result = self.conn.fetch_rows("SELECT DISTINCT task_id FROM migration_task_logs", vec![]).await?;
+                result.rows.iter().filter_map(|row| row.first().and_then(|v| v.as_string())).collect()
+            }
+        };
+        // 只保留每个任务最新的若干条
+        let sql = r#"
+            DELETE FROM migration_task_logs
+            WHERE task_id = ? AND seq <= (
+                SELECT seq FROM migration_task_logs WHERE task_id = ? ORDER BY seq DESC LIMIT 1 OFFSET ?
+            )
+        "#;
+        for task_id in task_ids {
+            let params = vec![
+                SqlValue::Text(task_id.clone()),
+                SqlValue::Text(task_id),
+                SqlValue::Int(MAX_ENTRIES_PER_TASK as i64),
+            ];
+            deleted += self.conn.execute_values(sql, params).await?;
+        }
+        Ok(deleted)
+    }
+}
+
+fn decode_entry(result: &SqlRowSet, row: &[SqlValue]) -> Result<TaskLogEntry, String> {
+    let text = |name: &str| result.get(row, name).and_then(|v| v.as_string());
+    let int = |name: &str| result.get(row, name).and_then(|v| v.as_i64());
+
+    let millis = int("created_at").ok_or_else(|| "Missing created_at".to_string())?;
+    Ok(TaskLogEntry {
+        id: text("id").ok_or_else(|| "Missing id".to_string())?,
+        task_id: text("task_id").ok_or_else(|| "Missing task_id".to_string())?,
+        run_id: text("run_id"),
+        level: LogLevel::from_rank(int("level").unwrap_or(0)),
+        message: text("message").unwrap_or_default(),
+        timestamp: Utc.timestamp_millis_opt(millis).single().unwrap_or_default(),
+    })
+}
+
+// 任务日志句柄：输出到控制台，并在配置后写入存储、推送给界面
+#[derive(Clone)]
+pub struct TaskLogger {
+    task_id: String,
+    run_id: Option<String>,
+    store: Option<TaskLogStore>,
+    sink: Option<LogSink>,
+    // 本句柄写入的条数，用于定期清理
+    written: Arc<AtomicU64>,
+}
+
+impl TaskLogger {
+    // 只输出到控制台的日志句柄
+    pub fn new(task_id: String) -> Self {
+        Self {
+            task_id,
+            run_id: None,
+            store: None,
+            sink: None,
+            written: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    // 设置所属的执行记录
+    pub fn with_run(mut self, run_id: String) -> Self {
+        self.run_id = Some(run_id);
+        self
+    }
+
+    // 设置日志存储
+    pub fn with_store(mut self, store: TaskLogStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    // 设置日志事件的接收方
+    pub fn with_sink(mut self, sink: LogSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub async fn info(&self, message: impl Into<String>) {
+        self.log(LogLevel::Info, message.into()).await;
+    }
+
+    pub async fn warning(&self, message: impl Into<String>) {
+        self.log(LogLevel::Warning, message.into()).await;
+    }
+
+    pub async fn error(&self, message: impl Into<String>) {
+        self.log(LogLevel::Error, message.into()).await;
+    }
+
+    // 写入一条日志，写入存储失败时只记入运行日志，不影响任务执行
+    pub async fn log(&self, level: LogLevel, message: String) {
+        let entry = TaskLogEntry {
+            id: Uuid::new_v4().to_string(),
+            task_id: self.task_id.clone(),
+            run_id: self.run_id.clone(),
+            level,
+            message,
+            timestamp: Utc::now(),
+        };
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append(&entry).await {
+                log::warn!("无法保存任务 {} 的日志: {}", self.task_id, e);
+            } else if (self.written.fetch_add(1, Ordering::Relaxed) + 1).is_multiple_of(PRUNE_EVERY) {
+                if let Err(e) = store.prune(Some(&self.task_id)).await {
+                    log::warn!("清理任务 {} 的日志失败: {}", self.task_id, e);
+                }
+            }
+        }
+        if let Some(sink) = &self.sink {
+            sink(&entry);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+// 迁移任务持久化模块 - 把任务定义和状态写入应用配置库，应用重启后重新加载
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use tokio::sync::{Mutex, RwLock};
+use crate::database::{SqlRowSet, SqlValue, SqlxDatabaseConnection, UnifiedConnectionManager};
+use crate::migration::MigrationTask;
+use crate::migration::task::{RunStats, TaskProgress, TaskRun, TaskStatus};
+
+// 应用退出时仍在执行的任务重新加载后的错误信息
+pub const TASK_INTERRUPTED: &str = "Task was interrupted by application restart";
+
+// 任务存储
+#[derive(Clone)]
+pub struct TaskStore {
+    conn: SqlxDatabaseConnection,
+    // 串行化写入，保证后发生的状态不会被较早的快照覆盖
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl TaskStore {
+    // 打开配置库中的任务存储，必要时创建表
+    pub async fn open(conn_manager: &UnifiedConnectionManager, config_db_id: &str) -> Result<Self, String> {
+        let conn = conn_manager.get_sqlx_connection(config_db_id).await
+            .ok_or_else(|| format!("Task database connection not found: {}", config_db_id))?;
+
+        let create_table_sql = r#"
+            CREATE TABLE IF NOT EXISTS migration_tasks (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT NOT NULL,
+                source_db_id TEXT NOT NULL,
+                target_db_id TEXT NOT NULL,
+                strategy TEXT NOT NULL,
+                status TEXT NOT NULL,
+                progress TEXT NOT NULL,
+                error TEXT,
+                cdc_checkpoint TEXT,
+                verify_on_complete INTEGER NOT NULL DEFAULT 0,
+                verification TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                started_at INTEGER,
+                completed_at INTEGER
+            )
+        "#;
+        conn.execute_raw(create_table_sql).await?;
+        // 旧版本创建的表缺少校验相关的列
+        add_column_if_missing(&conn, "migration_tasks", "verify_on_complete", "INTEGER NOT NULL DEFAULT 0").await?;
+        add_column_if_missing(&conn, "migration_tasks", "verification", "TEXT").await?;
+
+        let create_runs_sql = r#"
+            CREATE TABLE IF NOT EXISTS migration_task_runs (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                run_number INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                rows_read INTEGER NOT NULL,
+                rows_written INTEGER NOT NULL,
+                rows_failed INTEGER NOT NULL,
+                bytes INTEGER NOT NULL,
+                masked_columns TEXT,
+                error TEXT,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER
+            )
+        "#;
+        conn.execute_raw(create_runs_sql).await?;
+        add_column_if_missing(&conn, "migration_task_runs", "masked_columns", "TEXT").await?;
+        conn.execute_raw("CREATE INDEX IF NOT EXISTS idx_migration_task_runs_task ON migration_task_runs (task_id, run_number)").await?;
+
+        Ok(Self {
+            conn,
+            write_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    // 读取全部任务，执行中的任务标记为失败，之后可以从检查点继续
+    pub async fn load_tasks(&self) -> Result<Vec<MigrationTask>, String> {
+        let sql = r#"
+            SELECT id, name, description, source_db_id, target_db_id, strategy, status, progress, error,
+                   cdc_checkpoint, verify_on_complete, verification, created_at, updated_at, started_at, completed_at
+            FROM migration_tasks
+            ORDER BY created_at
+        "#;
+        let result = self.conn.fetch_rows(sql, vec![]).await?;
+
+        let mut tasks = Vec::with_capacity(result.rows.len());
+        for row in &result.rows {
+            let id = result.get(row, "id").and_then(|v| v.as_string()).unwrap_or_default();
+            match decode_task(&result, row) {
+                Ok(mut task) => {
+                    if task.status == TaskStatus::Running {
+                        task.fail(TASK_INTERRUPTED.to_string())?;
+                    }
+                    tasks.push(task);
+                }
+                // 策略配置无法解析（如版本变更）时跳过该任务，不影响其他任务
+                Err(e) => log::warn!("无法加载迁移任务 {}: {}", id, e),
+            }
+        }
+        Ok(tasks)
+    }
+
+    // 把任务的最新状态写入存储，任务已不存在时忽略
+    pub async fn sync_task(&self, tasks: &RwLock<HashMap<String, MigrationTask>>, id: &str) -> Result<(), String> {
+        let _guard = self.write_lock.lock().await;
+        let task = match tasks.read().await.get(id) {
+            Some(task) => task.clone(),
+            None => return Ok(()),
+        };
+        self.save_task(&task).await
+    }
+
+    // 把应用退出时仍在执行的记录标记为失败，返回更新的记录数
+    pub async fn fail_interrupted_runs(&self) -> Result<u64, String> {
+        let sql = "UPDATE migration_task_runs SET status = ?, error = ? WHERE status = ?";
+        let params = vec![
+            SqlValue::Text(status_text(&TaskStatus::Failed)?),
+            SqlValue::Text(TASK_INTERRUPTED.to_string()),
+            SqlValue::Text(status_text(&TaskStatus::Running)?),
+        ];
+        self.conn.execute_values(sql, params).await
+    }
+
+    // 任务的下一个执行序号
+    pub async fn next_run_number(&self, task_id: &str) -> Result<u64, String> {
+        let sql = "SELECT MAX(run_number) FROM migration_task_runs WHERE task_id = ?";
+        let result = self.conn.fetch_rows(sql, vec![SqlValue::Text(task_id.to_string())]).await?;
+        let last = result.rows.first().and_then(|row| row.first()).and_then(|v| v.as_i64()).unwrap_or(0);
+        Ok(last as u64 + 1)
+    }
+
+    // 写入执行记录
+    pub async fn save_run(&self, run: &TaskRun) -> Result<(), String> {
+        let sql = r#"
+            INSERT OR REPLACE INTO migration_task_runs
+            (id, task_id, run_number, status, rows_read, rows_written, rows_failed, bytes, masked_columns, error, started_at, ended_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#;
+        let params = vec![
+            SqlValue::Text(run.id.clone()),
+            SqlValue::Text(run.task_id.clone()),
+            SqlValue::Int(run.run_number as i64),
+            SqlValue::Text(status_text(&run.status)?),
+            SqlValue::Int(run.stats.rows_read as i64),
+            SqlValue::Int(run.stats.rows_written as i64),
+            SqlValue::Int(run.stats.rows_failed as i64),
+            SqlValue::Int(run.stats.bytes as i64),
+            if run.stats.masked_columns.is_empty() {
+                SqlValue::Null
+            } else {
+                SqlValue::Text(to_json(&run.stats.masked_columns)?)
+            },
+            run.error.clone().map(SqlValue::Text).unwrap_or(SqlValue::Null),
+            SqlValue::Int(to_millis(run.started_at)),
+            run.ended_at.map(|t| SqlValue::Int(to_millis(t))).unwrap_or(SqlValue::Null),
+        ];
+        self.conn.execute_values(sql, params).await?;
+        Ok(())
+    }
+
+    // 任务的全部执行记录，最近的在前
+    pub async fn list_runs(&self, task_id: &str) -> Result<Vec<TaskRun>, String> {
+        let sql = format!("{} WHERE task_id = ? ORDER BY run_number DESC", SELECT_RUNS_SQL);
+        let result = self.conn.fetch_rows(&sql, vec![SqlValue::Text(task_id.to_string())]).await?;
+        result.rows.iter().map(|row| decode_run(&result, row)).collect()
+    }
+
+    // 读取一条执行记录
+    pub async fn get_run(&self, run_id: &str) -> Result<Option<TaskRun>, String> {
+        let sql = format!("{} WHERE id = ?", SELECT_RUNS_SQL);
+        let result = self.conn.fetch_rows(&sql, vec![SqlValue::Text(run_id.to_string())]).await?;
+        result.rows.first().map(|row| decode_run(&result, row)).transpose()
+    }
+
+    // 写入任务
+    async fn save_task(&self, task: &MigrationTask) -> Result<(), String> {
+        let sql = r#"
+            INSERT OR REPLACE INTO migration_tasks
+            (id, name, description, source_db_id, target_db_id, strategy, status, progress, error,
+             cdc_checkpoint, verify_on_complete, verification, created_at, updated_at, started_at, completed_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#;
+        let optional_text = |value: Option<String>| value.map(SqlValue::Text).unwrap_or(SqlValue::Null);
+        let params = vec![
+            SqlValue::Text(task.id.clone()),
+            SqlValue::Text(task.name.clone()),
+            SqlValue::Text(task.description.clone()),
+            SqlValue::Text(task.source_db_id.clone()),
+            SqlValue::Text(task.target_db_id.clone()),
+            SqlValue::Text(to_json(&task.strategy)?),
+            SqlValue::Text(task.status_to_string()),
+            SqlValue::Text(to_json(&task.progress)?),
+            optional_text(task.error.clone()),
+            optional_text(task.cdc_checkpoint.as_ref().map(to_json).transpose()?),
+            SqlValue::Int(task.verify_on_complete as i64),
+            optional_text(task.verification.as_ref().map(to_json).transpose()?),
+            SqlValue::Int(to_millis(task.created_at)),
+            SqlValue::Int(to_millis(task.updated_at)),
+            task.started_at.map(|t| SqlValue::Int(to_millis(t))).unwrap_or(SqlValue::Null),
+            task.completed_at.map(|t| SqlValue::Int(to_millis(t))).unwrap_or(SqlValue::Null),
+        ];
+        self.conn.execute_values(sql, params).await?;
+        Ok(())
+    }
+}
+
+const SELECT_RUNS_SQL: &str = "SELECT id, task_id, run_number, status, rows_read, rows_written, rows_failed, bytes, masked_columns, error, started_at, ended_at FROM migration_task_runs";
+
+fn decode_run(result: &SqlRowSet, row: &[SqlValue]) -> Result<TaskRun, String> {
+    let text = |name: &str| result.get(row, name).and_then(|v| v.as_string());
+    let int = |name: &str| result.get(row, name).and_then(|v| v.as_i64());
+    let count = |name: &str| int(name).unwrap_or(0).max(0) as u64;
+
+    let status: TaskStatus = serde_json::from_value(JsonValue::String(text("status").unwrap_or_default()))
+        .map_err(|e| format!("Invalid run status: {}", e))?;
+    Ok(TaskRun {
+        id: text("id").ok_or_else(|| "Missing id".to_string())?,
+        task_id: text("task_id").ok_or_else(|| "Missing task_id".to_string())?,
+        run_number: count("run_number"),
+        status,
+        stats: RunStats {
+            rows_read: count("rows_read"),
+            rows_written: count("rows_written"),
+            rows_failed: count("rows_failed"),
+            bytes: count("bytes"),
+            masked_columns: text("masked_columns").map(|json| from_json(&json)).transpose()?.unwrap_or_default(),
+        },
+        error: text("error"),
+        started_at: int("started_at").map(from_millis).ok_or_else(|| "Missing started_at".to_string())?,
+        ended_at: int("ended_at").map(from_millis),
+    })
+}
+
+fn decode_task(result: &SqlRowSet, row: &[SqlValue]) -> Result<MigrationTask, String> {
+    let text = |name: &str| result.get(row, name).and_then(|v| v.as_string());
+    let time = |name: &str| result.get(row, name).and_then(|v| v.as_i64()).map(from_millis);
+    let required = |name: &str| text(name).ok_or_else(|| format!("Missing {}", name));
+
+    let status: TaskStatus = serde_json::from_value(JsonValue::String(required("status")?))
+        .map_err(|e| format!("Invalid status: {}", e))?;
+    let progress: TaskProgress = from_json(&required("progress")?)?;
+    Ok(MigrationTask {
+        id: required("id")?,
+        name: required("name")?,
+        description: text("description").unwrap_or_default(),
+        source_db_id: required("source_db_id")?,
+        target_db_id: required("target_db_id")?,
+        strategy: from_json(&required("strategy")?)?,
+        status,
+        progress,
+        error: text("error"),
+        created_at: time("created_at").ok_or_else(|| "Missing created_at".to_string())?,
+        updated_at: time("updated_at").ok_or_else(|| "Missing updated_at".to_string())?,
+        started_at: time("started_at"),
+        completed_at: time("completed_at"),
+        cdc_checkpoint: text("cdc_checkpoint").map(|json| from_json(&json)).transpose()?,
+        verify_on_complete: result.get(row, "verify_on_complete").and_then(|v| v.as_bool()).unwrap_or(false),
+        verification: text("verification").map(|json| from_json(&json)).transpose()?,
+    })
+}
+
+// 表中不存在该列时添加，用于升级旧版本创建的表
+pub async fn add_column_if_missing(conn: &SqlxDatabaseConnection, table: &str, column: &str, definition: &str) -> Result<(), String> {
+    let schema = conn.get_table_schema(table).await?
+        .ok_or_else(|| format!("Table not found: {}", table))?;
+    if schema.column(column).is_some() {
+        return Ok(());
+    }
+    let sql = format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition);
+    conn.execute_raw(&sql).await
+}
+
+// 状态按枚举名保存，与任务表一致
+fn status_text(status: &TaskStatus) -> Result<String, String> {
+    match serde_json::to_value(status) {
+        Ok(JsonValue::String(text)) => Ok(text),
+        _ => Err(format!("Failed to serialize status: {:?}", status)),
+    }
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+fn from_millis(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("Failed to serialize task: {}", e))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("Failed to deserialize task: {}", e))
+}
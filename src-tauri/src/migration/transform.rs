@@ -0,0 +1,962 @@
+// 行转换模块 - 在读取和写入之间按配置顺序对每一行执行内置的转换器
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sha2::Sha256;
+use crate::database::{ColumnKind, ColumnSchema, DatabaseSchema, DatabaseType, SqlRowSet, SqlValue, TableSchema};
+use crate::migration::cdc::ChangeEvent;
+use crate::migration::table_copy;
+
+// 类型转换的目标类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CastType {
+    Integer,
+    Float,
+    // 定点数按文本保存，避免精度损失
+    Decimal,
+    Text,
+    Bool,
+}
+
+fn default_separator() -> String {
+    " ".to_string()
+}
+
+fn default_redaction() -> String {
+    "***".to_string()
+}
+
+fn default_keep_end() -> usize {
+    4
+}
+
+fn default_mask_char() -> char {
+    '*'
+}
+
+// 列的脱敏策略，按 {policy, ...} 的形式与列名一起写在 mask 转换器的参数中。
+// 伪造和哈希的结果由加盐的HMAC决定，相同的原值总是得到相同的结果，跨表的关联关系得以保留。
+// 这些策略必须配置非空的salt：没有密钥时任何人都能用同样的算法对常见的值逐个计算，从结果反查出原值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum MaskPolicy {
+    // 整个值替换为固定文本
+    Redact {
+        #[serde(default = "default_redaction")]
+        replacement: String,
+    },
+    // 保留开头和结尾的若干字符，其余字符替换为掩码字符；值不长于保留的字符数时全部替换
+    Partial {
+        #[serde(default)]
+        keep_start: usize,
+        #[serde(default = "default_keep_end")]
+        keep_end: usize,
+        #[serde(default = "default_mask_char")]
+        mask_char: char,
+    },
+    // 保持用户名的长度和字符类别，域名统一替换为保留域名 example.com，避免向真实地址发信
+    FakeEmail { salt: String },
+    // 替换全部数字，保留 +、空格、括号和连字符等格式字符
+    FakePhone { salt: String },
+    // 每个词替换为虚构的姓名，保持词数和大小写
+    FakeName { salt: String },
+    // 加盐的HMAC-SHA256，输出十六进制文本
+    Hash { salt: String },
+    Null,
+}
+
+impl MaskPolicy {
+    // 加盐策略的salt，其他策略返回None
+    pub fn salt(&self) -> Option<&str> {
+        match self {
+            MaskPolicy::FakeEmail { salt }
+            | MaskPolicy::FakePhone { salt }
+            | MaskPolicy::FakeName { salt }
+            | MaskPolicy::Hash { salt } => Some(salt),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MaskPolicy::Redact { .. } => "redact",
+            MaskPolicy::Partial { .. } => "partial",
+            MaskPolicy::FakeEmail { .. } => "fake_email",
+            MaskPolicy::FakePhone { .. } => "fake_phone",
+            MaskPolicy::FakeName { .. } => "fake_name",
+            MaskPolicy::Hash { .. } => "hash",
+            MaskPolicy::Null => "null",
+        }
+    }
+}
+
+// 本次执行中被脱敏的一列
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaskedColumn {
+    pub table: String,
+    pub column: String,
+    pub policy: String,
+}
+
+// 转换器的名称和参数，按 {name, params} 的形式序列化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "name", content = "params", rename_all = "snake_case")]
+pub enum TransformKind {
+    // 把列值转换为指定类型，无法转换时报错
+    Cast { column: String, to: CastType },
+    // 去掉文本首尾的空白
+    Trim { column: String },
+    Lowercase { column: String },
+    // 按正则表达式替换全部匹配，replacement 中可用 $1 引用分组
+    RegexReplace { column: String, pattern: String, replacement: String },
+    // 值为NULL时改为给定的值
+    DefaultIfNull { column: String, value: SqlValue },
+    // 按 from 解析日期时间后按 to 重新格式化（chrono格式），from 为空时识别常见的ISO格式
+    DateFormat {
+        column: String,
+        #[serde(default)]
+        from: Option<String>,
+        to: String,
+    },
+    // 把多列用分隔符拼接后写入 target 列，NULL值跳过，全部为NULL时结果为NULL
+    Concat {
+        columns: Vec<String>,
+        #[serde(default)]
+        separator: String,
+        target: String,
+    },
+    // 按分隔符拆分后依次写入 targets 列，最后一列保留剩余部分，不足的列为NULL
+    Split {
+        column: String,
+        #[serde(default = "default_separator")]
+        separator: String,
+        targets: Vec<String>,
+    },
+    // 按JSON路径（如 $.address.city 或 $.items[0].sku）取值，target 为空时写回原列
+    JsonPath {
+        column: String,
+        path: String,
+        #[serde(default)]
+        target: Option<String>,
+    },
+    // 按脱敏策略改写列值，NULL保持不变
+    Mask {
+        column: String,
+        #[serde(flatten)]
+        policy: MaskPolicy,
+    },
+}
+
+// 任务上配置的一个转换器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transformer {
+    // 只对该源表生效，为空时对所有包含相关列的表生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    #[serde(flatten)]
+    pub kind: TransformKind,
+}
+
+impl TransformKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransformKind::Cast { .. } => "cast",
+            TransformKind::Trim { .. } => "trim",
+            TransformKind::Lowercase { .. } => "lowercase",
+            TransformKind::RegexReplace { .. } => "regex_replace",
+            TransformKind::DefaultIfNull { .. } => "default_if_null",
+            TransformKind::DateFormat { .. } => "date_format",
+            TransformKind::Concat { .. } => "concat",
+            TransformKind::Split { .. } => "split",
+            TransformKind::JsonPath { .. } => "json_path",
+            TransformKind::Mask { .. } => "mask",
+        }
+    }
+
+    // 读取的列
+    fn inputs(&self) -> Vec<&str> {
+        match self {
+            TransformKind::Concat { columns, .. } => columns.iter().map(String::as_str).collect(),
+            TransformKind::Cast { column, .. }
+            | TransformKind::Trim { column }
+            | TransformKind::Lowercase { column }
+            | TransformKind::RegexReplace { column, .. }
+            | TransformKind::DefaultIfNull { column, .. }
+            | TransformKind::DateFormat { column, .. }
+            | TransformKind::Split { column, .. }
+            | TransformKind::JsonPath { column, .. }
+            | TransformKind::Mask { column, .. } => vec![column.as_str()],
+        }
+    }
+
+    // 写入的列，不存在时作为新列追加到行末
+    fn outputs(&self) -> Vec<&str> {
+        match self {
+            TransformKind::Concat { target, .. } => vec![target.as_str()],
+            TransformKind::Split { targets, .. } => targets.iter().map(String::as_str).collect(),
+            TransformKind::JsonPath { column, target, .. } => vec![target.as_deref().unwrap_or(column)],
+            other => other.inputs(),
+        }
+    }
+}
+
+// JSON路径的一段
+#[derive(Debug, Clone)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+// 编译后的转换器：列已解析为行中的位置
+#[derive(Debug, Clone)]
+struct Step {
+    kind: TransformKind,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    regex: Option<Regex>,
+    path: Vec<PathSegment>,
+}
+
+// 一张表（或一条查询）的转换链，输入为读取的列，输出为转换后的列：读取的列在前，转换器新增的列在后
+#[derive(Debug, Clone)]
+pub struct RowTransform {
+    steps: Vec<Step>,
+    columns: Vec<String>,
+    // 输出列是否被转换器改写或新增
+    modified: Vec<bool>,
+}
+
+impl RowTransform {
+    // 为源表编译转换链：指定了其他表的转换器忽略；未指定表的转换器缺少列时跳过，指定了本表的缺少列时报错。
+    // table为空表示自定义查询，全部转换器都生效且列必须存在
+    pub fn new(transformers: &[Transformer], table: Option<&str>, columns: &[String]) -> Result<Self, String> {
+        let mut columns = columns.to_vec();
+        let mut modified = vec![false; columns.len()];
+        let mut steps = Vec::new();
+        for transformer in transformers {
+            let scoped = match (&transformer.table, table) {
+                (Some(name), Some(table)) if !name.eq_ignore_ascii_case(table) => continue,
+                (Some(_), Some(_)) | (_, None) => true,
+                (None, Some(_)) => false,
+            };
+            let kind = &transformer.kind;
+            let find = |columns: &[String], name: &str| columns.iter().position(|c| c.eq_ignore_ascii_case(name));
+            let mut inputs = Vec::new();
+            for name in kind.inputs() {
+                match find(&columns, name) {
+                    Some(index) => inputs.push(index),
+                    None if scoped => {
+                        return Err(format!(
+                            "Transformer {} references column {} which is not in {}",
+                            kind.name(),
+                            name,
+                            table.unwrap_or("the query result")
+                        ))
+                    }
+                    None => break,
+                }
+            }
+            if inputs.len() < kind.inputs().len() {
+                continue;
+            }
+            let mut outputs = Vec::new();
+            for name in kind.outputs() {
+                let index = match find(&columns, name) {
+                    Some(index) => index,
+                    None => {
+                        columns.push(name.to_string());
+                        modified.push(true);
+                        columns.len() - 1
+                    }
+                };
+                modified[index] = true;
+                outputs.push(index);
+            }
+            let (regex, path) = compile(kind)?;
+            steps.push(Step { kind: kind.clone(), inputs, outputs, regex, path });
+        }
+        Ok(Self { steps, columns, modified })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    // 转换后的列名
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    // 输出列是否由转换器改写或新增
+    pub fn is_modified(&self, index: usize) -> bool {
+        self.modified.get(index).copied().unwrap_or(false)
+    }
+
+    // 由mask转换器脱敏的列，table为报告中使用的表名
+    pub fn masked_columns(&self, table: &str) -> Vec<MaskedColumn> {
+        self.steps
+            .iter()
+            .filter_map(|step| match &step.kind {
+                TransformKind::Mask { policy, .. } => Some(MaskedColumn {
+                    table: table.to_string(),
+                    column: self.columns[step.outputs[0]].clone(),
+                    policy: policy.name().to_string(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // 按顺序执行转换链，返回与columns()对应的一行
+    pub fn apply(&self, mut row: Vec<SqlValue>) -> Result<Vec<SqlValue>, String> {
+        row.resize(self.columns.len(), SqlValue::Null);
+        for step in &self.steps {
+            step.apply(&mut row).map_err(|e| {
+                format!("Transformer {} failed on column {}: {}", step.kind.name(), self.columns[step.inputs[0]], e)
+            })?;
+        }
+        Ok(row)
+    }
+
+    // 转换查询结果的全部行，结果集的列换成转换后的列
+    pub fn apply_to_rows(&self, rows: &mut SqlRowSet) -> Result<(), String> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        for row in rows.rows.iter_mut() {
+            *row = self.apply(std::mem::take(row))?;
+        }
+        rows.columns = self.columns.clone();
+        Ok(())
+    }
+}
+
+// 检查转换器的参数并预编译正则表达式和JSON路径
+fn compile(kind: &TransformKind) -> Result<(Option<Regex>, Vec<PathSegment>), String> {
+    let mut regex = None;
+    let mut path = Vec::new();
+    match kind {
+        TransformKind::RegexReplace { pattern, .. } => {
+            regex = Some(Regex::new(pattern).map_err(|e| format!("Invalid regex pattern {}: {}", pattern, e))?);
+        }
+        TransformKind::DateFormat { from, to, .. } => {
+            for format in from.iter().chain(std::iter::once(to)) {
+                if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                    return Err(format!("Invalid date format: {}", format));
+                }
+            }
+        }
+        TransformKind::Concat { columns, .. } if columns.is_empty() => {
+            return Err("Transformer concat needs at least one column".to_string());
+        }
+        TransformKind::Split { separator, targets, .. } if separator.is_empty() || targets.is_empty() => {
+            return Err("Transformer split needs a separator and at least one target column".to_string());
+        }
+        TransformKind::JsonPath { path: text, .. } => path = parse_path(text)?,
+        TransformKind::Mask { policy, .. } if policy.salt().is_some_and(str::is_empty) => {
+            return Err(format!("Mask policy {} needs a non-empty salt", policy.name()));
+        }
+        _ => {}
+    }
+    Ok((regex, path))
+}
+
+// 按转换器推断源表转换后的结构，用于生成建表DDL：类型转换的列换成目标类型，新增的列按文本类型追加
+pub fn transform_schema(transformers: &[Transformer], schema: &DatabaseSchema) -> DatabaseSchema {
+    DatabaseSchema {
+        db_type: schema.db_type.clone(),
+        tables: schema.tables.iter().map(|table| transform_table(transformers, &schema.db_type, table)).collect(),
+    }
+}
+
+fn transform_table(transformers: &[Transformer], db_type: &DatabaseType, table: &TableSchema) -> TableSchema {
+    let names: Vec<String> = table.columns.iter().map(|c| c.name.clone()).collect();
+    // 转换链无法编译时保持原结构，执行时会报告错误
+    let transform = match RowTransform::new(transformers, Some(&table.name), &names) {
+        Ok(transform) if !transform.is_empty() => transform,
+        _ => return table.clone(),
+    };
+    let mut columns = table.columns.clone();
+    for name in &transform.columns()[names.len()..] {
+        columns.push(ColumnSchema::new(db_type, name.clone(), "TEXT".to_string(), true));
+    }
+    for step in &transform.steps {
+        let column = &mut columns[step.outputs[0]];
+        let type_name = match &step.kind {
+            TransformKind::Cast { to, .. } => cast_type_name(db_type, *to),
+            // 置空的列须允许NULL
+            TransformKind::Mask { policy: MaskPolicy::Null, .. } => {
+                column.nullable = true;
+                continue;
+            }
+            // 伪造的电话号码保持原类型，其他策略的结果为文本，长度可能超过原列
+            TransformKind::Mask { policy, .. } if !matches!(policy, MaskPolicy::FakePhone { .. }) => "TEXT",
+            _ => continue,
+        };
+        *column = ColumnSchema {
+            default_value: None,
+            auto_increment: false,
+            ..ColumnSchema::new(db_type, column.name.clone(), type_name.to_string(), column.nullable)
+        };
+    }
+    TableSchema { columns, ..table.clone() }
+}
+
+// 类型转换的目标类型在源库方言下的声明类型
+fn cast_type_name(db_type: &DatabaseType, to: CastType) -> &'static str {
+    match (to, db_type) {
+        (CastType::Integer, DatabaseType::Sqlite) => "INTEGER",
+        (CastType::Integer, _) => "BIGINT",
+        (CastType::Float, DatabaseType::PostgreSql) => "DOUBLE PRECISION",
+        (CastType::Float, DatabaseType::MySql) => "DOUBLE",
+        (CastType::Float, _) => "REAL",
+        (CastType::Decimal, DatabaseType::Sqlite) => "NUMERIC",
+        (CastType::Decimal, _) => "DECIMAL(38, 10)",
+        (CastType::Bool, DatabaseType::MySql) => "TINYINT(1)",
+        (CastType::Bool, _) => "BOOLEAN",
+        (CastType::Text, _) => "TEXT",
+    }
+}
+
+// 对变更事件执行转换链，按表和列缓存编译后的转换链
+pub struct EventTransform<'a> {
+    transformers: &'a [Transformer],
+    compiled: HashMap<(String, Vec<String>), RowTransform>,
+}
+
+impl<'a> EventTransform<'a> {
+    pub fn new(transformers: &'a [Transformer]) -> Self {
+        Self {
+            transformers,
+            compiled: HashMap::new(),
+        }
+    }
+
+    pub fn apply(&mut self, event: ChangeEvent) -> Result<ChangeEvent, String> {
+        if self.transformers.is_empty() {
+            return Ok(event);
+        }
+        let before = event.before.map(|row| self.apply_row(&event.table, row)).transpose()?;
+        let after = event.after.map(|row| self.apply_row(&event.table, row)).transpose()?;
+        Ok(ChangeEvent { before, after, ..event })
+    }
+
+    fn apply_row(&mut self, table: &str, row: Vec<(String, SqlValue)>) -> Result<Vec<(String, SqlValue)>, String> {
+        let (names, values): (Vec<String>, Vec<SqlValue>) = row.into_iter().unzip();
+        let key = (table.to_string(), names);
+        if !self.compiled.contains_key(&key) {
+            let transform = RowTransform::new(self.transformers, Some(table), &key.1)?;
+            self.compiled.insert(key.clone(), transform);
+        }
+        let transform = &self.compiled[&key];
+        let values = transform.apply(values)?;
+        Ok(transform.columns().iter().cloned().zip(values).collect())
+    }
+
+    // 已处理的事件中被脱敏的列
+    pub fn masked_columns(&self) -> Vec<MaskedColumn> {
+        self.compiled
+            .iter()
+            .flat_map(|((table, _), transform)| transform.masked_columns(table))
+            .collect()
+    }
+}
+
+// 源表中被转换器改写的列，两端的值不再相同，校验和差异比较时跳过
+pub fn transformed_columns(transformers: &[Transformer], table: &TableSchema) -> Result<Vec<String>, String> {
+    let names: Vec<String> = table.columns.iter().map(|c| c.name.clone()).collect();
+    let transform = RowTransform::new(transformers, Some(&table.name), &names)?;
+    Ok(names.into_iter().enumerate().filter(|(i, _)| transform.is_modified(*i)).map(|(_, name)| name).collect())
+}
+
+// 检查全部转换器的参数：正则表达式、日期格式和JSON路径能够编译，拼接和拆分的列不为空
+pub fn check_transformers(transformers: &[Transformer]) -> Result<(), String> {
+    for transformer in transformers {
+        compile(&transformer.kind)?;
+    }
+    Ok(())
+}
+
+// 按源库结构校验转换器，指定了表的转换器还需引用迁移范围内的源表和其中的列；tables为空时为源库全部表
+pub fn validate_transformers(transformers: &[Transformer], schema: &DatabaseSchema, tables: &[String]) -> Result<(), String> {
+    check_transformers(transformers)?;
+    for transformer in transformers {
+        if let Some(name) = &transformer.table {
+            let table = schema
+                .table(name)
+                .ok_or_else(|| format!("Transformed source table not found: {}", name))?;
+            if !tables.is_empty() && !tables.iter().any(|t| t.eq_ignore_ascii_case(&table.name)) {
+                return Err(format!("Transformed table {} is not migrated by this task", table.name));
+            }
+            let columns: Vec<String> = table.columns.iter().map(|c| c.name.clone()).collect();
+            RowTransform::new(transformers, Some(&table.name), &columns)?;
+        }
+    }
+    Ok(())
+}
+
+impl Step {
+    fn apply(&self, row: &mut [SqlValue]) -> Result<(), String> {
+        let input = row[self.inputs[0]].clone();
+        match &self.kind {
+            TransformKind::Cast { to, .. } => row[self.outputs[0]] = cast(input, *to)?,
+            TransformKind::Trim { .. } => row[self.outputs[0]] = map_text(input, |s| s.trim().to_string()),
+            TransformKind::Lowercase { .. } => row[self.outputs[0]] = map_text(input, |s| s.to_lowercase()),
+            TransformKind::RegexReplace { replacement, .. } => {
+                if let Some(regex) = &self.regex {
+                    row[self.outputs[0]] = map_text(input, |s| regex.replace_all(&s, replacement.as_str()).into_owned());
+                }
+            }
+            TransformKind::DefaultIfNull { value, .. } => {
+                if input.is_null() {
+                    row[self.outputs[0]] = value.clone();
+                }
+            }
+            TransformKind::DateFormat { from, to, .. } => {
+                row[self.outputs[0]] = match input.as_string() {
+                    Some(text) => SqlValue::Text(reformat_date(&text, from.as_deref(), to)?),
+                    None => SqlValue::Null,
+                };
+            }
+            TransformKind::Concat { separator, .. } => {
+                let parts: Vec<String> = self.inputs.iter().filter_map(|&i| row[i].as_string()).collect();
+                row[self.outputs[0]] = if parts.is_empty() {
+                    SqlValue::Null
+                } else {
+                    SqlValue::Text(parts.join(separator))
+                };
+            }
+            TransformKind::Split { separator, .. } => {
+                let parts: Vec<String> = match input.as_string() {
+                    Some(text) => text.splitn(self.outputs.len(), separator.as_str()).map(str::to_string).collect(),
+                    None => Vec::new(),
+                };
+                for (i, &output) in self.outputs.iter().enumerate() {
+                    row[output] = parts.get(i).cloned().map(SqlValue::Text).unwrap_or(SqlValue::Null);
+                }
+            }
+            TransformKind::JsonPath { .. } => {
+                row[self.outputs[0]] = match input.as_string() {
+                    Some(text) => {
+                        let json: JsonValue = serde_json::from_str(&text).map_err(|e| format!("invalid JSON: {}", e))?;
+                        extract_path(&json, &self.path).map(json_to_value).unwrap_or(SqlValue::Null)
+                    }
+                    None => SqlValue::Null,
+                };
+            }
+            TransformKind::Mask { policy, .. } => row[self.outputs[0]] = mask(input, policy),
+        }
+        Ok(())
+    }
+}
+
+// 只改写文本值，其他非空值先转换为文本
+fn map_text(value: SqlValue, f: impl Fn(String) -> String) -> SqlValue {
+    match value {
+        SqlValue::Null | SqlValue::Bytes(_) => value,
+        other => other.as_string().map(|s| SqlValue::Text(f(s))).unwrap_or(SqlValue::Null),
+    }
+}
+
+fn cast(value: SqlValue, to: CastType) -> Result<SqlValue, String> {
+    if value.is_null() {
+        return Ok(SqlValue::Null);
+    }
+    let kind = match to {
+        CastType::Integer => ColumnKind::Integer,
+        CastType::Float => ColumnKind::Float,
+        CastType::Decimal => ColumnKind::Decimal,
+        CastType::Text => ColumnKind::Text,
+        CastType::Bool => ColumnKind::Bool,
+    };
+    let original = value.clone();
+    let cast = match (to, table_copy::coerce_value(value, kind)) {
+        (CastType::Integer, SqlValue::Int(i)) => Some(SqlValue::Int(i)),
+        // 整数值的浮点文本（如 "12.0"）也可以转换为整数
+        (CastType::Integer, other) => other
+            .as_string()
+            .and_then(|s| s.trim().parse::<f64>().ok())
+            .filter(|f| f.fract() == 0.0 && f.abs() < i64::MAX as f64)
+            .map(|f| SqlValue::Int(f as i64)),
+        (CastType::Float, SqlValue::Float(f)) => Some(SqlValue::Float(f)),
+        (CastType::Bool, SqlValue::Bool(b)) => Some(SqlValue::Bool(b)),
+        (CastType::Decimal, SqlValue::Text(s)) => s.trim().parse::<f64>().ok().map(|_| SqlValue::Text(s.trim().to_string())),
+        (CastType::Text, text @ SqlValue::Text(_)) => Some(text),
+        _ => None,
+    };
+    cast.ok_or_else(|| format!("cannot cast {:?} to {:?}", original, to))
+}
+
+// 伪造姓名时使用的名和姓
+const FIRST_NAMES: &[&str] = &[
+    "James", "Mary", "John", "Linda", "Robert", "Susan", "Michael", "Karen", "David", "Lisa",
+    "Daniel", "Emma", "Thomas", "Anna", "Paul", "Laura", "Mark", "Sarah", "Peter", "Julia",
+];
+const LAST_NAMES: &[&str] = &[
+    "Smith", "Brown", "Taylor", "Wilson", "Clark", "Walker", "Hall", "Young", "King", "Wright",
+    "Green", "Baker", "Adams", "Nelson", "Carter", "Mitchell", "Turner", "Parker", "Collins", "Morris",
+];
+// 中文姓名按字替换：第一个字换成姓，其余换成名中常用的字
+const CHINESE_SURNAMES: &[char] = &['王', '李', '张', '刘', '陈', '杨', '赵', '黄', '周', '吴', '徐', '孙', '马', '朱', '胡', '郭'];
+const CHINESE_GIVEN: &[char] = &['伟', '芳', '娜', '敏', '静', '丽', '强', '磊', '洋', '艳', '勇', '军', '杰', '娟', '涛', '明', '超', '霞', '平', '刚'];
+
+fn mask(value: SqlValue, policy: &MaskPolicy) -> SqlValue {
+    let text = match value.as_string() {
+        Some(text) => text,
+        None => return SqlValue::Null,
+    };
+    match policy {
+        MaskPolicy::Redact { replacement } => SqlValue::Text(replacement.clone()),
+        MaskPolicy::Partial { keep_start, keep_end, mask_char } => {
+            let chars: Vec<char> = text.chars().collect();
+            let masked: String = if keep_start + keep_end >= chars.len() {
+                chars.iter().map(|_| *mask_char).collect()
+            } else {
+                chars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| if i < *keep_start || i >= chars.len() - keep_end { *c } else { *mask_char })
+                    .collect()
+            };
+            SqlValue::Text(masked)
+        }
+        MaskPolicy::FakeEmail { salt } => {
+            let local = text.rsplit_once('@').map(|(local, _)| local).unwrap_or(&text);
+            let bytes = keyed_bytes(salt, "email", &text, local.chars().count());
+            SqlValue::Text(format!("{}@example.com", scramble(local, &bytes)))
+        }
+        MaskPolicy::FakePhone { salt } => {
+            let bytes = keyed_bytes(salt, "phone", &text, text.len());
+            let mut leading = true;
+            let phone: String = text
+                .chars()
+                .zip(&bytes)
+                .map(|(c, b)| match c.to_digit(10) {
+                    Some(d) => {
+                        // 首位数字不为零时仍不为零，保持号码的位数
+                        let nonzero = leading && d != 0;
+                        leading = false;
+                        char::from(if nonzero { b'1' + b % 9 } else { b'0' + b % 10 })
+                    }
+                    None => c,
+                })
+                .collect();
+            match value {
+                SqlValue::Int(_) => phone.parse().map(SqlValue::Int).unwrap_or(SqlValue::Text(phone)),
+                _ => SqlValue::Text(phone),
+            }
+        }
+        MaskPolicy::FakeName { salt } => SqlValue::Text(fake_name(salt, &text)),
+        MaskPolicy::Hash { salt } => SqlValue::Text(keyed_bytes(salt, "hash", &text, 32).iter().map(|b| format!("{:02x}", b)).collect()),
+        MaskPolicy::Null => SqlValue::Null,
+    }
+}
+
+// 以盐为密钥对原值计算HMAC-SHA256，按计数器扩展到需要的字节数；purpose区分不同策略，同一原值在各策略下的结果互不相关
+fn keyed_bytes(salt: &str, purpose: &str, text: &str, len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    let mut counter = 0u32;
+    while bytes.len() < len {
+        let mut mac = Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(purpose.as_bytes());
+        mac.update(&counter.to_be_bytes());
+        mac.update(text.as_bytes());
+        bytes.extend_from_slice(&mac.finalize().into_bytes());
+        counter += 1;
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+// 按字符类别替换：字母换成同样大小写的字母，数字换成数字，其他字符保留；非ASCII字母换成小写字母
+fn scramble(text: &str, bytes: &[u8]) -> String {
+    text.chars()
+        .zip(bytes)
+        .map(|(c, b)| match c {
+            'A'..='Z' => char::from(b'A' + b % 26),
+            '0'..='9' => char::from(b'0' + b % 10),
+            _ if c.is_alphabetic() => char::from(b'a' + b % 26),
+            _ => c,
+        })
+        .collect()
+}
+
+fn fake_name(salt: &str, text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let bytes = keyed_bytes(salt, "name", text, chars.len() * 2);
+    let pick = |i: usize, len: usize| usize::from(u16::from_be_bytes([bytes[i * 2], bytes[i * 2 + 1]])) % len;
+    let mut output = String::new();
+    let mut words = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphabetic() {
+            output.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && chars[i].is_alphabetic() {
+            i += 1;
+        }
+        let word = &chars[start..i];
+        if word.iter().all(|c| c.is_ascii()) {
+            let names = if words == 0 { FIRST_NAMES } else { LAST_NAMES };
+            let name = names[pick(start, names.len())];
+            let upper = word.len() > 1 && word.iter().all(|c| c.is_uppercase());
+            let lower = word.iter().all(|c| c.is_lowercase());
+            output.push_str(&match (upper, lower) {
+                (true, _) => name.to_uppercase(),
+                (_, true) => name.to_lowercase(),
+                _ => name.to_string(),
+            });
+        } else {
+            // 中文姓名保持字数
+            for j in start..i {
+                let pool = if words == 0 && j == start { CHINESE_SURNAMES } else { CHINESE_GIVEN };
+                output.push(pool[pick(j, pool.len())]);
+            }
+        }
+        words += 1;
+    }
+    output
+}
+
+// 未指定输入格式时依次尝试的带时区和不带时区的格式
+const OFFSET_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f%#z", "%Y-%m-%dT%H:%M:%S%.f%#z"];
+const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+// 解析后的日期时间，带时区的值格式化时可以输出时区
+enum ParsedDate {
+    Offset(DateTime<FixedOffset>),
+    Naive(NaiveDateTime),
+}
+
+fn parse_date(text: &str, from: Option<&str>) -> Option<ParsedDate> {
+    let text = text.trim();
+    let (offset_formats, naive_formats): (Vec<&str>, Vec<&str>) = match from {
+        Some(format) => (vec![format], vec![format]),
+        None => (OFFSET_FORMATS.to_vec(), NAIVE_FORMATS.to_vec()),
+    };
+    if let Some(parsed) = offset_formats.iter().find_map(|f| DateTime::parse_from_str(text, f).ok()) {
+        return Some(ParsedDate::Offset(parsed));
+    }
+    if let Some(parsed) = naive_formats.iter().find_map(|f| NaiveDateTime::parse_from_str(text, f).ok()) {
+        return Some(ParsedDate::Naive(parsed));
+    }
+    // 只有日期的值按当天零点处理
+    NaiveDate::parse_from_str(text, from.unwrap_or("%Y-%m-%d"))
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(ParsedDate::Naive)
+}
+
+fn reformat_date(text: &str, from: Option<&str>, to: &str) -> Result<String, String> {
+    let parsed = parse_date(text, from).ok_or_else(|| match from {
+        Some(format) => format!("cannot parse {:?} with format {}", text, format),
+        None => format!("cannot parse {:?} as a date", text),
+    })?;
+    // 格式中含有值不具备的字段（如无时区的值输出 %z）时格式化会失败
+    let mut output = String::new();
+    let written = match parsed {
+        ParsedDate::Offset(date) => write!(output, "{}", date.format(to)),
+        ParsedDate::Naive(date) => write!(output, "{}", date.format(to)),
+    };
+    written.map_err(|_| format!("cannot format {:?} with format {}", text, to))?;
+    Ok(output)
+}
+
+// 解析 $.a.b[0]、a.b.0 或 $['a'] 形式的JSON路径
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let invalid = || format!("Invalid JSON path: {}", path);
+    let chars: Vec<char> = path.trim().chars().collect();
+    let mut i = usize::from(chars.first() == Some(&'$'));
+    let mut segments = Vec::new();
+    while i < chars.len() {
+        match chars[i] {
+            '.' => i += 1,
+            '[' => {
+                let end = chars[i..].iter().position(|&c| c == ']').map(|p| i + p).ok_or_else(invalid)?;
+                let inner: String = chars[i + 1..end].iter().collect();
+                let inner = inner.trim();
+                let quoted = inner.len() >= 2
+                    && ((inner.starts_with('\'') && inner.ends_with('\'')) || (inner.starts_with('"') && inner.ends_with('"')));
+                if quoted {
+                    segments.push(PathSegment::Key(inner[1..inner.len() - 1].to_string()));
+                } else {
+                    segments.push(PathSegment::Index(inner.parse().map_err(|_| invalid())?));
+                }
+                i = end + 1;
+            }
+            _ => {
+                let end = chars[i..].iter().position(|&c| c == '.' || c == '[').map(|p| i + p).unwrap_or(chars.len());
+                let key: String = chars[i..end].iter().collect();
+                segments.push(match key.parse::<usize>() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Key(key),
+                });
+                i = end;
+            }
+        }
+    }
+    Ok(segments)
+}
+
+fn extract_path<'a>(json: &'a JsonValue, path: &[PathSegment]) -> Option<&'a JsonValue> {
+    path.iter().try_fold(json, |value, segment| match segment {
+        PathSegment::Key(key) => value.get(key),
+        // 数字段也可以是对象的键
+        PathSegment::Index(index) => value.get(index).or_else(|| value.get(index.to_string())),
+    })
+}
+
+// JSON值转换为单元格值，对象和数组保留为JSON文本
+fn json_to_value(value: &JsonValue) -> SqlValue {
+    match value {
+        JsonValue::Null => SqlValue::Null,
+        JsonValue::Bool(b) => SqlValue::Bool(*b),
+        JsonValue::Number(n) => n
+            .as_i64()
+            .map(SqlValue::Int)
+            .or_else(|| n.as_f64().map(SqlValue::Float))
+            .unwrap_or_else(|| SqlValue::Text(n.to_string())),
+        JsonValue::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transformers(value: JsonValue) -> Result<Vec<Transformer>, String> {
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    fn compile_mask(policy: JsonValue) -> Result<RowTransform, String> {
+        let mut params = serde_json::json!({"column": "secret"});
+        params.as_object_mut().unwrap().extend(policy.as_object().unwrap().clone());
+        let transformers = transformers(serde_json::json!([{"name": "mask", "params": params}]))?;
+        RowTransform::new(&transformers, Some("people"), &["secret".to_string()])
+    }
+
+    #[test]
+    fn keyed_policies_need_a_salt() {
+        for policy in ["fake_email", "fake_phone", "fake_name", "hash"] {
+            let missing = compile_mask(serde_json::json!({"policy": policy})).err().unwrap();
+            assert!(missing.contains("missing field `salt`"), "{}: {}", policy, missing);
+            let empty = compile_mask(serde_json::json!({"policy": policy, "salt": ""})).err().unwrap();
+            assert_eq!(empty, format!("Mask policy {} needs a non-empty salt", policy));
+            assert!(compile_mask(serde_json::json!({"policy": policy, "salt": "pepper"})).is_ok());
+        }
+        for policy in ["redact", "partial", "null"] {
+            assert!(compile_mask(serde_json::json!({"policy": policy})).is_ok(), "{}", policy);
+        }
+    }
+
+    fn masked(policy: JsonValue, value: SqlValue) -> SqlValue {
+        compile_mask(policy).unwrap().apply(vec![value]).unwrap().remove(0)
+    }
+
+    fn text(value: SqlValue) -> String {
+        value.as_string().unwrap()
+    }
+
+    #[test]
+    fn masks_with_fixed_policies() {
+        let redact = serde_json::json!({"policy": "redact"});
+        assert_eq!(masked(redact.clone(), SqlValue::Text("alice".to_string())), SqlValue::Text(default_redaction()));
+        assert_eq!(masked(redact, SqlValue::Null), SqlValue::Null);
+        let partial = serde_json::json!({"policy": "partial", "keep_start": 2, "keep_end": 2, "mask_char": "#"});
+        assert_eq!(masked(partial.clone(), SqlValue::Text("13800138000".to_string())), SqlValue::Text("13#######00".to_string()));
+        assert_eq!(masked(partial, SqlValue::Text("abc".to_string())), SqlValue::Text("###".to_string()));
+        assert_eq!(masked(serde_json::json!({"policy": "null"}), SqlValue::Int(7)), SqlValue::Null);
+    }
+
+    #[test]
+    fn keyed_policies_are_deterministic_per_salt() {
+        let email = SqlValue::Text("Alice.Smith42@corp.io".to_string());
+        for policy in ["fake_email", "fake_phone", "fake_name", "hash"] {
+            let value = SqlValue::Text("Alice Smith 42".to_string());
+            let first = masked(serde_json::json!({"policy": policy, "salt": "pepper"}), value.clone());
+            let again = masked(serde_json::json!({"policy": policy, "salt": "pepper"}), value.clone());
+            let other = masked(serde_json::json!({"policy": policy, "salt": "paprika"}), value.clone());
+            assert_eq!(first, again, "{}", policy);
+            assert_ne!(first, other, "{}", policy);
+            assert_ne!(first, value, "{}", policy);
+        }
+        let fake = text(masked(serde_json::json!({"policy": "fake_email", "salt": "pepper"}), email));
+        let (local, domain) = fake.split_once('@').unwrap();
+        assert_eq!(domain, "example.com");
+        assert_eq!(local.len(), "Alice.Smith42".len());
+        assert_eq!(&local[5..6], ".");
+        assert!(local[..1].chars().all(|c| c.is_ascii_uppercase()));
+        assert!(local[11..].chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn fake_values_keep_their_shape() {
+        let salt = serde_json::json!({"policy": "fake_phone", "salt": "pepper"});
+        let phone = text(masked(salt, SqlValue::Text("+86 (138) 0013-8000".to_string())));
+        assert_eq!(phone.len(), "+86 (138) 0013-8000".len());
+        assert!(phone.starts_with('+') && !phone.starts_with("+0"));
+        assert_eq!(phone.chars().filter(|c| !c.is_ascii_digit()).collect::<String>(), "+ () -");
+        let number = masked(serde_json::json!({"policy": "fake_phone", "salt": "pepper"}), SqlValue::Int(13800138000));
+        assert!(matches!(number, SqlValue::Int(n) if (10_000_000_000..100_000_000_000).contains(&n)));
+
+        let name = text(masked(serde_json::json!({"policy": "fake_name", "salt": "pepper"}), SqlValue::Text("JOHN doe".to_string())));
+        let (first, last) = name.split_once(' ').unwrap();
+        assert!(FIRST_NAMES.iter().any(|n| n.to_uppercase() == first), "{}", name);
+        assert!(LAST_NAMES.iter().any(|n| n.to_lowercase() == last), "{}", name);
+        let chinese = text(masked(serde_json::json!({"policy": "fake_name", "salt": "pepper"}), SqlValue::Text("欧阳娜娜".to_string())));
+        assert_eq!(chinese.chars().count(), 4);
+        assert!(CHINESE_SURNAMES.contains(&chinese.chars().next().unwrap()));
+
+        let hash = text(masked(serde_json::json!({"policy": "hash", "salt": "pepper"}), SqlValue::Int(42)));
+        assert_eq!(hash.len(), 64);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn applies_transformer_chain_in_order() {
+        let transformers = transformers(serde_json::json!([
+            {"name": "trim", "params": {"column": "email"}},
+            {"name": "lowercase", "params": {"column": "email"}},
+            {"name": "split", "params": {"column": "email", "separator": "@", "targets": ["user", "domain"]}},
+            {"name": "concat", "params": {"columns": ["domain", "id"], "separator": "/", "target": "ref"}},
+            {"name": "default_if_null", "params": {"column": "note", "value": "n/a"}},
+            {"name": "mask", "table": "other", "params": {"column": "email", "policy": "null"}},
+        ]))
+        .unwrap();
+        let columns = ["id".to_string(), "email".to_string(), "note".to_string()];
+        let transform = RowTransform::new(&transformers, Some("people"), &columns).unwrap();
+        assert_eq!(transform.columns(), ["id", "email", "note", "user", "domain", "ref"]);
+        assert!(!transform.is_modified(0));
+        assert!(transform.is_modified(1) && transform.is_modified(5));
+        assert!(transform.masked_columns("people").is_empty());
+        let row = transform
+            .apply(vec![SqlValue::Int(7), SqlValue::Text("  Bob@Example.COM ".to_string()), SqlValue::Null])
+            .unwrap();
+        assert_eq!(
+            row.iter().map(|v| v.as_string()).collect::<Vec<_>>(),
+            [Some("7"), Some("bob@example.com"), Some("n/a"), Some("bob"), Some("example.com"), Some("example.com/7")]
+                .map(|v| v.map(str::to_string))
+        );
+    }
+
+    #[test]
+    fn reports_the_failing_transformer() {
+        let transformers = transformers(serde_json::json!([{"name": "cast", "params": {"column": "id", "to": "integer"}}])).unwrap();
+        let transform = RowTransform::new(&transformers, None, &["id".to_string()]).unwrap();
+        assert_eq!(transform.apply(vec![SqlValue::Text(" 12 ".to_string())]).unwrap(), [SqlValue::Int(12)]);
+        let err = transform.apply(vec![SqlValue::Text("twelve".to_string())]).unwrap_err();
+        assert!(err.starts_with("Transformer cast failed on column id"), "{}", err);
+        assert!(RowTransform::new(&transformers, None, &["name".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+// 迁移校验模块 - 比较源表与目标表的行数和与顺序无关的校验和，确认迁移后的数据一致
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use serde_with::{serde_as, TimestampMilliSeconds};
+use crate::database::{ColumnKind, ColumnSchema, SqlValue, SqlxDatabaseConnection, TableSchema, UnifiedConnectionManager};
+use crate::migration::MigrationStrategyEnum;
+use crate::migration::filter;
+use crate::migration::mapping::{self, ColumnRules, TableMapping};
+use crate::migration::strategy;
+use crate::migration::table_copy;
+use crate::migration::task_log::TaskLogger;
+use crate::migration::transform::{self, Transformer};
+
+// 单张表的校验结果
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TableVerification {
+    pub table: String,
+    // 参与比较的目标列，即按映射规则两端共有的列，不含常量列
+    pub columns: Vec<String>,
+    pub source_rows: u64,
+    pub target_rows: u64,
+    // 各行校验值之和的十六进制表示，与读取顺序无关
+    pub source_checksum: String,
+    pub target_checksum: String,
+    pub passed: bool,
+    // 无法比较时的原因，如目标表不存在
+    pub error: Option<String>,
+}
+
+impl TableVerification {
+    fn failed(table: &str, error: String) -> Self {
+        Self {
+            table: table.to_string(),
+            columns: Vec::new(),
+            source_rows: 0,
+            target_rows: 0,
+            source_checksum: String::new(),
+            target_checksum: String::new(),
+            passed: false,
+            error: Some(error),
+        }
+    }
+}
+
+// 任务的校验报告
+#[serde_as]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct VerificationReport {
+    // 全部表一致且校验过程没有出错时为true
+    pub passed: bool,
+    pub tables: Vec<TableVerification>,
+    // 校验过程出错（如连接失败）时的错误信息
+    pub error: Option<String>,
+    #[serde_as(as = "TimestampMilliSeconds")]
+    pub verified_at: SystemTime,
+    pub duration_ms: u64,
+}
+
+impl VerificationReport {
+    // 校验过程出错时的报告
+    pub fn failed(error: String) -> Self {
+        Self {
+            passed: false,
+            tables: Vec::new(),
+            error: Some(error),
+            verified_at: SystemTime::now(),
+            duration_ms: 0,
+        }
+    }
+
+    // 不一致的表数
+    pub fn mismatched(&self) -> usize {
+        self.tables.iter().filter(|t| !t.passed).count()
+    }
+}
+
+// 校验迁移任务的源库和目标库，策略不支持校验时返回错误
+pub async fn verify_migration(
+    strategy: &MigrationStrategyEnum,
+    source_db_id: &str,
+    target_db_id: &str,
+    conn_manager: Arc<UnifiedConnectionManager>,
+    logger: &TaskLogger,
+) -> Result<VerificationReport, String> {
+    let tables = strategy
+        .verification_tables()
+        .ok_or_else(|| format!("Verification is not supported for {} strategy", strategy.get_name()))?;
+    let (source_conn, target_conn) = strategy::get_sqlx_pair(source_db_id, target_db_id, &conn_manager).await?;
+    verify_tables(&source_conn, &target_conn, &tables, strategy.mappings(), strategy.transformers(), strategy.filters(), logger).await
+}
+
+// 逐表比较源库与映射后的目标表（表列表为空时比较源库全部表），源表只比较满足过滤条件的行，单张表出错不影响其他表
+pub async fn verify_tables(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    tables: &[String],
+    mappings: &[TableMapping],
+    transformers: &[Transformer],
+    filters: &BTreeMap<String, String>,
+    logger: &TaskLogger,
+) -> Result<VerificationReport, String> {
+    let started = Instant::now();
+    let schema = source_conn.introspect().await?;
+    for table in tables {
+        if schema.table(table).is_none() {
+            return Err(format!("Source table not found: {}", table));
+        }
+    }
+    let selected: Vec<_> = schema
+        .tables_in_dependency_order()
+        .into_iter()
+        .filter(|t| tables.is_empty() || tables.iter().any(|name| name.eq_ignore_ascii_case(&t.name)))
+        .collect();
+    logger.info(format!("开始校验数据，共 {} 张表", selected.len())).await;
+
+    let mut results = Vec::with_capacity(selected.len());
+    for source_table in selected {
+        let mapping = mapping::mapping_for(mappings, &source_table.name);
+        let result = match target_conn.get_table_schema(mapping.target_table()).await {
+            Ok(Some(target_table)) => {
+                let filter = filter::filter_for(filters, &source_table.name);
+                verify_table(source_conn, target_conn, source_table, &target_table, &mapping.columns, transformers, filter).await
+            }
+            Ok(None) => TableVerification::failed(&source_table.name, format!("Target table not found: {}", mapping.target_table())),
+            Err(e) => TableVerification::failed(&source_table.name, e),
+        };
+        match &result.error {
+            Some(e) => logger.error(format!("表 {} 校验失败: {}", result.table, e)).await,
+            None if result.passed => logger.info(format!("表 {} 校验一致，共 {} 行", result.table, result.source_rows)).await,
+            None => logger.error(format!(
+                "表 {} 数据不一致: 源表 {} 行，校验和 {}；目标表 {} 行，校验和 {}",
+                result.table, result.source_rows, result.source_checksum, result.target_rows, result.target_checksum
+            )).await,
+        }
+        results.push(result);
+    }
+
+    Ok(VerificationReport {
+        passed: results.iter().all(|t| t.passed),
+        tables: results,
+        error: None,
+        verified_at: SystemTime::now(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+// 比较一张表两端共有列的行数和校验和，两端同时读取；转换器改写的列不参与比较
+async fn verify_table(
+    source_conn: &SqlxDatabaseConnection,
+    target_conn: &SqlxDatabaseConnection,
+    source_table: &TableSchema,
+    target_table: &TableSchema,
+    rules: &ColumnRules,
+    transformers: &[Transformer],
+    filter: Option<&str>,
+) -> TableVerification {
+    let transformed = match transform::transformed_columns(transformers, source_table) {
+        Ok(transformed) => transformed,
+        Err(e) => return TableVerification::failed(&source_table.name, e),
+    };
+    let pairs: Vec<_> = rules
+        .column_pairs(source_table, target_table)
+        .into_iter()
+        .filter(|(s, _)| !transformed.contains(&s.name))
+        .collect();
+    if pairs.is_empty() {
+        return TableVerification::failed(&source_table.name, format!("Table {} has no columns in common with target", source_table.name));
+    }
+    // 两端的值都按源列的类型规范化，与复制时的类型转换一致
+    let kinds: Vec<ColumnKind> = pairs.iter().map(|(s, _)| s.kind).collect();
+    let source_columns: Vec<&ColumnSchema> = pairs.iter().map(|(s, _)| *s).collect();
+    let target_columns: Vec<&ColumnSchema> = pairs.iter().map(|(_, t)| *t).collect();
+
+    let (source, target) = tokio::join!(
+        table_checksum(source_conn, &source_table.name, &source_columns, &kinds, filter),
+        table_checksum(target_conn, &target_table.name, &target_columns, &kinds, None),
+    );
+    let (source, target) = match (source, target) {
+        (Ok(source), Ok(target)) => (source, target),
+        (Err(e), _) => return TableVerification::failed(&source_table.name, format!("Failed to read source table: {}", e)),
+        (_, Err(e)) => return TableVerification::failed(&source_table.name, format!("Failed to read target table: {}", e)),
+    };
+    TableVerification {
+        table: source_table.name.clone(),
+        columns: target_columns.iter().map(|c| c.name.clone()).collect(),
+        source_rows: source.rows,
+        target_rows: target.rows,
+        source_checksum: format!("{:016x}", source.sum),
+        target_checksum: format!("{:016x}", target.sum),
+        passed: source == target,
+        error: None,
+    }
+}
+
+// 一组行的行数和各行校验值之和，与读取顺序无关
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Checksum {
+    pub rows: u64,
+    pub sum: u64,
+}
+
+impl Checksum {
+    pub fn add(&mut self, row_hash: u64) {
+        self.rows += 1;
+        self.sum = self.sum.wrapping_add(row_hash);
+    }
+}
+
+// 流式读取整张表（有过滤条件时只读满足条件的行），累加每行规范化后的校验值
+async fn table_checksum(
+    conn: &SqlxDatabaseConnection,
+    table: &str,
+    columns: &[&ColumnSchema],
+    kinds: &[ColumnKind],
+    filter: Option<&str>,
+) -> Result<Checksum, String> {
+    let sql = table_copy::select_columns_sql(&conn.db_type(), table, columns) + &filter::where_clause(filter, "");
+    query_checksum(conn, sql, vec![], columns, kinds).await
+}
+
+// 流式读取查询结果并计算校验和，查询的列与columns一一对应
+pub async fn query_checksum(
+    conn: &SqlxDatabaseConnection,
+    sql: String,
+    params: Vec<SqlValue>,
+    columns: &[&ColumnSchema],
+    kinds: &[ColumnKind],
+) -> Result<Checksum, String> {
+    let db_type = conn.db_type();
+    let mut batches = conn.stream_rows(sql, params, table_copy::DEFAULT_BATCH_SIZE);
+    let mut checksum = Checksum::default();
+    while let Some(batch) = batches.recv().await {
+        for row in batch?.rows {
+            let normalized: Vec<Option<String>> = row
+                .into_iter()
+                .zip(columns)
+                .zip(kinds)
+                .map(|((value, column), kind)| normalize_value(table_copy::decode_value(&db_type, column, value), *kind))
+                .collect();
+            checksum.add(row_hash(&normalized));
+        }
+    }
+    Ok(checksum)
+}
+
+// 一行规范化后的值的校验值
+pub fn row_hash(values: &[Option<String>]) -> u64 {
+    let mut hash = FNV_OFFSET;
+    for value in values {
+        match value {
+            Some(text) => {
+                hash = fnv1a(hash, &[1]);
+                hash = fnv1a(hash, text.as_bytes());
+            }
+            None => hash = fnv1a(hash, &[0]),
+        }
+        // 字段分隔符，UTF-8文本中不会出现
+        hash = fnv1a(hash, &[0xff]);
+    }
+    mix(hash)
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+// 打散行校验值的各位，使求和后的结果分布更均匀
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58476d1ce4e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+// 把值规范化为与数据库方言无关的文本，NULL返回None
+pub fn normalize_value(value: SqlValue, kind: ColumnKind) -> Option<String> {
+    match table_copy::coerce_value(value, kind) {
+        SqlValue::Null => None,
+        SqlValue::Bool(b) => Some(if b { "1" } else { "0" }.to_string()),
+        SqlValue::Int(i) => Some(i.to_string()),
+        SqlValue::Float(f) => Some(format_float(f)),
+        SqlValue::Bytes(bytes) => Some(bytes.iter().map(|b| format!("{:02x}", b)).collect()),
+        SqlValue::Text(text) => Some(match kind {
+            ColumnKind::Decimal | ColumnKind::Float => normalize_decimal(&text),
+            ColumnKind::Json => serde_json::from_str::<serde_json::Value>(&text)
+                .map(|json| json.to_string())
+                .unwrap_or(text),
+            ColumnKind::Date | ColumnKind::Time | ColumnKind::DateTime => normalize_temporal(&text),
+            _ => text,
+        }),
+    }
+}
+
+// 浮点数按最短的可还原表示输出，整数值不带小数部分
+fn format_float(f: f64) -> String {
+    if f == 0.0 {
+        "0".to_string()
+    } else {
+        f.to_string()
+    }
+}
+
+// 定点数去掉正号和小数部分末尾的0，如 +12.50 规范为 12.5
+fn normalize_decimal(text: &str) -> String {
+    let text = text.trim();
+    let text = text.strip_prefix('+').unwrap_or(text);
+    if text.contains(['e', 'E']) {
+        return text.parse::<f64>().map(format_float).unwrap_or_else(|_| text.to_string());
+    }
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        text
+    };
+    match text {
+        "" | "-" | "-0" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+// 日期时间统一为 YYYY-MM-DD HH:MM:SS[.fff] 形式，去掉UTC时区后缀和小数部分末尾的0
+fn normalize_temporal(text: &str) -> String {
+    let mut text = text.trim().replacen('T', " ", 1);
+    for suffix in ["Z", "+00:00", "+00"] {
+        if let Some(stripped) = text.strip_suffix(suffix) {
+            text = stripped.to_string();
+            break;
+        }
+    }
+    let time_start = text.rfind(' ').map(|i| i + 1).unwrap_or(0);
+    if text[time_start..].contains('.') {
+        text = text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> SqlValue {
+        SqlValue::Text(value.to_string())
+    }
+
+    #[test]
+    fn row_hash_separates_fields_and_nulls() {
+        let some = |values: &[&str]| values.iter().map(|v| Some(v.to_string())).collect::<Vec<_>>();
+        assert_ne!(row_hash(&some(&["ab", "c"])), row_hash(&some(&["a", "bc"])));
+        assert_ne!(row_hash(&[None]), row_hash(&some(&[""])));
+        assert_ne!(row_hash(&some(&["a", "b"])), row_hash(&some(&["b", "a"])));
+        assert_eq!(row_hash(&some(&["a", "b"])), row_hash(&some(&["a", "b"])));
+    }
+
+    #[test]
+    fn checksum_ignores_row_order() {
+        let hashes = [row_hash(&[Some("1".to_string())]), row_hash(&[Some("2".to_string())]), row_hash(&[None])];
+        let mut forward = Checksum::default();
+        let mut backward = Checksum::default();
+        for hash in hashes {
+            forward.add(hash);
+        }
+        for hash in hashes.iter().rev() {
+            backward.add(*hash);
+        }
+        assert_eq!(forward, backward);
+        assert_eq!(forward.rows, 3);
+
+        let mut missing = Checksum::default();
+        missing.add(hashes[0]);
+        missing.add(hashes[1]);
+        assert_ne!(forward, missing);
+    }
+
+    #[test]
+    fn normalizes_values_across_dialects() {
+        assert_eq!(normalize_value(SqlValue::Null, ColumnKind::Text), None);
+        assert_eq!(normalize_value(SqlValue::Int(1), ColumnKind::Bool), Some("1".to_string()));
+        assert_eq!(normalize_value(text("false"), ColumnKind::Bool), Some("0".to_string()));
+        assert_eq!(normalize_value(text(" 42 "), ColumnKind::Integer), Some("42".to_string()));
+        assert_eq!(normalize_value(SqlValue::Float(2.0), ColumnKind::Integer), Some("2".to_string()));
+        assert_eq!(normalize_value(SqlValue::Float(-0.0), ColumnKind::Float), Some("0".to_string()));
+        assert_eq!(normalize_value(SqlValue::Bytes(vec![0, 171]), ColumnKind::Bytes), Some("00ab".to_string()));
+        assert_eq!(
+            normalize_value(text("{\"b\": 1,  \"a\": [1, 2]}"), ColumnKind::Json),
+            normalize_value(text("{\"a\":[1,2],\"b\":1}"), ColumnKind::Json)
+        );
+    }
+
+    #[test]
+    fn normalizes_decimals() {
+        assert_eq!(normalize_decimal("+12.50"), "12.5");
+        assert_eq!(normalize_decimal("100"), "100");
+        assert_eq!(normalize_decimal("100.000"), "100");
+        assert_eq!(normalize_decimal("-0.00"), "0");
+        assert_eq!(normalize_decimal("1.5E2"), "150");
+    }
+
+    #[test]
+    fn normalizes_temporal_values() {
+        assert_eq!(normalize_temporal("2024-01-02T03:04:05Z"), "2024-01-02 03:04:05");
+        assert_eq!(normalize_temporal("2024-01-02 03:04:05.120000+00"), "2024-01-02 03:04:05.12");
+        assert_eq!(normalize_temporal("2024-01-02 03:04:05.000"), "2024-01-02 03:04:05");
+        assert_eq!(normalize_temporal("2024-01-02"), "2024-01-02");
+        assert_eq!(normalize_temporal("10:00:00"), "10:00:00");
+    }
+}
@@ -30,10 +30,11 @@ export interface MigrationStrategy {
   batchSize?: number;
   retryCount?: number;
   timeout?: number;
-  filters?: Record<string, any>;
+  filters?: Record<string, string>;
   transformers?: Array<{
     name: string;
     params: Record<string, any>;
+    table?: string;
   }>;
 }
 